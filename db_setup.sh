@@ -25,6 +25,10 @@ CREATE TABLE IF NOT EXISTS protocol (
   protocol_name varchar(255),
   auth_token varchar(255) DEFAULT NULL,
   is_proof_repeat_allowed INT DEFAULT 0,
+  callback_url varchar(1024) DEFAULT NULL,
+  callback_secret varchar(255) DEFAULT NULL,
+  priority_tier INT DEFAULT NULL,
+  fair_share_weight INT DEFAULT 1,
   PRIMARY KEY (protocol_name)
 );
 
@@ -56,7 +60,9 @@ CREATE TABLE IF NOT EXISTS task (
   task_type INT,
   proof_hash VARCHAR(255),
   proof_id INT,
-  task_status INT
+  task_status INT,
+  leased_by VARCHAR(255) DEFAULT NULL,
+  lease_expires_at DATETIME DEFAULT NULL
 );
 
 CREATE INDEX id_task_status ON task(task_status);
@@ -79,10 +85,13 @@ CREATE TABLE IF NOT EXISTS proof (
   reducded_proof_receipt_path varchar(255) DEFAULT NULL,
   version INT DEFAULT NULL,
   cycle_used int DEFAULT NULL,
+  sp1_aggregation_attempts INT DEFAULT 0,
+  aggregation_attempts INT DEFAULT 0,
   FOREIGN KEY (user_circuit_hash) REFERENCES user_circuit_data(circuit_hash)
 );
 
 CREATE INDEX idx_proof_status ON proof(proof_status);
+CREATE INDEX idx_proof_hash ON proof(proof_hash);
 
 CREATE TABLE IF NOT EXISTS superproof (
   id INT AUTO_INCREMENT PRIMARY KEY,
@@ -110,7 +119,8 @@ CREATE TABLE IF NOT EXISTS superproof (
   version int DEFAULT NULL,
   agg_cycle_used int DEFAULT NULL,
   total_cycle_used bigint DEFAULT NULL,
-  snark_cycle_used int DEFAULT NULL
+  snark_cycle_used int DEFAULT NULL,
+  batch_close_reason int DEFAULT NULL
 );
 
 CREATE TABLE IF NOT EXISTS bonsai_image (
@@ -118,7 +128,8 @@ CREATE TABLE IF NOT EXISTS bonsai_image (
   elf_file_path varchar(255) DEFAULT NULL,
   circuit_verifying_id varchar(255) DEFAULT NULL,
   proving_scheme varchar(255) DEFAULT NULL,
-  is_aggregation_image_id int DEFAULT NULL
+  is_aggregation_image_id int DEFAULT NULL,
+  accepted_receipt_kinds varchar(255) DEFAULT NULL
 );
 
 CREATE TABLE IF NOT EXISTS proof_submission_config (
@@ -139,6 +150,47 @@ CREATE TABLE IF NOT EXISTS superproof_contract_config (
   address varchar(255) DEFAULT NULL
 );
 
+CREATE TABLE IF NOT EXISTS webhook_outbox (
+  id INT AUTO_INCREMENT PRIMARY KEY,
+  protocol_name varchar(255),
+  event_type varchar(64),
+  payload TEXT,
+  status INT,
+  attempts INT DEFAULT 0,
+  next_attempt_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+  claimed_by varchar(255) DEFAULT NULL,
+  claimed_until DATETIME DEFAULT NULL,
+  last_error TEXT DEFAULT NULL,
+  created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+  delivered_at DATETIME DEFAULT NULL,
+  FOREIGN KEY (protocol_name) REFERENCES protocol(protocol_name)
+);
+
+CREATE INDEX idx_webhook_outbox_status ON webhook_outbox(status, next_attempt_at);
+
+CREATE TABLE IF NOT EXISTS status_event (
+  id INT AUTO_INCREMENT PRIMARY KEY,
+  protocol_name varchar(255),
+  event_type varchar(64),
+  proof_hash varchar(255) DEFAULT NULL,
+  superproof_id INT DEFAULT NULL,
+  payload TEXT,
+  created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_status_event_protocol ON status_event(protocol_name, id);
+CREATE INDEX idx_status_event_proof_hash ON status_event(proof_hash);
+CREATE INDEX idx_status_event_superproof_id ON status_event(superproof_id);
+
+CREATE TABLE IF NOT EXISTS worker_lock (
+  lock_name varchar(255),
+  held_by varchar(255) DEFAULT NULL,
+  lease_expires_at DATETIME DEFAULT NULL,
+  PRIMARY KEY (lock_name)
+);
+
+INSERT IGNORE INTO worker_lock(lock_name) VALUES ('aggregator');
+
 CREATE TABLE IF NOT EXISTS cost_saved (
   total_gas_saved DECIMAL(18,2) DEFAULT 0,
   total_usd_saved DECIMAL(18,2) DEFAULT 0
@@ -148,3 +200,24 @@ INSERT INTO cost_saved VALUES (0,0);
 
 "
 
+
+# Reduction images of the gnark bls12-381 schemes. Registration of those circuits fails until their image is
+# present, set the image id, elf path and image id words (e.g. "[1,2,3,4,5,6,7,8]") of the guest build to add it.
+seed_bonsai_image() {
+  local proving_scheme=$1 image_id=$2 elf_file_path=$3 circuit_verifying_id=$4
+  if [ -z "$image_id" ]; then
+    echo "no image id set, skipping the $proving_scheme bonsai image"
+    return
+  fi
+  mysql -u"$DB_USER" -p"$DB_PASS" "$DB_NAME" -e "
+DELETE FROM bonsai_image WHERE proving_scheme = '$proving_scheme';
+INSERT INTO bonsai_image(image_id, elf_file_path, circuit_verifying_id, proving_scheme, is_aggregation_image_id, accepted_receipt_kinds)
+VALUES ('$image_id', '$elf_file_path', '$circuit_verifying_id', '$proving_scheme', 0, 'Succinct');
+"
+}
+
+seed_bonsai_image GnarkGroth16Bls12381 "$GNARK_GROTH16_BLS12_381_IMAGE_ID" "$GNARK_GROTH16_BLS12_381_ELF_PATH" "$GNARK_GROTH16_BLS12_381_VERIFYING_ID"
+seed_bonsai_image GnarkPlonkBls12381 "$GNARK_PLONK_BLS12_381_IMAGE_ID" "$GNARK_PLONK_BLS12_381_ELF_PATH" "$GNARK_PLONK_BLS12_381_VERIFYING_ID"
+
+# Halo2Ipa, Plonky3, Sp1Groth16 and Sp1Plonk have no reduction guest yet, their circuits are rejected at registration (`Scheme::SUPPORTED`).
+# GnarkPlonkBls12381 circuits are rejected too until its verifier is checked against gnark generated fixtures.
@@ -1,19 +1,51 @@
-use rocket::{serde::json::Json, catch};
+use rocket::{http::Status, serde::json::Json, catch, Request};
+use quantum_types::enums::error_code::ErrorCode;
 
-use crate::error::error::ErrorResponse;
+use crate::{error::error::ErrorResponse, types::json_body::JsonParseError};
+
+// A request body the json guard could not parse, reported with the failing field path
+fn get_invalid_payload_response(status: Status, req: &Request<'_>) -> Option<ErrorResponse> {
+    let parse_error = req.local_cache(|| None::<JsonParseError>).clone()?;
+    let mut response = ErrorResponse::new(status, ErrorCode::InvalidPayload, parse_error.message);
+    response.path = Some(parse_error.path);
+    Some(response)
+}
+
+#[catch(400)]
+pub fn bad_request(req: &Request<'_>) -> Json<ErrorResponse> {
+    Json(get_invalid_payload_response(Status::BadRequest, req).unwrap_or_else(|| {
+        ErrorResponse::new(Status::BadRequest, ErrorCode::BadRequest, "Bad Request".to_string())
+    }))
+}
+
+#[catch(401)]
+pub fn unauthorized() -> Json<ErrorResponse> {
+    Json(ErrorResponse::new(Status::Unauthorized, ErrorCode::Unauthorized, "Unauthorized".to_string()))
+}
+
+#[catch(404)]
+pub fn not_found() -> Json<ErrorResponse> {
+    Json(ErrorResponse::new(Status::NotFound, ErrorCode::NotFound, "Not Found".to_string()))
+}
+
+#[catch(413)]
+pub fn payload_too_large() -> Json<ErrorResponse> {
+    Json(ErrorResponse::new(Status::PayloadTooLarge, ErrorCode::PayloadTooLarge, "Payload Too Large".to_string()))
+}
 
 #[catch(415)]
 pub fn unsupported_media_type() -> Json<ErrorResponse> {
-    Json(ErrorResponse {
-        error_type: "Unsupported Media Type".to_string(),
-        message: "Unsupported Media Type".to_string(),
-    })
+    Json(ErrorResponse::new(Status::UnsupportedMediaType, ErrorCode::UnsupportedMediaType, "Unsupported Media Type".to_string()))
+}
+
+#[catch(422)]
+pub fn unprocessable_entity(req: &Request<'_>) -> Json<ErrorResponse> {
+    Json(get_invalid_payload_response(Status::UnprocessableEntity, req).unwrap_or_else(|| {
+        ErrorResponse::new(Status::UnprocessableEntity, ErrorCode::InvalidPayload, "Unprocessable Entity".to_string())
+    }))
 }
 
 #[catch(500)]
 pub fn internal_server_error() -> Json<ErrorResponse> {
-    Json(ErrorResponse {
-        error_type: "Internal Server Error".to_string(),
-        message: "Internal Server Error".to_string(),
-    })
-}
\ No newline at end of file
+    Json(ErrorResponse::new(Status::InternalServerError, ErrorCode::InternalError, "Internal Server Error".to_string()))
+}
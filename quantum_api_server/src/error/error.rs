@@ -1,4 +1,6 @@
-use serde::Serialize;
+use quantum_db::error::error::CustomError as DBError;
+use quantum_types::{enums::error_code::ErrorCode, types::error::CodedError};
+use serde::{Deserialize, Serialize};
 use tracing::info;
 use std::io::Cursor;
 use rocket::http::Status;
@@ -7,10 +9,25 @@ use rocket::response::{self, Response, Responder};
 use rocket::http::ContentType;
 //use core::resp::Error;
 
-#[derive(Serialize)]
+#[derive(Serialize, Deserialize, Debug)]
 pub struct ErrorResponse {
     pub error_type: String,
+    pub error_code: ErrorCode,
     pub message: String,
+    // json path of the offending request body field, only set for request body errors
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+}
+
+impl ErrorResponse {
+    pub fn new(status: Status, error_code: ErrorCode, message: String) -> Self {
+        ErrorResponse {
+            error_type: status.to_string(),
+            error_code,
+            message,
+            path: None,
+        }
+    }
 }
 
 //#[derive(Error)]
@@ -24,14 +41,82 @@ pub enum CustomError {
 
     //#[resp("{0}")]
     BadRequest(String),
+
+    InvalidPayload(String),
+    PayloadTooLarge(String),
+    UnsupportedMediaType(String),
+    Unauthorized(String),
+    CircuitNotRegistered(String),
+    CircuitReductionNotCompleted(String),
+    SchemeMismatch(String),
+    UnsupportedScheme(String),
+    ProofDuplicate(String),
+    ProofInvalid(String),
+    PisInvalid(String),
+    VkeyInvalid(String),
+    ProofNotVerified(String),
+    ProtocolAlreadyRegistered(String),
+    Database(String),
 }
 
 impl CustomError {
+    pub fn from_code(code: ErrorCode, message: String) -> Self {
+        match code {
+            ErrorCode::BadRequest => CustomError::BadRequest(message),
+            ErrorCode::InvalidPayload => CustomError::InvalidPayload(message),
+            ErrorCode::PayloadTooLarge => CustomError::PayloadTooLarge(message),
+            ErrorCode::UnsupportedMediaType => CustomError::UnsupportedMediaType(message),
+            ErrorCode::Unauthorized => CustomError::Unauthorized(message),
+            ErrorCode::NotFound => CustomError::NotFound(message),
+            ErrorCode::CircuitNotRegistered => CustomError::CircuitNotRegistered(message),
+            ErrorCode::CircuitReductionNotCompleted => CustomError::CircuitReductionNotCompleted(message),
+            ErrorCode::SchemeMismatch => CustomError::SchemeMismatch(message),
+            ErrorCode::UnsupportedScheme => CustomError::UnsupportedScheme(message),
+            ErrorCode::ProofDuplicate => CustomError::ProofDuplicate(message),
+            ErrorCode::ProofInvalid => CustomError::ProofInvalid(message),
+            ErrorCode::PisInvalid => CustomError::PisInvalid(message),
+            ErrorCode::VkeyInvalid => CustomError::VkeyInvalid(message),
+            ErrorCode::ProofNotVerified => CustomError::ProofNotVerified(message),
+            ErrorCode::ProtocolAlreadyRegistered => CustomError::ProtocolAlreadyRegistered(message),
+            ErrorCode::DatabaseError => CustomError::Database(message),
+            ErrorCode::InternalError => CustomError::Internal(message),
+        }
+    }
+
+    pub fn error_code(&self) -> ErrorCode {
+        match self {
+            CustomError::Internal(_) => ErrorCode::InternalError,
+            CustomError::NotFound(_) => ErrorCode::NotFound,
+            CustomError::BadRequest(_) => ErrorCode::BadRequest,
+            CustomError::InvalidPayload(_) => ErrorCode::InvalidPayload,
+            CustomError::PayloadTooLarge(_) => ErrorCode::PayloadTooLarge,
+            CustomError::UnsupportedMediaType(_) => ErrorCode::UnsupportedMediaType,
+            CustomError::Unauthorized(_) => ErrorCode::Unauthorized,
+            CustomError::CircuitNotRegistered(_) => ErrorCode::CircuitNotRegistered,
+            CustomError::CircuitReductionNotCompleted(_) => ErrorCode::CircuitReductionNotCompleted,
+            CustomError::SchemeMismatch(_) => ErrorCode::SchemeMismatch,
+            CustomError::UnsupportedScheme(_) => ErrorCode::UnsupportedScheme,
+            CustomError::ProofDuplicate(_) => ErrorCode::ProofDuplicate,
+            CustomError::ProofInvalid(_) => ErrorCode::ProofInvalid,
+            CustomError::PisInvalid(_) => ErrorCode::PisInvalid,
+            CustomError::VkeyInvalid(_) => ErrorCode::VkeyInvalid,
+            CustomError::ProofNotVerified(_) => ErrorCode::ProofNotVerified,
+            CustomError::ProtocolAlreadyRegistered(_) => ErrorCode::ProtocolAlreadyRegistered,
+            CustomError::Database(_) => ErrorCode::DatabaseError,
+        }
+    }
+
     fn get_http_status(&self) -> Status {
         match self {
-            CustomError::Internal(_) => Status::InternalServerError,
-            CustomError::NotFound(_) => Status::NotFound,
-            _ => Status::BadRequest,
+            CustomError::Internal(_) | CustomError::Database(_) => Status::InternalServerError,
+            CustomError::NotFound(_) | CustomError::CircuitNotRegistered(_) => Status::NotFound,
+            CustomError::Unauthorized(_) => Status::Unauthorized,
+            CustomError::ProofDuplicate(_) | CustomError::ProtocolAlreadyRegistered(_)
+                | CustomError::CircuitReductionNotCompleted(_) | CustomError::ProofNotVerified(_) => Status::Conflict,
+            CustomError::ProofInvalid(_) | CustomError::PisInvalid(_) | CustomError::VkeyInvalid(_) => Status::UnprocessableEntity,
+            CustomError::BadRequest(_) | CustomError::InvalidPayload(_) | CustomError::SchemeMismatch(_) | CustomError::UnsupportedScheme(_) => Status::BadRequest,
+            CustomError::PayloadTooLarge(_) => Status::PayloadTooLarge,
+            CustomError::UnsupportedMediaType(_) => Status::UnsupportedMediaType,
         }
     }
 }
@@ -39,7 +124,11 @@ impl CustomError {
 impl std::fmt::Display for CustomError {
     fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
         let error_message = match &self{
-            Self::Internal(str) | Self::BadRequest(str) | Self::NotFound(str) => {
+            Self::Internal(str) | Self::BadRequest(str) | Self::NotFound(str) | Self::InvalidPayload(str)
+            | Self::PayloadTooLarge(str) | Self::UnsupportedMediaType(str) | Self::Unauthorized(str)
+            | Self::CircuitNotRegistered(str) | Self::CircuitReductionNotCompleted(str) | Self::SchemeMismatch(str)
+            | Self::UnsupportedScheme(str) | Self::ProofDuplicate(str) | Self::ProofInvalid(str) | Self::PisInvalid(str)
+            | Self::VkeyInvalid(str) | Self::ProofNotVerified(str) | Self::ProtocolAlreadyRegistered(str) | Self::Database(str) => {
                 info!("Error is: {}", str);
                 str
             }
@@ -54,10 +143,11 @@ impl<'r> Responder<'r, 'static> for CustomError {
     fn respond_to(self, _: &'r Request<'_>) -> response::Result<'static> {
         // serialize struct into json string
 
-        let err_response = serde_json::to_string(&ErrorResponse{
-            error_type: self.get_http_status().to_string(),
-            message: self.to_string(),
-        }).unwrap();
+        let err_response = serde_json::to_string(&ErrorResponse::new(
+            self.get_http_status(),
+            self.error_code(),
+            self.to_string(),
+        )).unwrap();
 
         Response::build()
             .status(self.get_http_status())
@@ -67,8 +157,24 @@ impl<'r> Responder<'r, 'static> for CustomError {
     }
 }
 
+/*
+    Picks the most specific typed error in the chain: an api `CustomError`, then a `CodedError` tag
+    (e.g. from the proof/vkey validators), then a db error. Anything untyped is an internal error.
+ */
 impl From<anyhow::Error> for CustomError {
     fn from(error: anyhow::Error) -> Self {
-        CustomError::Internal(error.to_string())
+        if let Some(e) = error.downcast_ref::<CustomError>() {
+            return e.clone();
+        }
+        if let Some(e) = error.downcast_ref::<CodedError>() {
+            return CustomError::from_code(e.code, e.message.clone());
+        }
+        if let Some(e) = error.downcast_ref::<DBError>() {
+            return match e {
+                DBError::NotFound(_) => CustomError::NotFound(e.to_string()),
+                DBError::DB(_) => CustomError::Database(e.to_string()),
+            };
+        }
+        CustomError::Internal(error.root_cause().to_string())
     }
-}
\ No newline at end of file
+}
@@ -2,13 +2,13 @@ use std::env;
 
 use connection::get_pool;
 use dotenv::dotenv;
-use quantum_api_server::{catcher, connection, routes::{self, protocol_proof::get_protocol_proof}};
+use quantum_api_server::{catcher, connection, routes::{self, events::{get_proof_events, get_protocol_events}, protocol_callback::register_callback, protocol_proof::get_protocol_proof}};
 use quantum_types::types::config::ConfigData;
 use quantum_utils::logger::initialize_logger;
 use quantum_types;
 use rocket::data::{Limits, ToByteUnit};
-use routes::{ping::ping, register_circuit::register_circuit, circuit_reduction::get_circuit_reduction_status, proof::{submit_proof, get_proof_status}, auth_protocol::generate_auth_token, index::index};
-use catcher::{unsupported_media_type, internal_server_error};
+use routes::{ping::ping, register_circuit::register_circuit, circuit_reduction::get_circuit_reduction_status, proof::{submit_proof, submit_proof_batch, get_proof_status}, auth_protocol::generate_auth_token, index::index};
+use catcher::{bad_request, unauthorized, not_found, payload_too_large, unsupported_media_type, unprocessable_entity, internal_server_error};
 
 #[macro_use] extern crate rocket;
 
@@ -38,6 +38,6 @@ async fn rocket() -> _ {
     let t = rocket::Config::figment();
     // .merge(("limits", limits));
     rocket::custom(t).manage(config_data).manage(_guard)
-    .mount("/", routes![index, ping, register_circuit, get_circuit_reduction_status, submit_proof, get_proof_status, generate_auth_token, get_protocol_proof]).attach(cors)
-    .register("/", catchers![unsupported_media_type, internal_server_error])
+    .mount("/", routes![index, ping, register_circuit, get_circuit_reduction_status, submit_proof, submit_proof_batch, get_proof_status, generate_auth_token, get_protocol_proof, register_callback, get_proof_events, get_protocol_events]).attach(cors)
+    .register("/", catchers![bad_request, unauthorized, not_found, payload_too_large, unsupported_media_type, unprocessable_entity, internal_server_error])
 }
\ No newline at end of file
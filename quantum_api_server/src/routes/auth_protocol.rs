@@ -11,7 +11,7 @@ pub async fn generate_auth_token(data: GenerateAuthTokenRequest) -> AnyhowResult
         Ok(r) => Ok(Json(r)),
         Err(e) => {
             error!("Error in auth/protocol: {:?}", e);
-            Err(CustomError::Internal(e.root_cause().to_string()))
+            Err(CustomError::from(e))
         }
     }
 }
\ No newline at end of file
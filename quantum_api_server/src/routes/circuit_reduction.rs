@@ -10,7 +10,7 @@ pub async fn get_circuit_reduction_status(_auth_token: AuthToken, circuit_id: St
         Ok(s) => Ok(Json(s)),
         Err(e) => {
             error!("Error in /circuit/<circuit_id>/status: {:?}",e);
-            Err(CustomError::Internal(e.root_cause().to_string()))
+            Err(CustomError::from(e))
         }
     }
 }
\ No newline at end of file
@@ -0,0 +1,199 @@
+use std::{collections::BTreeSet, time::Duration};
+
+use anyhow::Result as AnyhowResult;
+use quantum_db::repository::{proof_repository::get_proof_by_proof_hash, protocol::get_protocol_by_auth_token, status_event_repository::{get_latest_status_event_id, get_proof_status_events, get_protocol_status_events}};
+use quantum_types::types::{config::ConfigData, db::status_event::StatusEvent};
+use quantum_utils::error_line;
+use rocket::{get, response::stream::{Event, EventStream}, tokio::{select, time::sleep}, Shutdown, State};
+use tracing::error;
+
+use crate::{connection::get_pool, error::error::CustomError, types::{auth::AuthToken, events::LastEventId}};
+
+const EVENT_BATCH_SIZE: u64 = 100;
+
+/*
+    Event ids are AUTO_INCREMENT values taken at insert, so an event can commit after a higher id was already streamed.
+    Every poll re-reads the `resume_window` ids below the last streamed one and only sends the events not sent yet.
+    An event is streamed as long as it commits before `resume_window` higher ids were streamed, later ones are missed,
+    so the window (`event_stream_resume_window`) has to cover the events inserted while the longest transaction is open.
+    After a reconnect the window is sent again, clients dedupe on the event id.
+ */
+struct StreamCursor {
+    last_event_id: u64,
+    resume_window: u64,
+    sent_event_ids: BTreeSet<u64>,
+}
+
+impl StreamCursor {
+    fn new(last_event_id: u64, resume_window: u64) -> Self {
+        StreamCursor { last_event_id, resume_window, sent_event_ids: BTreeSet::new() }
+    }
+
+    fn window_start(&self) -> u64 {
+        self.last_event_id.saturating_sub(self.resume_window)
+    }
+
+    fn take_unsent(&mut self, status_events: Vec<StatusEvent>) -> Vec<StatusEvent> {
+        let unsent: Vec<StatusEvent> = status_events.into_iter().filter(|e| self.sent_event_ids.insert(e.id)).collect();
+        if let Some(max_id) = unsent.iter().map(|e| e.id).max() {
+            self.last_event_id = std::cmp::max(self.last_event_id, max_id);
+        }
+        // ids below the window are never read again
+        self.sent_event_ids = self.sent_event_ids.split_off(&(self.window_start() + 1));
+        unsent
+    }
+}
+
+fn get_sse_event(status_event: StatusEvent) -> Event {
+    Event::data(status_event.payload)
+        .event(status_event.event_type)
+        .id(status_event.id.to_string())
+}
+
+/*
+    Streams the status transitions of a proof, including the ones of its superproof.
+    Without a last event id the full history of the proof is replayed first.
+ */
+#[get("/proof/<proof_hash>/events?<last_event_id>")]
+pub async fn get_proof_events(_auth_token: AuthToken, proof_hash: String, last_event_id: Option<u64>, last_event_id_header: LastEventId, config_data: &State<ConfigData>, mut shutdown: Shutdown) -> AnyhowResult<EventStream![], CustomError> {
+    if get_proof_by_proof_hash(get_pool().await, &proof_hash).await.is_err() {
+        return Err(CustomError::NotFound(error_line!(format!("proof not found: {}", proof_hash))));
+    }
+
+    // the header is set by EventSource on reconnect, so it wins over the query param of the original url
+    let mut cursor = StreamCursor::new(last_event_id_header.0.or(last_event_id).unwrap_or(0), config_data.event_stream_resume_window);
+    let poll_interval = Duration::from_millis(config_data.event_stream_poll_millis);
+    Ok(EventStream! {
+        loop {
+            let mut has_more = false;
+            match get_proof_status_events(get_pool().await, &proof_hash, cursor.window_start(), cursor.last_event_id, cursor.resume_window).await {
+                Ok(status_events) => {
+                    for status_event in cursor.take_unsent(status_events) {
+                        yield get_sse_event(status_event);
+                    }
+                }
+                Err(e) => error!("error in fetching late status events of proof {}: {:?}", proof_hash, e),
+            }
+            match get_proof_status_events(get_pool().await, &proof_hash, cursor.last_event_id, u64::MAX, EVENT_BATCH_SIZE).await {
+                Ok(status_events) => {
+                    has_more = status_events.len() as u64 == EVENT_BATCH_SIZE;
+                    for status_event in cursor.take_unsent(status_events) {
+                        yield get_sse_event(status_event);
+                    }
+                }
+                Err(e) => error!("error in fetching status events of proof {}: {:?}", proof_hash, e),
+            }
+            if has_more {
+                continue;
+            }
+            select! {
+                _ = sleep(poll_interval) => {},
+                _ = &mut shutdown => break,
+            }
+        }
+    })
+}
+
+// Streams the status transitions of every proof and superproof of the caller's protocol
+#[get("/events?<last_event_id>")]
+pub async fn get_protocol_events(_auth_token: AuthToken, last_event_id: Option<u64>, last_event_id_header: LastEventId, config_data: &State<ConfigData>, mut shutdown: Shutdown) -> AnyhowResult<EventStream![], CustomError> {
+    let protocol = match get_protocol_by_auth_token(get_pool().await, &_auth_token.0).await? {
+        Some(p) => p,
+        None => {
+            error!("No protocol against this auth token");
+            return Err(CustomError::Unauthorized(error_line!("/events No protocol against this auth token".to_string())));
+        }
+    };
+
+    // without a last event id only transitions from now on are streamed
+    let mut cursor = StreamCursor::new(match last_event_id_header.0.or(last_event_id) {
+        Some(id) => id,
+        None => get_latest_status_event_id(get_pool().await).await?,
+    }, config_data.event_stream_resume_window);
+    let poll_interval = Duration::from_millis(config_data.event_stream_poll_millis);
+    let protocol_name = protocol.protocol_name;
+    Ok(EventStream! {
+        loop {
+            let mut has_more = false;
+            match get_protocol_status_events(get_pool().await, &protocol_name, cursor.window_start(), cursor.last_event_id, cursor.resume_window).await {
+                Ok(status_events) => {
+                    for status_event in cursor.take_unsent(status_events) {
+                        yield get_sse_event(status_event);
+                    }
+                }
+                Err(e) => error!("error in fetching late status events of protocol {}: {:?}", protocol_name, e),
+            }
+            match get_protocol_status_events(get_pool().await, &protocol_name, cursor.last_event_id, u64::MAX, EVENT_BATCH_SIZE).await {
+                Ok(status_events) => {
+                    has_more = status_events.len() as u64 == EVENT_BATCH_SIZE;
+                    for status_event in cursor.take_unsent(status_events) {
+                        yield get_sse_event(status_event);
+                    }
+                }
+                Err(e) => error!("error in fetching status events of protocol {}: {:?}", protocol_name, e),
+            }
+            if has_more {
+                continue;
+            }
+            select! {
+                _ = sleep(poll_interval) => {},
+                _ = &mut shutdown => break,
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RESUME_WINDOW: u64 = 200;
+
+    fn status_event(id: u64) -> StatusEvent {
+        StatusEvent {
+            id,
+            protocol_name: String::from("electron"),
+            event_type: String::from("proof_status"),
+            proof_hash: None,
+            superproof_id: None,
+            payload: String::from("{}"),
+        }
+    }
+
+    fn ids(status_events: &Vec<StatusEvent>) -> Vec<u64> {
+        status_events.iter().map(|e| e.id).collect()
+    }
+
+    #[test]
+    fn test_late_committed_event_is_sent_once() {
+        let mut cursor = StreamCursor::new(0, RESUME_WINDOW);
+        // id 2 is not committed yet when 1 and 3 are polled
+        assert_eq!(ids(&cursor.take_unsent(vec![status_event(1), status_event(3)])), vec![1, 3]);
+        assert_eq!(cursor.last_event_id, 3);
+        assert_eq!(cursor.window_start(), 0);
+
+        // the next window read sees it, the already sent ones are skipped
+        assert_eq!(ids(&cursor.take_unsent(vec![status_event(1), status_event(2), status_event(3)])), vec![2]);
+        assert_eq!(ids(&cursor.take_unsent(vec![status_event(1), status_event(2), status_event(3)])), Vec::<u64>::new());
+        assert_eq!(cursor.last_event_id, 3);
+    }
+
+    #[test]
+    fn test_sent_ids_below_the_window_are_dropped() {
+        let mut cursor = StreamCursor::new(0, RESUME_WINDOW);
+        cursor.take_unsent(vec![status_event(1), status_event(RESUME_WINDOW + 5)]);
+        assert_eq!(cursor.window_start(), 5);
+        assert_eq!(cursor.sent_event_ids.iter().cloned().collect::<Vec<u64>>(), vec![RESUME_WINDOW + 5]);
+    }
+
+    #[test]
+    fn test_event_committed_after_the_window_is_missed() {
+        let mut cursor = StreamCursor::new(0, 2);
+        cursor.take_unsent(vec![status_event(1), status_event(4)]);
+        assert_eq!(cursor.window_start(), 2);
+        // ids 2 and 3 commit late, only 3 is still inside the window that gets re-read
+        let window_read: Vec<StatusEvent> = vec![status_event(2), status_event(3), status_event(4)]
+            .into_iter().filter(|e| e.id > cursor.window_start() && e.id <= cursor.last_event_id).collect();
+        assert_eq!(ids(&cursor.take_unsent(window_read)), vec![3]);
+    }
+}
@@ -1,7 +1,9 @@
 pub mod auth_protocol;
 pub mod circuit_reduction;
+pub mod events;
 pub mod index;
 pub mod ping;
 pub mod proof;
 pub mod protocol_proof;
+pub mod protocol_callback;
 pub mod register_circuit;
\ No newline at end of file
@@ -0,0 +1,33 @@
+use anyhow::Result as AnyhowResult;
+use quantum_db::{repository::protocol::get_protocol_by_auth_token, webhook::check_callback_url};
+use quantum_utils::error_line;
+use rocket::post;
+use rocket::serde::json::Json;
+use tracing::error;
+
+use crate::{connection::get_pool, error::error::CustomError, service::protocol::register_protocol_callback, types::{auth::AuthToken, protocol_callback::{RegisterCallbackRequest, RegisterCallbackResponse}}};
+
+#[post("/protocol/callback", data = "<data>")]
+pub async fn register_callback(_auth_token: AuthToken, data: RegisterCallbackRequest) -> AnyhowResult<Json<RegisterCallbackResponse>, CustomError> {
+    let protocol = get_protocol_by_auth_token(get_pool().await, &_auth_token.0).await?;
+    let protocol = match protocol {
+        Some(p) => p,
+        None => {
+            error!("No protocol against this auth token");
+            return Err(CustomError::Unauthorized(error_line!("/protocol/callback No protocol against this auth token".to_string())));
+        },
+    };
+
+    if let Err(e) = check_callback_url(&data.callback_url).await {
+        return Err(CustomError::BadRequest(error_line!(e.to_string())));
+    }
+
+    let response = register_protocol_callback(&protocol.protocol_name, &data.callback_url).await;
+    match response {
+        Ok(r) => Ok(Json(r)),
+        Err(e) => {
+            error!("Error in /protocol/callback: {:?}", e);
+            Err(CustomError::from(e))
+        }
+    }
+}
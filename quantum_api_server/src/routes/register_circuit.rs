@@ -1,6 +1,6 @@
 use anyhow::Result as AnyhowResult;
 use quantum_db::repository::protocol::get_protocol_by_auth_token;
-use quantum_types::{enums::proving_schemes::ProvingSchemes, types::{config::ConfigData, gnark_groth16::GnarkGroth16Vkey, gnark_plonk::GnarkPlonkVkey, halo2_plonk::Halo2PlonkVkey, halo2_poseidon::Halo2PoseidonVkey, plonk2::Plonky2Vkey, riscs0::Risc0Vkey, snarkjs_groth16::SnarkJSGroth16Vkey, sp1::Sp1Vkey, nitro_att::NitroAttVkey}};
+use quantum_types::{dispatch_scheme, types::config::ConfigData};
 use quantum_utils::error_line;
 use rocket::post;
 use rocket::serde::json::Json;
@@ -11,12 +11,11 @@ use crate::{connection::get_pool, error::error::CustomError, service::register_c
 
 #[post("/register_circuit", data = "<data>")]
 pub async fn register_circuit(auth_token: AuthToken, data: RegisterCircuitRequest, config_data: &State<ConfigData>) -> AnyhowResult<Json<RegisterCircuitResponse>, CustomError> {
-    let response: AnyhowResult<RegisterCircuitResponse>;
     let protocol = match get_protocol_by_auth_token(get_pool().await, &auth_token.0).await {
         Ok(p) => Ok(p),
         Err(e) => {
             error!("error in db while fetching protocol");
-            Err(CustomError::Internal(error_line!(format!("error in db while fetching protocol. Error: {}", e))))
+            Err(CustomError::from(e))
         },
     };
     let protocol = protocol?;
@@ -25,39 +24,21 @@ pub async fn register_circuit(auth_token: AuthToken, data: RegisterCircuitReques
         Some(p) => Ok(p),
         None => {
             error!("No protocol against this auth token");
-            Err(CustomError::Internal(error_line!("/register_circuit No protocol against this auth token".to_string())))
+            Err(CustomError::Unauthorized(error_line!("/register_circuit No protocol against this auth token".to_string())))
         },
     };
     let protocol = protocol?;
 
     info!("{:?}", protocol);
 
-    if data.proof_type == ProvingSchemes::GnarkGroth16 {
-        response = register_circuit_exec::<GnarkGroth16Vkey>(data, config_data, protocol).await;
-    } else if data.proof_type == ProvingSchemes::Groth16 {
-        response = register_circuit_exec::<SnarkJSGroth16Vkey>(data, config_data, protocol).await;
-    } else if data.proof_type == ProvingSchemes::Halo2Plonk {
-        response = register_circuit_exec::<Halo2PlonkVkey>(data, config_data, protocol).await;
-    } else if data.proof_type == ProvingSchemes::GnarkPlonk {
-        response = register_circuit_exec::<GnarkPlonkVkey>(data, config_data, protocol).await;
-    }else if data.proof_type == ProvingSchemes::Plonky2 {
-        response = register_circuit_exec::<Plonky2Vkey>(data, config_data, protocol).await;
-    } else if data.proof_type == ProvingSchemes::Halo2Poseidon {
-        response = register_circuit_exec::<Halo2PoseidonVkey>(data, config_data, protocol).await;
-    }else if data.proof_type == ProvingSchemes::Sp1 {
-        response = register_circuit_exec::<Sp1Vkey>(data, config_data, protocol).await;
-    }else if data.proof_type == ProvingSchemes::Risc0 {
-        response = register_circuit_exec::<Risc0Vkey>(data, config_data, protocol).await;
-    }else if data.proof_type == ProvingSchemes::NitroAtt {
-        response = register_circuit_exec::<NitroAttVkey>(data, config_data, protocol).await;
-    } else {
-        return Err(CustomError::Internal(String::from("Unsupported Proving Scheme")))
-    }
+    let response = dispatch_scheme!(data.proof_type, S => {
+        register_circuit_exec::<S>(data, config_data, protocol).await
+    });
     match response {
         Ok(resp)  => Ok(Json(resp)),
         Err(e) => {
             error!("Error in /register_circuit: {:?}", e);
-            Err(CustomError::Internal(e.root_cause().to_string()))
+            Err(CustomError::from(e))
         }
     }
 }
\ No newline at end of file
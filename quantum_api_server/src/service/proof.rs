@@ -1,12 +1,13 @@
-use std::str::FromStr;
+use std::{collections::HashSet, str::FromStr};
 
 use crate::{
     connection::get_pool,
     error::error::CustomError,
+    service::register_circuit::get_registered_circuit_data,
     types::{
         proof_data::ProofDataResponse,
         protocol_proof::ProtocolProofResponse,
-        submit_proof::{SubmitProofRequest, SubmitProofResponse},
+        submit_proof::{SubmitProofBatchItemResponse, SubmitProofBatchRequest, SubmitProofBatchResponse, SubmitProofRequest, SubmitProofResponse},
     },
 };
 use agg_core::inputs::{compute_combined_vkey_hash, compute_leaf_value};
@@ -17,16 +18,17 @@ use quantum_db::repository::proof_repository::get_proof_by_proof_hash;
 use quantum_db::repository::protocol::get_protocol_by_protocol_name;
 use quantum_db::repository::{
     bonsai_image::get_bonsai_image_by_image_id,
-    proof_repository::{get_latest_proof_by_circuit_hash, insert_proof},
+    proof_repository::{get_latest_proof_by_circuit_hash, insert_proof, insert_proofs_with_tasks, NewProof},
     superproof_repository::get_superproof_by_id,
     task_repository::create_proof_task,
     user_circuit_data_repository::get_user_circuit_data_by_circuit_hash,
 };
-use quantum_types::{enums::proving_schemes::ProvingSchemes, types::db::proof::Proof as DbProof};
+use quantum_types::{enums::error_code::ErrorCode, types::error::WithErrorCode, types::db::{proof::Proof as DbProof, protocol::Protocol, user_circuit_data::UserCircuitData}};
+use quantum_types::schemes::Scheme;
 use quantum_types::{
     enums::{
         circuit_reduction_status::CircuitReductionStatus, proof_status::ProofStatus,
-        task_status::TaskStatus, task_type::TaskType,
+        risc0_receipt_kind::Risc0ReceiptKind, task_status::TaskStatus, task_type::TaskType,
     },
     traits::{pis::Pis, proof::Proof, vkey::Vkey},
     types::config::ConfigData,
@@ -42,28 +44,27 @@ use utils::hash::{Hasher, KeccakHasher};
 // use imt_core::types::Leaf;
 use tiny_merkle::proof::Position;
 
-pub async fn submit_proof_exec<T: Proof, F: Pis, V: Vkey>(
-    data: SubmitProofRequest,
+pub async fn submit_proof_exec<S: Scheme>(
+    mut data: SubmitProofRequest,
     config_data: &State<ConfigData>,
 ) -> AnyhowResult<SubmitProofResponse> {
     validate_circuit_data_in_submit_proof_request(&data).await?;
 
-    let proof: T = T::deserialize_proof(&mut data.proof.as_slice())?;
-    let pis: F = F::deserialize_pis(&mut data.pis.as_slice())?;
+    // the scheme is checked above, so the vkey is read with the scheme it was registered with
+    let user_circuit_data = get_registered_circuit_data(&data.circuit_hash).await?;
+    set_pis_from_proof::<S>(&mut data, &user_circuit_data.vk_path).with_error_code(ErrorCode::ProofInvalid)?;
 
-    let user_circuit_data =
-        get_user_circuit_data_by_circuit_hash(get_pool().await, &data.circuit_hash).await?;
-    let user_vk = V::read_vk(&user_circuit_data.vk_path)?;
+    let proof = S::Proof::deserialize_proof(&mut data.proof.as_slice()).with_error_code(ErrorCode::ProofInvalid)?;
+    let pis = S::Pis::deserialize_pis(&mut data.pis.as_slice()).with_error_code(ErrorCode::PisInvalid)?;
 
-    let proof_id_hash;
-    if data.proof_type != ProvingSchemes::Sp1 {
-        proof_id_hash = KeccakHasher::combine_hash(&user_vk.keccak_hash()?, &pis.keccak_hash()?);
-    } else {
-        proof_id_hash = KeccakHasher::hash_out(&proof.get_proof_bytes()?);
-    }
+    let user_vk = S::Vkey::read_vk(&user_circuit_data.vk_path)?;
 
+    let proof_id_hash = KeccakHasher::combine_hash(&user_vk.keccak_hash()?, &pis.keccak_hash()?);
     let proof_hash = encode_keccak_hash(&proof_id_hash)?;
-    proof.validate_proof(&user_circuit_data.vk_path, &data.pis.clone())?;
+    proof
+        .validate_proof(&user_circuit_data.vk_path, &data.pis.clone())
+        .with_error_code(ErrorCode::ProofInvalid)?;
+    check_receipt_kind_accepted::<S>(&proof, &user_circuit_data.bonsai_image_id).await?;
     info!("proof validated");
     check_if_proof_already_exist(&proof_hash, &data.circuit_hash).await?;
 
@@ -89,32 +90,265 @@ pub async fn submit_proof_exec<T: Proof, F: Pis, V: Vkey>(
         &proof_hash,
         &pis_full_path,
         &proof_full_path,
-        if data.proof_type == ProvingSchemes::Sp1 {
-            ProofStatus::Reduced
-        } else {
-            ProofStatus::Registered
-        },
+        ProofStatus::Registered,
         &data.circuit_hash,
         &public_inputs_json_string,
     )
     .await?;
-    if data.proof_type != ProvingSchemes::Sp1 {
-        create_proof_task(
-            get_pool().await,
-            &data.circuit_hash,
-            TaskType::ProofGeneration,
-            TaskStatus::NotPicked,
-            &proof_hash,
-            proof_id,
-        )
-        .await?;
-    }
+    create_proof_task(
+        get_pool().await,
+        &data.circuit_hash,
+        TaskType::ProofGeneration,
+        TaskStatus::NotPicked,
+        &proof_hash,
+        proof_id,
+    )
+    .await?;
 
     Ok(SubmitProofResponse {
         proof_id: proof_hash,
     })
 }
 
+// Risc0 receipts must be of a kind the circuit's bonsai image accepts, other schemes have no receipt kind
+async fn check_receipt_kind_accepted<S: Scheme>(proof: &S::Proof, bonsai_image_id: &str) -> AnyhowResult<()> {
+    let receipt_kind = match S::get_receipt_kind(proof).with_error_code(ErrorCode::ProofInvalid)? {
+        Some(kind) => kind,
+        None => return Ok(()),
+    };
+    let bonsai_image = get_bonsai_image_by_image_id(get_pool().await, bonsai_image_id).await?;
+    check_receipt_kind(receipt_kind, &bonsai_image.accepted_receipt_kinds)
+}
+
+fn check_receipt_kind(receipt_kind: Risc0ReceiptKind, accepted_receipt_kinds: &[Risc0ReceiptKind]) -> AnyhowResult<()> {
+    if !accepted_receipt_kinds.contains(&receipt_kind) {
+        let accepted_kinds: Vec<String> = accepted_receipt_kinds.iter().map(|kind| kind.to_string()).collect();
+        return Err(anyhow!(CustomError::ProofInvalid(error_line!(format!(
+            "{} risc0 receipts are not accepted for this circuit, accepted kinds: {}",
+            receipt_kind.to_string(),
+            accepted_kinds.join(", ")
+        )))));
+    }
+    Ok(())
+}
+
+// Some schemes carry their public inputs inside the proof, extract them into `data.pis`
+fn set_pis_from_proof<S: Scheme>(data: &mut SubmitProofRequest, vk_path: &str) -> AnyhowResult<()> {
+    if S::PIS_IN_PROOF {
+        let proof = S::Proof::deserialize_proof(&mut data.proof.as_slice())?;
+        data.pis = S::extract_pis(&proof, vk_path)?.serialize_pis()?;
+    }
+    Ok(())
+}
+
+struct ValidatedBatchProof<S: Scheme> {
+    proof: S::Proof,
+    pis: S::Pis,
+    proof_hash: String,
+}
+
+fn validate_batch_proof<S: Scheme>(
+    mut data: SubmitProofRequest,
+    user_vk_hash: [u8; 32],
+    vk_path: &str,
+) -> AnyhowResult<ValidatedBatchProof<S>> {
+    set_pis_from_proof::<S>(&mut data, vk_path).with_error_code(ErrorCode::ProofInvalid)?;
+    let proof = S::Proof::deserialize_proof(&mut data.proof.as_slice()).with_error_code(ErrorCode::ProofInvalid)?;
+    let pis = S::Pis::deserialize_pis(&mut data.pis.as_slice()).with_error_code(ErrorCode::PisInvalid)?;
+
+    let proof_id_hash = KeccakHasher::combine_hash(&user_vk_hash, &pis.keccak_hash()?);
+    let proof_hash = encode_keccak_hash(&proof_id_hash)?;
+
+    proof.validate_proof(vk_path, &data.pis).with_error_code(ErrorCode::ProofInvalid)?;
+    Ok(ValidatedBatchProof { proof, pis, proof_hash })
+}
+
+// Validates the proofs on the blocking pool, `parallelism` proofs at a time. Results keep the input order.
+async fn validate_batch_proofs<S>(
+    proofs: Vec<SubmitProofRequest>,
+    user_vk_hash: [u8; 32],
+    vk_path: &str,
+    parallelism: usize,
+) -> Vec<AnyhowResult<ValidatedBatchProof<S>>>
+where
+    S: Scheme + 'static,
+    S::Proof: Send + 'static,
+    S::Pis: Send + 'static,
+{
+    let mut results = Vec::with_capacity(proofs.len());
+    let mut proofs = proofs.into_iter().peekable();
+    while proofs.peek().is_some() {
+        let handles: Vec<_> = proofs
+            .by_ref()
+            .take(parallelism)
+            .map(|data| {
+                let vk_path = vk_path.to_string();
+                tokio::task::spawn_blocking(move || validate_batch_proof::<S>(data, user_vk_hash, &vk_path))
+            })
+            .collect();
+        for handle in handles {
+            results.push(match handle.await {
+                Ok(result) => result,
+                Err(e) => Err(anyhow!(error_line!(format!("proof validation panicked: {:?}", e)))),
+            });
+        }
+    }
+    results
+}
+
+/*
+    Validates and stores a batch of proofs of one circuit. A proof failing validation only fails its own entry,
+    the accepted proofs are inserted together in a single DB transaction.
+ */
+pub async fn submit_proof_batch_exec<S>(
+    data: SubmitProofBatchRequest,
+    protocol: &Protocol,
+    user_circuit_data: &UserCircuitData,
+    config_data: &ConfigData,
+) -> AnyhowResult<SubmitProofBatchResponse>
+where
+    S: Scheme + 'static,
+    S::Proof: Send + 'static,
+    S::Pis: Send + 'static,
+{
+    let mut results: Vec<SubmitProofBatchItemResponse> = (0..data.proofs.len())
+        .map(|_| SubmitProofBatchItemResponse { proof_id: None, error: None, error_code: None })
+        .collect();
+
+    // circuit lookup and vkey read happen once for the whole batch
+    let user_vk = S::Vkey::read_vk(&user_circuit_data.vk_path)?;
+    let user_vk_hash = user_vk.keccak_hash()?;
+
+    let mut indexes = vec![];
+    let mut proofs = vec![];
+    for (index, proof) in data.proofs.into_iter().enumerate() {
+        if proof.circuit_hash != data.circuit_hash {
+            set_batch_item_error(&mut results[index], CustomError::BadRequest(error_line!("circuit hash of the proof does not match the batch circuit hash")));
+        } else if proof.proof_type != user_circuit_data.proving_scheme {
+            set_batch_item_error(&mut results[index], CustomError::SchemeMismatch(error_line!("prove type is not correct")));
+        } else {
+            indexes.push(index);
+            proofs.push(proof);
+        }
+    }
+
+    let parallelism = if S::PARALLEL_VALIDATION {
+        std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1)
+    } else {
+        1
+    };
+    let validated_proofs = validate_batch_proofs::<S>(proofs, user_vk_hash, &user_circuit_data.vk_path, parallelism).await;
+    info!("validated batch of {} proofs", validated_proofs.len());
+
+    let mut seen_proof_hashes = HashSet::new();
+    let mut accepted_indexes = vec![];
+    let mut new_proofs = vec![];
+    let mut written_paths = vec![];
+    for (index, validated_proof) in indexes.into_iter().zip(validated_proofs) {
+        let validated_proof = match validated_proof {
+            Ok(p) => p,
+            Err(e) => {
+                set_batch_item_error(&mut results[index], CustomError::from(e));
+                continue;
+            }
+        };
+        if !seen_proof_hashes.insert(validated_proof.proof_hash.clone()) && protocol.is_proof_repeat_allowed == 0 {
+            set_batch_item_error(&mut results[index], CustomError::ProofDuplicate(error_line!("proof already exist in the batch")));
+            continue;
+        }
+        if let Err(e) = check_receipt_kind_accepted::<S>(&validated_proof.proof, &user_circuit_data.bonsai_image_id).await {
+            set_batch_item_error(&mut results[index], CustomError::from(e));
+            continue;
+        }
+        match prepare_batch_proof(&validated_proof, &data.circuit_hash, config_data).await {
+            Ok((new_proof, paths)) => {
+                accepted_indexes.push((index, validated_proof.proof_hash));
+                new_proofs.push(new_proof);
+                written_paths.extend(paths);
+            }
+            Err(e) => set_batch_item_error(&mut results[index], CustomError::from(e)),
+        }
+    }
+
+    if !new_proofs.is_empty() {
+        match insert_proofs_with_tasks(get_pool().await, &new_proofs, protocol.is_proof_repeat_allowed != 0).await {
+            Ok(proof_ids) => {
+                // a proof stored by a concurrent submission since `check_if_proof_already_exist` is skipped by the insert
+                for ((index, proof_hash), proof_id) in accepted_indexes.into_iter().zip(proof_ids) {
+                    match proof_id {
+                        Some(_) => results[index].proof_id = Some(proof_hash),
+                        None => set_batch_item_error(&mut results[index], CustomError::ProofDuplicate(error_line!("proof already exist"))),
+                    }
+                }
+            }
+            Err(e) => {
+                error!("error in inserting batch proofs: {:?}", e);
+                // nothing references the dumped files once the transaction is rolled back
+                for path in &written_paths {
+                    if let Err(e) = std::fs::remove_file(path) {
+                        error!("error in removing orphaned proof file {}: {:?}", path, e);
+                    }
+                }
+                let error = CustomError::from(e);
+                for (index, _) in accepted_indexes {
+                    set_batch_item_error(&mut results[index], error.clone());
+                }
+            }
+        }
+    }
+
+    Ok(SubmitProofBatchResponse { results })
+}
+
+fn set_batch_item_error(item: &mut SubmitProofBatchItemResponse, error: CustomError) {
+    item.error_code = Some(error.error_code());
+    item.error = Some(error.to_string());
+}
+
+// Also returns the files dumped by this call, files already on disk belong to an earlier proof with the same hash
+async fn prepare_batch_proof<S: Scheme>(
+    validated_proof: &ValidatedBatchProof<S>,
+    circuit_hash: &str,
+    config_data: &ConfigData,
+) -> AnyhowResult<(NewProof, Vec<String>)> {
+    let proof_hash = &validated_proof.proof_hash;
+    check_if_proof_already_exist(proof_hash, circuit_hash).await?;
+
+    // Dump proof and pis binaries
+    let proof_full_path = get_user_proof_path(
+        &config_data.storage_folder_path,
+        &config_data.proof_path,
+        circuit_hash,
+        proof_hash,
+    );
+    let pis_full_path = get_user_pis_path(
+        &config_data.storage_folder_path,
+        &config_data.public_inputs_path,
+        circuit_hash,
+        proof_hash,
+    );
+    let mut written_paths = vec![];
+    if !std::path::Path::new(&proof_full_path).exists() {
+        written_paths.push(proof_full_path.clone());
+    }
+    if !std::path::Path::new(&pis_full_path).exists() {
+        written_paths.push(pis_full_path.clone());
+    }
+    validated_proof.proof.dump_proof(&proof_full_path)?;
+    validated_proof.pis.dump_pis(&pis_full_path)?;
+
+    let public_inputs_json_string = serde_json::to_string(&validated_proof.pis.get_data()?)?;
+    let new_proof = NewProof {
+        proof_hash: proof_hash.clone(),
+        pis_path: pis_full_path,
+        proof_path: proof_full_path,
+        proof_status: ProofStatus::Registered,
+        user_circuit_hash: circuit_hash.to_string(),
+        pis_json_string: public_inputs_json_string,
+    };
+    Ok((new_proof, written_paths))
+}
+
 pub async fn get_proof_data_exec(
     proof_hash: String,
     config_data: &ConfigData,
@@ -156,30 +390,17 @@ pub async fn get_proof_data_exec(
 async fn validate_circuit_data_in_submit_proof_request(
     data: &SubmitProofRequest,
 ) -> AnyhowResult<()> {
-    let circuit_data =
-        get_user_circuit_data_by_circuit_hash(get_pool().await, &data.circuit_hash).await;
-    let circuit_data = match circuit_data {
-        Ok(cd) => Ok(cd),
-        Err(e) => {
-            info!("circuit has not been registered");
-            Err(anyhow!(CustomError::BadRequest(error_line!(format!(
-                "circuit hash not found. {}",
-                e.root_cause().to_string()
-            )))))
-        }
-    };
-
-    let circuit_data = circuit_data?;
+    let circuit_data = get_registered_circuit_data(&data.circuit_hash).await?;
     if circuit_data.circuit_reduction_status != CircuitReductionStatus::Completed {
         info!("circuit reduction not completed");
-        return Err(anyhow!(CustomError::BadRequest(error_line!(
+        return Err(anyhow!(CustomError::CircuitReductionNotCompleted(error_line!(
             "circuit reduction not completed".to_string()
         ))));
     }
 
     if data.proof_type != circuit_data.proving_scheme {
         info!("prove type is not correct");
-        return Err(anyhow!(CustomError::BadRequest(error_line!(
+        return Err(anyhow!(CustomError::SchemeMismatch(error_line!(
             "prove type is not correct".to_string()
         ))));
     }
@@ -203,7 +424,7 @@ pub async fn check_if_proof_already_exist(
         let protocol = get_protocol_by_protocol_name(get_pool().await, &protocol_name).await?;
         if protocol.is_proof_repeat_allowed == 0 {
             info!("proof already exist");
-            return Err(anyhow!(CustomError::BadRequest(error_line!(
+            return Err(anyhow!(CustomError::ProofDuplicate(error_line!(
                 "proof already exist".to_string()
             ))));
         }
@@ -211,15 +432,13 @@ pub async fn check_if_proof_already_exist(
     Ok(())
 }
 
-pub async fn get_protocol_proof_exec<T: Pis, V: Vkey>(
+pub async fn get_protocol_proof_exec<S: Scheme>(
     proof: &DbProof,
 ) -> AnyhowResult<ProtocolProofResponse, CustomError> {
     type H = KeccakHasher;
 
     let circuit_hash = decode_keccak_hex(&proof.user_circuit_hash.clone())?;
-    let user_circuit_data =
-        get_user_circuit_data_by_circuit_hash(get_pool().await, &proof.user_circuit_hash).await?;
-    let pis: T = T::read_pis(&proof.pis_path)?;
+    let pis = S::Pis::read_pis(&proof.pis_path)?;
     let protocol_pis_hash = pis.keccak_hash()?;
     let superproof = get_superproof_by_id(
         get_pool().await,
@@ -232,27 +451,24 @@ pub async fn get_protocol_proof_exec<T: Pis, V: Vkey>(
     let leaves: Vec<[u8; 32]>;
     let last_proof_elm: [u8; 32];
     let last_proof_elm_position: u8;
-    match user_circuit_data.proving_scheme {
-        ProvingSchemes::Sp1 => {
-            leaves = read_superproof_leaves::<H>(
-                &superproof
-                    .sp1_leaves_path
-                    .ok_or(anyhow!("missing sp1 leaves path"))?,
-            )?;
-            last_proof_elm =
-                decode_keccak_hex(&superproof.r0_root.ok_or(anyhow!("missing r0_root"))?)?;
-            last_proof_elm_position = 0;
-        }
-        _ => {
-            leaves = read_superproof_leaves::<H>(
-                &superproof
-                    .r0_leaves_path
-                    .ok_or(anyhow!("missing risc0 leaves path"))?,
-            )?;
-            last_proof_elm =
-                decode_keccak_hex(&superproof.sp1_root.ok_or(anyhow!("missing sp1_root"))?)?;
-            last_proof_elm_position = 1;
-        }
+    if S::SP1_AGGREGATED {
+        leaves = read_superproof_leaves::<H>(
+            &superproof
+                .sp1_leaves_path
+                .ok_or(anyhow!("missing sp1 leaves path"))?,
+        )?;
+        last_proof_elm =
+            decode_keccak_hex(&superproof.r0_root.ok_or(anyhow!("missing r0_root"))?)?;
+        last_proof_elm_position = 0;
+    } else {
+        leaves = read_superproof_leaves::<H>(
+            &superproof
+                .r0_leaves_path
+                .ok_or(anyhow!("missing risc0 leaves path"))?,
+        )?;
+        last_proof_elm =
+            decode_keccak_hex(&superproof.sp1_root.ok_or(anyhow!("missing sp1_root"))?)?;
+        last_proof_elm_position = 1;
     }
 
     let target_leaf = compute_leaf_value::<KeccakHasher>(&circuit_hash, &protocol_pis_hash);
@@ -328,3 +544,21 @@ pub fn get_imt_proof<H: Hasher>(
 
     Ok((proof, proof_helper))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_receipt_kind() {
+        let accepted_receipt_kinds = vec![Risc0ReceiptKind::Composite, Risc0ReceiptKind::Succinct];
+        assert!(check_receipt_kind(Risc0ReceiptKind::Succinct, &accepted_receipt_kinds).is_ok());
+        assert!(check_receipt_kind(Risc0ReceiptKind::Composite, &accepted_receipt_kinds).is_ok());
+
+        let err = check_receipt_kind(Risc0ReceiptKind::Groth16, &accepted_receipt_kinds).unwrap_err();
+        match err.downcast_ref::<CustomError>() {
+            Some(CustomError::ProofInvalid(message)) => assert!(message.contains("accepted kinds: Composite, Succinct")),
+            _ => panic!("expected a ProofInvalid error, got {:?}", err),
+        }
+    }
+}
@@ -1,24 +1,19 @@
-use quantum_db::repository::protocol::{check_if_protocol_already_registered, insert_protocol_auth_token};
+use quantum_db::repository::protocol::{check_if_protocol_already_registered, insert_protocol_auth_token, update_protocol_callback};
 use quantum_utils::error_line;
 use quantum_utils::keccak::get_keccak_hash_of_string;
+use rand::{rngs::OsRng, RngCore};
 use tracing::error;
 
-use crate::{connection::get_pool, error::error::CustomError, types::generate_auth_token::{GenerateAuthTokenRequest, GenerateAuthTokenResponse}};
+use crate::{connection::get_pool, error::error::CustomError, types::{generate_auth_token::{GenerateAuthTokenRequest, GenerateAuthTokenResponse}, protocol_callback::RegisterCallbackResponse}};
 
 use anyhow::{anyhow, Result as AnyhowResult};
 
 pub async fn generate_auth_token_for_protocol(data: GenerateAuthTokenRequest) -> AnyhowResult<GenerateAuthTokenResponse> {
     let protocol_name = data.protocol_name.to_uppercase();
-    let is_present = check_if_protocol_already_registered(get_pool().await, &protocol_name).await;
-    let is_present = match is_present {
-        Ok(t) => Ok(t) ,
-        Err(e) => Err(anyhow!(CustomError::Internal(e.root_cause().to_string()))),
-    };
-
-    let is_present = is_present?;
+    let is_present = check_if_protocol_already_registered(get_pool().await, &protocol_name).await?;
     if is_present {
         error!("protocol has already been registered");
-        return Err(anyhow!(CustomError::Internal(error_line!("protocol has already been registered".to_string()))));
+        return Err(anyhow!(CustomError::ProtocolAlreadyRegistered(error_line!("protocol has already been registered".to_string()))));
     }
 
     let protocol_name_with_secret = std::env::var("auth_token_secret").expect("auth_token_secret must be set.") + &protocol_name;
@@ -31,6 +26,19 @@ pub async fn generate_auth_token_for_protocol(data: GenerateAuthTokenRequest) ->
     })
 }
 
+// Registering again replaces the url and rotates the secret
+pub async fn register_protocol_callback(protocol_name: &str, callback_url: &str) -> AnyhowResult<RegisterCallbackResponse> {
+    let mut secret_bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut secret_bytes);
+    let callback_secret = hex::encode(secret_bytes);
+
+    update_protocol_callback(get_pool().await, protocol_name, callback_url, &callback_secret).await?;
+    Ok(RegisterCallbackResponse {
+        callback_url: callback_url.to_string(),
+        callback_secret,
+    })
+}
+
 fn get_token_from_hash(hash: [u8; 32]) -> String {
     let bytes = &hash[..24];
     let hex_string = hex::encode(&bytes);
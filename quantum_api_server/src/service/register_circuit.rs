@@ -1,45 +1,71 @@
 use quantum_db::repository::user_circuit_data_repository::{get_user_circuit_data_by_circuit_hash, insert_user_circuit_data};
-use quantum_types::{enums::circuit_reduction_status::CircuitReductionStatus, traits::vkey::Vkey, types::{config::ConfigData, db::protocol::Protocol}};
-use quantum_utils::{keccak::encode_keccak_hash, paths::get_user_vk_path};
+use quantum_db::error::error::CustomError as DBError;
+use quantum_types::{enums::{circuit_reduction_status::CircuitReductionStatus, error_code::ErrorCode}, schemes::Scheme, traits::vkey::Vkey, types::{config::ConfigData, db::{protocol::Protocol, user_circuit_data::UserCircuitData}, error::WithErrorCode}};
+use quantum_utils::{error_line, keccak::encode_keccak_hash, paths::get_user_vk_path};
 use rocket::State;
 
 use anyhow::{anyhow, Result as AnyhowResult};
 use tracing::info;
-use quantum_db::repository::bonsai_image::get_bonsai_image_by_proving_scheme;
+use quantum_db::repository::bonsai_image::{get_bonsai_image_by_image_id, get_bonsai_image_by_proving_scheme};
 use crate::{connection::get_pool, error::error::CustomError, types::{circuit_registration_status::CircuitRegistrationStatusResponse, register_circuit::{RegisterCircuitRequest, RegisterCircuitResponse}}};
 
 
-pub async fn register_circuit_exec<T: Vkey>(data: RegisterCircuitRequest, config_data: &State<ConfigData>, protocol: Protocol) -> AnyhowResult<RegisterCircuitResponse> {
+pub async fn register_circuit_exec<S: Scheme>(data: RegisterCircuitRequest, config_data: &State<ConfigData>, protocol: Protocol) -> AnyhowResult<RegisterCircuitResponse> {
+    if !S::SUPPORTED {
+        info!("{} circuits are not supported yet", S::PROVING_SCHEME.to_string());
+        return Err(anyhow!(CustomError::UnsupportedScheme(format!(
+            "{} circuits are not supported yet",
+            S::PROVING_SCHEME.to_string()
+        ))));
+    }
+
     // Retreive verification key bytes
     let vkey_bytes: Vec<u8> = data.vkey.clone();
 
     // Borsh deserialise to corresponding vkey struct
-    let vkey: T = T::deserialize_vkey(&mut vkey_bytes.as_slice())?;
+    let vkey = S::Vkey::deserialize_vkey(&mut vkey_bytes.as_slice()).with_error_code(ErrorCode::VkeyInvalid)?;
     let _ = match vkey.validate() {
         Ok(_) => Ok(()),
         Err(e) => {
             info!("vk is not valid");
-            Err(anyhow!(CustomError::Internal(format!("vk is invalid. {}",e))))
+            Err(anyhow!(CustomError::VkeyInvalid(format!("vk is invalid. {}",e))))
         },
     }?;
+    if let Err(e) = S::check_vkey_supported(&vkey) {
+        info!("vk is not supported: {}", e);
+        return Err(anyhow!(CustomError::UnsupportedScheme(e.to_string())));
+    }
     println!("validated");
     // Circuit Hash(str(Hash(vkey_bytes))) used to identify circuit
 
-    let bonsai_image = get_bonsai_image_by_proving_scheme(get_pool().await, data.proof_type).await?;
+    let bonsai_image = get_bonsai_image_by_proving_scheme(get_pool().await, S::REDUCTION_PROVING_SCHEME).await?;
 
     let circuit_hash = vkey.compute_circuit_hash(bonsai_image.circuit_verifying_id)?;
     let circuit_hash_string = encode_keccak_hash(&circuit_hash)?;
     println!("circuit_hash_string {:?}", circuit_hash_string);
 
     // Check if circuit is already registered
-    let is_circuit_already_registered = check_if_circuit_has_already_registered(circuit_hash_string.as_str()).await;
-    if is_circuit_already_registered  {
+    let registered_circuit = get_already_registered_circuit(circuit_hash_string.as_str()).await;
+    if let Some(registered_circuit) = registered_circuit {
+        // ark and snarkjs groth16 vkeys of a circuit hash alike, proofs are only accepted in the registered format
+        if registered_circuit.proving_scheme != data.proof_type {
+            info!("circuit has already been registered as {}", registered_circuit.proving_scheme.to_string());
+            return Err(anyhow!(CustomError::SchemeMismatch(error_line!(format!(
+                "circuit is already registered as {}",
+                registered_circuit.proving_scheme.to_string()
+            )))));
+        }
+        // the policy of a vkey is not part of its circuit hash, a circuit is registered under one policy only
+        let registered_vkey = S::Vkey::read_vk(&registered_circuit.vk_path)?;
+        if registered_vkey.policy_hash()? != vkey.policy_hash()? {
+            info!("circuit has already been registered with a different policy");
+            return Err(anyhow!(CustomError::VkeyInvalid(error_line!("circuit is already registered with a different policy"))));
+        }
         info!("circuit has already been registered");
         return Ok(
             RegisterCircuitResponse{circuit_hash: circuit_hash_string}
         );
     }
-    println!("already registered {:?}", is_circuit_already_registered);
 
 
     // dump vkey
@@ -58,9 +84,9 @@ pub async fn register_circuit_exec<T: Vkey>(data: RegisterCircuitRequest, config
 }
 
 pub async fn get_circuit_registration_status(circuit_hash: String) -> AnyhowResult<CircuitRegistrationStatusResponse> {
-    let user_circuit = get_user_circuit_data_by_circuit_hash(get_pool().await, circuit_hash.as_str()).await?;
+    let user_circuit = get_registered_circuit_data(circuit_hash.as_str()).await?;
     let status = user_circuit.circuit_reduction_status;
-    let bonsai_image = get_bonsai_image_by_proving_scheme(get_pool().await, user_circuit.proving_scheme).await?;
+    let bonsai_image = get_bonsai_image_by_image_id(get_pool().await, &user_circuit.bonsai_image_id).await?;
     
     let mut circuit_verifying_id_bytes = vec![];
     for i in bonsai_image.circuit_verifying_id {
@@ -76,12 +102,25 @@ pub async fn get_circuit_registration_status(circuit_hash: String) -> AnyhowResu
     })
 }
 
-async fn check_if_circuit_has_already_registered(circuit_hash_string: &str) -> bool {
+// Lookup by a client supplied circuit hash, a missing row is reported as CIRCUIT_NOT_REGISTERED
+pub async fn get_registered_circuit_data(circuit_hash: &str) -> AnyhowResult<UserCircuitData> {
+    match get_user_circuit_data_by_circuit_hash(get_pool().await, circuit_hash).await {
+        Ok(circuit_data) => Ok(circuit_data),
+        Err(e) => match e.downcast_ref::<DBError>() {
+            Some(DBError::NotFound(_)) => {
+                info!("circuit has not been registered");
+                Err(anyhow!(CustomError::CircuitNotRegistered(error_line!(format!("circuit hash not found: {}", circuit_hash)))))
+            }
+            _ => Err(e),
+        },
+    }
+}
+
+async fn get_already_registered_circuit(circuit_hash_string: &str) -> Option<UserCircuitData> {
     let circuit_data = get_user_circuit_data_by_circuit_hash(get_pool().await, circuit_hash_string).await;
-    let is_circuit_already_registered = match circuit_data {
-        Ok(_) => true,
-        Err(_) => false
-    };
-    is_circuit_already_registered
+    match circuit_data {
+        Ok(circuit_data) => Some(circuit_data),
+        Err(_) => None
+    }
 }
 
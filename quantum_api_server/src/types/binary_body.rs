@@ -0,0 +1,123 @@
+use std::{fmt, str::FromStr};
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use quantum_types::enums::proving_schemes::ProvingSchemes;
+use rocket::{data::{self, ByteUnit, Data}, form::Errors, http::Status, outcome::Outcome, Request};
+use serde::{de::{self, value::MapAccessDeserializer, MapAccess, SeqAccess, Visitor}, Deserialize, Deserializer};
+use tracing::info;
+
+use crate::types::json_body::JsonBodyError;
+
+// Encoding of a binary value sent as a string, always named explicitly by the client
+#[derive(Clone, Copy, Debug, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BinaryEncoding {
+    Hex,
+    Base64,
+}
+
+impl BinaryEncoding {
+    pub fn decode(&self, value: &str) -> Result<Vec<u8>, String> {
+        match self {
+            BinaryEncoding::Hex => hex::decode(value).map_err(|e| format!("invalid hex string: {}", e)),
+            BinaryEncoding::Base64 => BASE64.decode(value).map_err(|e| format!("invalid base64 string: {}", e)),
+        }
+    }
+}
+
+impl FromStr for BinaryEncoding {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "hex" => Ok(BinaryEncoding::Hex),
+            "base64" => Ok(BinaryEncoding::Base64),
+            _ => Err(format!("unknown encoding `{}`, expected `hex` or `base64`", s)),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct EncodedBinary {
+    encoding: BinaryEncoding,
+    data: String,
+}
+
+// Reads a raw `application/octet-stream` body, capped by the `limit_name` limit
+pub async fn read_binary_body<'r>(req: &'r Request<'_>, data: Data<'r>, limit_name: &str, default_limit: ByteUnit) -> data::Outcome<'r, Vec<u8>, JsonBodyError> {
+    use JsonBodyError::*;
+    let limit = req.limits().get(limit_name).unwrap_or(default_limit);
+    let bytes = match data.open(limit).into_bytes().await {
+        Ok(bytes) if bytes.is_complete() => bytes.into_inner(),
+        Ok(_) => return Outcome::Error((Status::PayloadTooLarge, TooLarge)),
+        Err(e) => return Outcome::Error((Status::InternalServerError, Io(e))),
+    };
+    info!("{} binary request data length {}", limit_name, bytes.len());
+    Outcome::Success(bytes)
+}
+
+// Field path and message of the first multipart form error
+pub fn get_form_error(errors: &Errors<'_>) -> (String, String) {
+    let path = errors
+        .iter()
+        .find_map(|e| e.name.as_ref().map(|name| name.to_string()))
+        .unwrap_or(".".to_string());
+    (path, errors.to_string())
+}
+
+// Metadata of binary uploads is passed in the query string
+pub fn get_query_field(req: &Request<'_>, name: &str) -> Result<String, (String, String)> {
+    match req.query_value::<String>(name) {
+        Some(Ok(value)) => Ok(value),
+        Some(Err(e)) => Err((name.to_string(), e.to_string())),
+        None => Err((name.to_string(), format!("missing query parameter `{}`", name))),
+    }
+}
+
+// An optional binary query param `name`, decoded with the encoding given in `<name>_encoding`
+pub fn get_optional_query_binary(req: &Request<'_>, name: &str) -> Result<Vec<u8>, (String, String)> {
+    let value = match req.query_value::<String>(name) {
+        Some(Ok(value)) => value,
+        Some(Err(e)) => return Err((name.to_string(), e.to_string())),
+        None => return Ok(vec![]),
+    };
+    let encoding_name = format!("{}_encoding", name);
+    let encoding = BinaryEncoding::from_str(&get_query_field(req, &encoding_name)?).map_err(|e| (encoding_name, e))?;
+    encoding.decode(&value).map_err(|e| (name.to_string(), e))
+}
+
+pub fn parse_proving_scheme(name: &str, value: &str) -> Result<ProvingSchemes, (String, String)> {
+    ProvingSchemes::from_str(value).map_err(|e| (name.to_string(), e))
+}
+
+/*
+    Binary fields of json bodies. Accepts the original array of byte values, or a much smaller
+    `{"encoding": "hex" | "base64", "data": "..."}` object.
+ */
+pub fn deserialize_binary<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+    struct BinaryVisitor;
+
+    impl<'de> Visitor<'de> for BinaryVisitor {
+        type Value = Vec<u8>;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("an array of bytes or an object with `encoding` and `data`")
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Vec<u8>, A::Error> {
+            let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+            while let Some(byte) = seq.next_element::<u8>()? {
+                bytes.push(byte);
+            }
+            Ok(bytes)
+        }
+
+        fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<Vec<u8>, A::Error> {
+            let encoded = EncodedBinary::deserialize(MapAccessDeserializer::new(map))?;
+            encoded.encoding.decode(&encoded.data).map_err(de::Error::custom)
+        }
+    }
+
+    deserializer.deserialize_any(BinaryVisitor)
+}
@@ -0,0 +1,17 @@
+use rocket::request::{FromRequest, Outcome, Request};
+
+// `Last-Event-ID` header, sent by EventSource clients when they reconnect
+pub struct LastEventId(pub Option<u64>);
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for LastEventId {
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let last_event_id = request
+            .headers()
+            .get_one("Last-Event-ID")
+            .and_then(|id| id.trim().parse::<u64>().ok());
+        Outcome::Success(LastEventId(last_event_id))
+    }
+}
@@ -1,42 +1,23 @@
 use rocket::serde::Serialize;
-use rocket::{data::{self, FromData, ToByteUnit}, http::{ContentType, Status}, outcome::Outcome, Data, Request};
+use rocket::{data::{self, ByteUnit, FromData}, Data, Request};
 use serde::Deserialize;
-use tracing::info;
+use crate::types::json_body::{parse_json_body, JsonBody, JsonBodyError};
 
 #[derive(Clone, Debug, Deserialize)]
 pub struct GenerateAuthTokenRequest {
     pub protocol_name: String
 }
 
-#[derive(Debug)]
-pub enum Error {
-    TooLarge,
-    Io(std::io::Error)
+impl JsonBody for GenerateAuthTokenRequest {
+    const LIMIT_NAME: &'static str = "auth-protocol";
+    const DEFAULT_LIMIT: ByteUnit = ByteUnit::Mebibyte(1);
 }
 
 #[rocket::async_trait]
 impl<'r> FromData<'r> for GenerateAuthTokenRequest {
-    type Error = Error;
+    type Error = JsonBodyError;
     async fn from_data(req: &'r Request<'_>, data: Data<'r>) -> data::Outcome<'r, Self> {
-        use Error::*;
-        // Content type must be json
-        let json_ct = ContentType::JSON;
-        if req.content_type() != Some(&json_ct) {
-            return Outcome::Forward((data, Status::UnsupportedMediaType));
-        }
-        // Deserialise the request body into RegisterCircuitRequest
-        let stream = match data.open(1024.kibibytes()).into_string().await {
-            Ok(string) if string.is_complete() => string.into_inner(),
-            Ok(_) => return Outcome::Error((Status::PayloadTooLarge, TooLarge)),
-            Err(e) => return Outcome::Error((Status::InternalServerError, Io(e))),
-        };
-        info!("request data {:?}", stream);
-
-        // TODO: we can convert types here only
-
-        let generate_auth_token_request = serde_json::from_str(&stream).unwrap();
-
-        Outcome::Success(generate_auth_token_request)
+        parse_json_body(req, data).await
     }
 }
 
@@ -0,0 +1,66 @@
+use rocket::{data::{self, ByteUnit, Data}, http::{ContentType, Status}, outcome::Outcome, Request};
+use serde::de::DeserializeOwned;
+use serde_json::error::Category;
+use tracing::{info, error};
+
+// A json request body. The body size limit is read from the `limits` table of Rocket.toml
+pub trait JsonBody: DeserializeOwned {
+    const LIMIT_NAME: &'static str;
+    // used when Rocket.toml does not set `LIMIT_NAME`
+    const DEFAULT_LIMIT: ByteUnit;
+}
+
+#[derive(Debug)]
+pub enum JsonBodyError {
+    TooLarge,
+    Io(std::io::Error),
+    Parse(JsonParseError),
+}
+
+// Cached on the request so that the 400/422 catchers can tell the client where the body is wrong
+#[derive(Debug, Clone)]
+pub struct JsonParseError {
+    pub path: String,
+    pub message: String,
+}
+
+pub async fn parse_json_body<'r, T: JsonBody>(req: &'r Request<'_>, data: Data<'r>) -> data::Outcome<'r, T, JsonBodyError> {
+    use JsonBodyError::*;
+    // Content type must be json
+    let json_ct = ContentType::JSON;
+    if req.content_type() != Some(&json_ct) {
+        return Outcome::Forward((data, Status::UnsupportedMediaType));
+    }
+    let limit = req.limits().get(T::LIMIT_NAME).unwrap_or(T::DEFAULT_LIMIT);
+    let stream = match data.open(limit).into_string().await {
+        Ok(string) if string.is_complete() => string.into_inner(),
+        Ok(_) => return Outcome::Error((Status::PayloadTooLarge, TooLarge)),
+        Err(e) => return Outcome::Error((Status::InternalServerError, Io(e))),
+    };
+    // bodies carry whole proofs, so only the size is logged
+    info!("{} request data length {}", T::LIMIT_NAME, stream.len());
+
+    let mut deserializer = serde_json::Deserializer::from_str(&stream);
+    let body: T = match serde_path_to_error::deserialize(&mut deserializer) {
+        Ok(body) => body,
+        Err(e) => {
+            // syntax errors are a broken body, data errors a well formed body with wrong fields
+            let status = match e.inner().classify() {
+                Category::Data => Status::UnprocessableEntity,
+                _ => Status::BadRequest,
+            };
+            return reject(req, status, e.path().to_string(), e.inner().to_string());
+        }
+    };
+    if let Err(e) = deserializer.end() {
+        return reject(req, Status::BadRequest, ".".to_string(), e.to_string());
+    }
+    Outcome::Success(body)
+}
+
+pub fn reject<'r, T>(req: &'r Request<'_>, status: Status, path: String, message: String) -> data::Outcome<'r, T, JsonBodyError> {
+    error!("invalid {} request body at {}: {}", req.uri().path(), path, message);
+    let parse_error = JsonParseError { path, message };
+    req.local_cache(|| Some(parse_error.clone()));
+    Outcome::Error((status, JsonBodyError::Parse(parse_error)))
+}
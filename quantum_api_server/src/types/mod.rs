@@ -4,4 +4,8 @@ pub mod submit_proof;
 pub mod proof_data;
 pub mod auth;
 pub mod generate_auth_token;
-pub mod protocol_proof;
\ No newline at end of file
+pub mod protocol_proof;
+pub mod protocol_callback;
+pub mod events;
+pub mod json_body;
+pub mod binary_body;
\ No newline at end of file
@@ -0,0 +1,30 @@
+use rocket::serde::Serialize;
+use rocket::{data::{self, ByteUnit, FromData}, Data, Request};
+use serde::Deserialize;
+use crate::types::json_body::{parse_json_body, JsonBody, JsonBodyError};
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct RegisterCallbackRequest {
+    pub callback_url: String
+}
+
+impl JsonBody for RegisterCallbackRequest {
+    const LIMIT_NAME: &'static str = "protocol-callback";
+    const DEFAULT_LIMIT: ByteUnit = ByteUnit::Mebibyte(1);
+}
+
+#[rocket::async_trait]
+impl<'r> FromData<'r> for RegisterCallbackRequest {
+    type Error = JsonBodyError;
+    async fn from_data(req: &'r Request<'_>, data: Data<'r>) -> data::Outcome<'r, Self> {
+        parse_json_body(req, data).await
+    }
+}
+
+// callback_secret is the HMAC key of the X-Quantum-Signature header on every callback
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct RegisterCallbackResponse {
+    pub callback_url: String,
+    pub callback_secret: String,
+}
@@ -1,47 +1,68 @@
 use quantum_types::enums::proving_schemes::ProvingSchemes;
 use rocket::serde::Serialize;
-use rocket::{data::{self, FromData, ToByteUnit}, http::{ContentType, Status}, outcome::Outcome, Data, Request};
+use rocket::{data::{self, ByteUnit, FromData}, form::{Form, FromForm}, http::Status, outcome::Outcome, Data, Request};
 use serde::Deserialize;
-use tracing::info;
+use crate::types::{binary_body::{deserialize_binary, get_form_error, get_query_field, parse_proving_scheme, read_binary_body}, json_body::{parse_json_body, reject, JsonBody, JsonBodyError}};
 // use crate::types::proving_schemes::ProvingSchemes;
 
+/*
+    Accepted as json (vkey as a byte array or an `{"encoding", "data"}` object), as `application/octet-stream` with the
+    raw vkey as body and a `proof_type` query param, or as `multipart/form-data` with a raw `vkey` part.
+ */
 // Note: not removing for the backward compatibility
 #[derive(Clone, Debug, Deserialize)]
 pub struct RegisterCircuitRequest {
+    #[serde(deserialize_with = "deserialize_binary")]
     pub vkey: Vec<u8>, // borsh serialised vkey
     pub proof_type: ProvingSchemes
 }
 
-#[derive(Debug)]
-pub enum Error {
-    TooLarge,
-    Io(std::io::Error)
+#[derive(FromForm)]
+struct RegisterCircuitForm<'r> {
+    proof_type: String,
+    vkey: &'r [u8],
+}
+
+impl JsonBody for RegisterCircuitRequest {
+    const LIMIT_NAME: &'static str = "register-circuit";
+    const DEFAULT_LIMIT: ByteUnit = ByteUnit::Mebibyte(1);
 }
 
 #[rocket::async_trait]
 impl<'r> FromData<'r> for RegisterCircuitRequest {
-    type Error = Error;
+    type Error = JsonBodyError;
     async fn from_data(req: &'r Request<'_>, data: Data<'r>) -> data::Outcome<'r, Self> {
-        use Error::*;
-        // Content type must be json
-        let json_ct = ContentType::JSON;
-        if req.content_type() != Some(&json_ct) {
-            return Outcome::Forward((data, Status::UnsupportedMediaType));
+        match req.content_type() {
+            Some(ct) if ct.is_binary() => register_circuit_from_octet_stream(req, data).await,
+            Some(ct) if ct.is_form_data() => register_circuit_from_multipart(req, data).await,
+            _ => parse_json_body(req, data).await,
         }
-        // Deserialise the request body into RegisterCircuitRequest
-        let stream = match data.open(1024.kibibytes()).into_string().await {
-            Ok(string) if string.is_complete() => string.into_inner(),
-            Ok(_) => return Outcome::Error((Status::PayloadTooLarge, TooLarge)),
-            Err(e) => return Outcome::Error((Status::InternalServerError, Io(e))),
-        };
-        info!("request data {:?}", stream);
-
-        // TODO: we can convert types here only
+    }
+}
 
-        let register_circuit_request = serde_json::from_str(&stream).unwrap();
+async fn register_circuit_from_octet_stream<'r>(req: &'r Request<'_>, data: Data<'r>) -> data::Outcome<'r, RegisterCircuitRequest> {
+    let proof_type = match get_query_field(req, "proof_type").and_then(|value| parse_proving_scheme("proof_type", &value)) {
+        Ok(proof_type) => proof_type,
+        Err((path, message)) => return reject(req, Status::UnprocessableEntity, path, message),
+    };
+    read_binary_body(req, data, RegisterCircuitRequest::LIMIT_NAME, RegisterCircuitRequest::DEFAULT_LIMIT).await
+        .map(|vkey| RegisterCircuitRequest { vkey, proof_type })
+}
 
-        Outcome::Success(register_circuit_request)
-    }
+async fn register_circuit_from_multipart<'r>(req: &'r Request<'_>, data: Data<'r>) -> data::Outcome<'r, RegisterCircuitRequest> {
+    let form = match <Form<RegisterCircuitForm<'r>> as FromData<'r>>::from_data(req, data).await {
+        Outcome::Success(form) => form.into_inner(),
+        Outcome::Forward(f) => return Outcome::Forward(f),
+        Outcome::Error((status, errors)) => {
+            let (path, message) = get_form_error(&errors);
+            return reject(req, status, path, message);
+        }
+    };
+    let proof_type = match parse_proving_scheme("proof_type", &form.proof_type) {
+        Ok(proof_type) => proof_type,
+        Err((path, message)) => return reject(req, Status::UnprocessableEntity, path, message),
+    };
+    Outcome::Success(RegisterCircuitRequest { vkey: form.vkey.to_vec(), proof_type })
 }
 
 
@@ -1,49 +1,122 @@
-use quantum_types::enums::proving_schemes::ProvingSchemes;
-use rocket::{data::{self, FromData, ToByteUnit}, http::{ContentType, Status}, outcome::Outcome, Data, Request};
+use quantum_types::enums::{error_code::ErrorCode, proving_schemes::ProvingSchemes};
+use rocket::{data::{self, ByteUnit, FromData}, form::{Form, FromForm}, http::Status, outcome::Outcome, Data, Request};
 use serde::{Deserialize, Serialize};
-use tracing::info;
+use crate::types::{binary_body::{deserialize_binary, get_form_error, get_optional_query_binary, get_query_field, parse_proving_scheme, read_binary_body}, json_body::{parse_json_body, reject, JsonBody, JsonBodyError}};
 
+/*
+    Accepted as json (binary fields as byte arrays or `{"encoding": "hex" | "base64", "data": ..}` objects), as
+    `application/octet-stream` with the raw proof as body and `circuit_hash`, `proof_type` and optional `pis` with
+    `pis_encoding` query params,
+    or as `multipart/form-data` with the same fields and raw `proof`/`pis` parts.
+ */
 #[derive(Clone, Debug, Deserialize)]
 pub struct SubmitProofRequest {
+    #[serde(deserialize_with = "deserialize_binary")]
     pub proof: Vec<u8>, // borsh serialised proof
+    #[serde(deserialize_with = "deserialize_binary")]
     pub pis: Vec<u8>,  // borsh serialised pis
     pub circuit_hash: String,
     pub proof_type: ProvingSchemes
 }
 
-#[derive(Debug)]
-pub enum Error {
-    TooLarge,
-    Io(std::io::Error)
+#[derive(FromForm)]
+struct SubmitProofForm<'r> {
+    circuit_hash: String,
+    proof_type: String,
+    proof: &'r [u8],
+    pis: Option<&'r [u8]>,
+}
+
+impl JsonBody for SubmitProofRequest {
+    const LIMIT_NAME: &'static str = "submit-proof";
+    const DEFAULT_LIMIT: ByteUnit = ByteUnit::Mebibyte(10);
 }
 
 #[rocket::async_trait]
 impl<'r> FromData<'r> for SubmitProofRequest {
-    type Error = Error;
+    type Error = JsonBodyError;
     async fn from_data(req: &'r Request<'_>, data: Data<'r>) -> data::Outcome<'r, Self> {
-        use Error::*;
-        // Content type must be json
-        let json_ct = ContentType::JSON;
-        if req.content_type() != Some(&json_ct) {
-            return Outcome::Forward((data, Status::UnsupportedMediaType));
+        match req.content_type() {
+            Some(ct) if ct.is_binary() => submit_proof_from_octet_stream(req, data).await,
+            Some(ct) if ct.is_form_data() => submit_proof_from_multipart(req, data).await,
+            _ => parse_json_body(req, data).await,
         }
-        // Deserialise the request body into RegisterCircuitRequest
-        let stream = match data.open(10.mebibytes()).into_string().await {
-            Ok(string) if string.is_complete() => string.into_inner(),
-            Ok(_) => return Outcome::Error((Status::PayloadTooLarge, TooLarge)),
-            Err(e) => return Outcome::Error((Status::InternalServerError, Io(e))),
-        };
-        info!("request data {:?}", stream);
-
-        // TODO: we can convert types here only
-
-        let submit_proof_request = serde_json::from_str(&stream).unwrap();
-        Outcome::Success(submit_proof_request)
     }
 }
 
+fn get_submit_proof_query_fields(req: &Request<'_>) -> Result<(String, ProvingSchemes, Vec<u8>), (String, String)> {
+    let circuit_hash = get_query_field(req, "circuit_hash")?;
+    let proof_type = parse_proving_scheme("proof_type", &get_query_field(req, "proof_type")?)?;
+    let pis = get_optional_query_binary(req, "pis")?;
+    Ok((circuit_hash, proof_type, pis))
+}
+
+async fn submit_proof_from_octet_stream<'r>(req: &'r Request<'_>, data: Data<'r>) -> data::Outcome<'r, SubmitProofRequest> {
+    let (circuit_hash, proof_type, pis) = match get_submit_proof_query_fields(req) {
+        Ok(fields) => fields,
+        Err((path, message)) => return reject(req, Status::UnprocessableEntity, path, message),
+    };
+    read_binary_body(req, data, SubmitProofRequest::LIMIT_NAME, SubmitProofRequest::DEFAULT_LIMIT).await
+        .map(|proof| SubmitProofRequest { proof, pis, circuit_hash, proof_type })
+}
+
+async fn submit_proof_from_multipart<'r>(req: &'r Request<'_>, data: Data<'r>) -> data::Outcome<'r, SubmitProofRequest> {
+    let form = match <Form<SubmitProofForm<'r>> as FromData<'r>>::from_data(req, data).await {
+        Outcome::Success(form) => form.into_inner(),
+        Outcome::Forward(f) => return Outcome::Forward(f),
+        Outcome::Error((status, errors)) => {
+            let (path, message) = get_form_error(&errors);
+            return reject(req, status, path, message);
+        }
+    };
+    let proof_type = match parse_proving_scheme("proof_type", &form.proof_type) {
+        Ok(proof_type) => proof_type,
+        Err((path, message)) => return reject(req, Status::UnprocessableEntity, path, message),
+    };
+    Outcome::Success(SubmitProofRequest {
+        proof: form.proof.to_vec(),
+        pis: form.pis.map(|pis| pis.to_vec()).unwrap_or_default(),
+        circuit_hash: form.circuit_hash,
+        proof_type,
+    })
+}
+
 #[derive(Serialize, Deserialize)]
 #[serde(crate = "rocket::serde")]
 pub struct SubmitProofResponse {
     pub proof_id: String,
 }
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct SubmitProofBatchRequest {
+    pub circuit_hash: String,
+    pub proofs: Vec<SubmitProofRequest>,
+}
+
+impl JsonBody for SubmitProofBatchRequest {
+    const LIMIT_NAME: &'static str = "submit-proof-batch";
+    const DEFAULT_LIMIT: ByteUnit = ByteUnit::Mebibyte(256);
+}
+
+#[rocket::async_trait]
+impl<'r> FromData<'r> for SubmitProofBatchRequest {
+    type Error = JsonBodyError;
+    async fn from_data(req: &'r Request<'_>, data: Data<'r>) -> data::Outcome<'r, Self> {
+        parse_json_body(req, data).await
+    }
+}
+
+// Result of a single proof of the batch, exactly one of proof_id or error is set
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct SubmitProofBatchItemResponse {
+    pub proof_id: Option<String>,
+    pub error: Option<String>,
+    pub error_code: Option<ErrorCode>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct SubmitProofBatchResponse {
+    pub results: Vec<SubmitProofBatchItemResponse>,
+}
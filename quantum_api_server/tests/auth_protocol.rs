@@ -27,7 +27,7 @@ async fn test_get_auth_token_with_invalid_payload(){
     let response = client.post("/auth/protocol").header(Header::new("Authorization", format!("Bearer {}", MASTER_AUTH_TOKEN)))
                                     .header(ContentType::JSON).body(payload).dispatch().await;
     
-    assert_eq!(response.status(), Status::InternalServerError);
+    assert_eq!(response.status(), Status::UnprocessableEntity);
     assert_eq!(response.content_type().unwrap(), ContentType::JSON);
 }
 
@@ -54,7 +54,7 @@ async fn test_get_auth_token_with_repeated_protocol_registration(){
     let response = client.post("/auth/protocol").header(Header::new("Authorization", format!("Bearer {}", MASTER_AUTH_TOKEN)))
                                     .header(ContentType::JSON).body(payload).dispatch().await;
     
-    assert_eq!(response.status(), Status::InternalServerError);
+    assert_eq!(response.status(), Status::Conflict);
     assert_eq!(response.content_type().unwrap(), ContentType::JSON);
 
     after_test("new_protocol").await;
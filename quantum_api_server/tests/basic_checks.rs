@@ -1,3 +1,5 @@
+use quantum_api_server::error::error::ErrorResponse;
+use quantum_types::enums::error_code::ErrorCode;
 use rocket::http::{ContentType, Header, Status};
 
 mod common;
@@ -12,7 +14,9 @@ async fn test_invalid_path(){
     let response = client.get(invalid_url).dispatch().await;
     assert_ne!(response.status(), Status::Ok);
     assert_eq!(response.status(), Status::NotFound);
-    assert_ne!(response.content_type().unwrap(), ContentType::JSON);
+    assert_eq!(response.content_type().unwrap(), ContentType::JSON);
+    let res: ErrorResponse = response.into_json().await.unwrap();
+    assert_eq!(res.error_code, ErrorCode::NotFound);
 }
 
 
@@ -23,7 +27,9 @@ async fn test_invalid_auth_token(){
     let response = client.get("/ping").header(Header::new("Authorization", invalid_auth_token)).dispatch().await;
     assert_ne!(response.status(), Status::Ok);
     assert_eq!(response.status(), Status::Unauthorized);
-    assert_ne!(response.content_type().unwrap(), ContentType::JSON);
+    assert_eq!(response.content_type().unwrap(), ContentType::JSON);
+    let res: ErrorResponse = response.into_json().await.unwrap();
+    assert_eq!(res.error_code, ErrorCode::Unauthorized);
 }
 
 #[tokio::test]
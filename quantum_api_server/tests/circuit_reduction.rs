@@ -1,6 +1,7 @@
 mod common;
 use common::{ repository::{task_repository::delete_all_task_data, user_circuit_data_repository::{delete_all_user_circuit_data, insert_random_protocol_user_circuit_data}}, setup};
-use quantum_api_server::{connection::get_pool, types::{circuit_registration_status::CircuitRegistrationStatusResponse, register_circuit::RegisterCircuitResponse}};
+use quantum_api_server::{connection::get_pool, error::error::ErrorResponse, types::{circuit_registration_status::CircuitRegistrationStatusResponse, register_circuit::RegisterCircuitResponse}};
+use quantum_types::enums::error_code::ErrorCode;
 use rocket::{http::{ContentType, Header, Status}, local::asynchronous::Client};
 
 const  AUTH_TOKEN: &str = "b3047d47c5d6551744680f5c3ba77de90acb84055eefdcbb";
@@ -37,8 +38,10 @@ async fn test_get_circuit_reduction_status_with_invalid_circuit_hash(){
     let response = client.get(format!("/circuit/{}/status", incorrect_circuit_hash)).header(Header::new("Authorization", format!("Bearer {}", AUTH_TOKEN))).dispatch().await;
     
     // validating response status and content_type
-    assert_eq!(response.status(), Status::InternalServerError);
-    assert_eq!(response.content_type().unwrap(), ContentType::JSON);    
+    assert_eq!(response.status(), Status::NotFound);
+    assert_eq!(response.content_type().unwrap(), ContentType::JSON);
+    let res: ErrorResponse = response.into_json().await.unwrap();
+    assert_eq!(res.error_code, ErrorCode::CircuitNotRegistered);    
     
     after_test().await; 
 }
@@ -26,4 +26,16 @@ pub async fn insert_electron_protocol(pool: &Pool<MySql>) -> AnyhowResult<()>{
         Err(e) => Err(anyhow!(CustomError::DB(error_line!(e))))
     };
     row_affected
-}
\ No newline at end of file
+}
+
+pub async fn clear_protocol_callback(pool: &Pool<MySql>, protocol_name: &str) -> AnyhowResult<()>{
+    let query = sqlx::query("UPDATE protocol SET callback_url = NULL, callback_secret = NULL WHERE protocol_name=?").bind(protocol_name);
+
+    info!("{}", query.sql());
+
+    let row_affected = match query.execute(&mut *pool.acquire().await.unwrap()).await {
+        Ok(_) => Ok(()),
+        Err(e) => Err(anyhow!(CustomError::DB(error_line!(e))))
+    };
+    row_affected
+}
@@ -2,8 +2,8 @@ use quantum_types::types::config::ConfigData;
 use rocket::{catchers, routes, Build, Rocket};
 
 use quantum_api_server::{
-    routes::{auth_protocol::generate_auth_token, circuit_reduction::get_circuit_reduction_status, index::index, ping::ping, proof::{get_proof_status, submit_proof}, protocol_proof::get_protocol_proof, register_circuit::register_circuit}, 
-    catcher::{unsupported_media_type, internal_server_error}
+    routes::{auth_protocol::generate_auth_token, circuit_reduction::get_circuit_reduction_status, index::index, ping::ping, proof::{get_proof_status, submit_proof, submit_proof_batch}, events::{get_proof_events, get_protocol_events}, protocol_callback::register_callback, protocol_proof::get_protocol_proof, register_circuit::register_circuit}, 
+    catcher::{bad_request, unauthorized, not_found, payload_too_large, unsupported_media_type, unprocessable_entity, internal_server_error}
 };
 
 pub fn rocket_builder() -> Rocket<Build> {
@@ -16,6 +16,6 @@ pub fn rocket_builder() -> Rocket<Build> {
     let t = rocket::Config::figment();
 
     rocket::custom(t).manage(config_data)
-    .mount("/", routes![index, ping, register_circuit, get_circuit_reduction_status, submit_proof, get_proof_status, generate_auth_token, get_protocol_proof]).attach(cors)
-    .register("/", catchers![unsupported_media_type, internal_server_error])
+    .mount("/", routes![index, ping, register_circuit, get_circuit_reduction_status, submit_proof, submit_proof_batch, get_proof_status, generate_auth_token, get_protocol_proof, register_callback, get_proof_events, get_protocol_events]).attach(cors)
+    .register("/", catchers![bad_request, unauthorized, not_found, payload_too_large, unsupported_media_type, unprocessable_entity, internal_server_error])
 }
\ No newline at end of file
@@ -1,6 +1,8 @@
 mod common;
 use common::{repository::{proof::delete_all_proof_data, task_repository::delete_all_task_data, user_circuit_data_repository::{delete_all_user_circuit_data, update_circuit_redn_status_user_circuit_data_completed}}, setup};
-use quantum_api_server::{connection::get_pool, types::{register_circuit::RegisterCircuitResponse, submit_proof::SubmitProofResponse}};
+use quantum_api_server::{connection::get_pool, error::error::ErrorResponse, types::{register_circuit::RegisterCircuitResponse, submit_proof::{SubmitProofBatchResponse, SubmitProofResponse}}};
+use ark_serialize::CanonicalSerialize;
+use quantum_types::{enums::error_code::ErrorCode, traits::{pis::Pis, proof::Proof, vkey::Vkey}, types::{ark_groth16::{ArkGroth16Pis, ArkGroth16Proof, ArkGroth16Vkey}, snarkjs_groth16::{SnarkJSGroth16Pis, SnarkJSGroth16Proof, SnarkJSGroth16Vkey}}};
 use rocket::{http::{ContentType, Header, Status}, local::asynchronous::Client};
 
 const  AUTH_TOKEN: &str = "b3047d47c5d6551744680f5c3ba77de90acb84055eefdcbb";
@@ -45,8 +47,26 @@ async fn test_submit_proof_with_invalid_payload(){
     let response = client.post("/proof").header(Header::new("Authorization", format!("Bearer {}", AUTH_TOKEN)))
                                                               .header(ContentType::JSON).body(payload).dispatch().await;
 
-    assert_eq!(response.status(), Status::InternalServerError);
+    assert_eq!(response.status(), Status::UnprocessableEntity);
     assert_eq!(response.content_type().unwrap(), ContentType::JSON);
+    let res: ErrorResponse = response.into_json().await.unwrap();
+    assert_eq!(res.error_code, ErrorCode::InvalidPayload);
+    assert!(res.path.is_some());
+}
+
+
+#[tokio::test]
+async fn test_submit_proof_with_malformed_json(){
+    let client = setup().await;
+    let payload = r#"{"proof": [1, 2,"#;
+
+    let response = client.post("/proof").header(Header::new("Authorization", format!("Bearer {}", AUTH_TOKEN)))
+                                                              .header(ContentType::JSON).body(payload).dispatch().await;
+
+    assert_eq!(response.status(), Status::BadRequest);
+    assert_eq!(response.content_type().unwrap(), ContentType::JSON);
+    let res: ErrorResponse = response.into_json().await.unwrap();
+    assert_eq!(res.error_code, ErrorCode::InvalidPayload);
 }
 
 
@@ -61,8 +81,10 @@ async fn test_submit_proof_with_invalid_proving_scheme(){
     let response = client.post("/proof").header(Header::new("Authorization", format!("Bearer {}", AUTH_TOKEN)))
                                  .header(ContentType::JSON).body(invalid_proving_scheme_payload).dispatch().await;
     
-    assert_eq!(response.status(), Status::InternalServerError);
+    assert_eq!(response.status(), Status::BadRequest);
     assert_eq!(response.content_type().unwrap(), ContentType::JSON);
+    let res: ErrorResponse = response.into_json().await.unwrap();
+    assert_eq!(res.error_code, ErrorCode::SchemeMismatch);
 
     after_test().await;
 }
@@ -79,8 +101,10 @@ async fn test_submit_proof_with_invalid_proof(){
     let response = client.post("/proof").header(Header::new("Authorization", format!("Bearer {}", AUTH_TOKEN)))
                                  .header(ContentType::JSON).body(invalid_proof_payload).dispatch().await;
     
-    assert_eq!(response.status(), Status::InternalServerError);
+    assert_eq!(response.status(), Status::UnprocessableEntity);
     assert_eq!(response.content_type().unwrap(), ContentType::JSON);
+    let res: ErrorResponse = response.into_json().await.unwrap();
+    assert_eq!(res.error_code, ErrorCode::ProofInvalid);
 
     after_test().await;
 }
@@ -96,7 +120,7 @@ async fn test_submit_proof_with_invalid_pis(){
     let response = client.post("/proof").header(Header::new("Authorization", format!("Bearer {}", AUTH_TOKEN)))
                                  .header(ContentType::JSON).body(invalid_pis_payload).dispatch().await;
     
-    assert_eq!(response.status(), Status::InternalServerError);
+    assert_eq!(response.status(), Status::UnprocessableEntity);
     assert_eq!(response.content_type().unwrap(), ContentType::JSON);
 
     after_test().await;
@@ -127,8 +151,10 @@ async fn test_submit_proof_with_repeated_proof(){
     let response = client.post("/proof").header(Header::new("Authorization", format!("Bearer {}", AUTH_TOKEN)))
                                  .header(ContentType::JSON).body(payload).dispatch().await;
 
-    assert_eq!(response.status(), Status::InternalServerError);
+    assert_eq!(response.status(), Status::Conflict);
     assert_eq!(response.content_type().unwrap(), ContentType::JSON);
+    let res: ErrorResponse = response.into_json().await.unwrap();
+    assert_eq!(res.error_code, ErrorCode::ProofDuplicate);
 
     after_test().await;
 }
@@ -151,4 +177,179 @@ async fn test_submit_proof_with_valid_payload(){
     assert!(!res.proof_id.is_empty());
 
     after_test().await;
-} 
\ No newline at end of file
+} 
+
+#[tokio::test]
+async fn test_submit_proof_with_hex_encoded_fields(){
+    let client = setup().await;
+
+    before_test(client).await;
+
+    // proof and pis as hex encoded objects instead of byte arrays
+    let payload = include_str!("common/data/proof/snark.json");
+    let mut proof: serde_json::Value = serde_json::from_str(payload).unwrap();
+    for field in ["proof", "pis"] {
+        let bytes: Vec<u8> = serde_json::from_value(proof[field].clone()).unwrap();
+        proof[field] = serde_json::json!({"encoding": "hex", "data": hex::encode(bytes)});
+    }
+
+    let response = client.post("/proof").header(Header::new("Authorization", format!("Bearer {}", AUTH_TOKEN)))
+                                .header(ContentType::JSON).body(proof.to_string()).dispatch().await;
+
+    assert_eq!(response.status(), Status::Ok);
+    let res: SubmitProofResponse = response.into_json().await.unwrap();
+    assert!(!res.proof_id.is_empty());
+
+    after_test().await;
+}
+
+#[tokio::test]
+async fn test_submit_proof_with_untagged_string_field(){
+    let client = setup().await;
+
+    // a bare string does not say how it is encoded
+    let payload = include_str!("common/data/proof/snark.json");
+    let mut proof: serde_json::Value = serde_json::from_str(payload).unwrap();
+    let bytes: Vec<u8> = serde_json::from_value(proof["proof"].clone()).unwrap();
+    proof["proof"] = serde_json::Value::String(hex::encode(bytes));
+
+    let response = client.post("/proof").header(Header::new("Authorization", format!("Bearer {}", AUTH_TOKEN)))
+                                .header(ContentType::JSON).body(proof.to_string()).dispatch().await;
+
+    assert_eq!(response.status(), Status::UnprocessableEntity);
+    let res: ErrorResponse = response.into_json().await.unwrap();
+    assert_eq!(res.error_code, ErrorCode::InvalidPayload);
+}
+
+#[tokio::test]
+async fn test_submit_proof_with_octet_stream_proof(){
+    let client = setup().await;
+
+    before_test(client).await;
+
+    // raw proof as body, metadata and hex encoded pis in the query
+    let payload = include_str!("common/data/proof/snark.json");
+    let proof: serde_json::Value = serde_json::from_str(payload).unwrap();
+    let proof_bytes: Vec<u8> = serde_json::from_value(proof["proof"].clone()).unwrap();
+    let pis: Vec<u8> = serde_json::from_value(proof["pis"].clone()).unwrap();
+    let uri = format!("/proof?circuit_hash={}&proof_type=Groth16&pis={}&pis_encoding=hex", proof["circuit_hash"].as_str().unwrap(), hex::encode(pis));
+
+    let response = client.post(uri).header(Header::new("Authorization", format!("Bearer {}", AUTH_TOKEN)))
+                                .header(ContentType::Binary).body(proof_bytes).dispatch().await;
+
+    assert_eq!(response.status(), Status::Ok);
+    let res: SubmitProofResponse = response.into_json().await.unwrap();
+    assert!(!res.proof_id.is_empty());
+
+    after_test().await;
+}
+
+#[tokio::test]
+async fn test_submit_proof_with_multipart_proof(){
+    let client = setup().await;
+
+    before_test(client).await;
+
+    let payload = include_str!("common/data/proof/snark.json");
+    let proof: serde_json::Value = serde_json::from_str(payload).unwrap();
+    let proof_bytes: Vec<u8> = serde_json::from_value(proof["proof"].clone()).unwrap();
+    let pis: Vec<u8> = serde_json::from_value(proof["pis"].clone()).unwrap();
+
+    let boundary = "quantum-proof-boundary";
+    let mut body = Vec::new();
+    for (name, value) in [("circuit_hash", proof["circuit_hash"].as_str().unwrap().as_bytes()), ("proof_type", "Groth16".as_bytes())] {
+        body.extend_from_slice(format!("--{}\r\nContent-Disposition: form-data; name=\"{}\"\r\n\r\n", boundary, name).as_bytes());
+        body.extend_from_slice(value);
+        body.extend_from_slice(b"\r\n");
+    }
+    for (name, value) in [("proof", &proof_bytes), ("pis", &pis)] {
+        body.extend_from_slice(format!("--{}\r\nContent-Disposition: form-data; name=\"{}\"; filename=\"{}\"\r\nContent-Type: application/octet-stream\r\n\r\n", boundary, name, name).as_bytes());
+        body.extend_from_slice(value);
+        body.extend_from_slice(b"\r\n");
+    }
+    body.extend_from_slice(format!("--{}--\r\n", boundary).as_bytes());
+
+    let response = client.post("/proof").header(Header::new("Authorization", format!("Bearer {}", AUTH_TOKEN)))
+                                .header(ContentType::FormData.with_params(("boundary", boundary))).body(body).dispatch().await;
+
+    assert_eq!(response.status(), Status::Ok);
+    let res: SubmitProofResponse = response.into_json().await.unwrap();
+    assert!(!res.proof_id.is_empty());
+
+    after_test().await;
+}
+
+// borsh bytes of a json byte array field
+fn get_bytes_field(payload: &serde_json::Value, field: &str) -> Vec<u8> {
+    serde_json::from_value(payload[field].clone()).unwrap()
+}
+
+#[tokio::test]
+async fn test_submit_ark_groth16_proof_with_valid_payload(){
+    let client = setup().await;
+
+    // the snarkjs groth16 fixtures converted to the ark-groth16 format
+    let circuit: serde_json::Value = serde_json::from_str(include_str!("common/data/circuit/snark.json")).unwrap();
+    let snarkjs_vkey = SnarkJSGroth16Vkey::deserialize_vkey(&mut get_bytes_field(&circuit, "vkey").as_slice()).unwrap();
+    let mut vkey_bytes = vec![];
+    snarkjs_vkey.get_ark_vk_for_snarkjs_groth16().unwrap().serialize_compressed(&mut vkey_bytes).unwrap();
+    let vkey = ArkGroth16Vkey { vkey_bytes }.serialize_vkey().unwrap();
+
+    let proof: serde_json::Value = serde_json::from_str(include_str!("common/data/proof/snark.json")).unwrap();
+    let snarkjs_proof = SnarkJSGroth16Proof::deserialize_proof(&mut get_bytes_field(&proof, "proof").as_slice()).unwrap();
+    let mut proof_bytes = vec![];
+    snarkjs_proof.get_ark_proof_for_snarkjs_groth16_proof().unwrap().serialize_compressed(&mut proof_bytes).unwrap();
+    let snarkjs_pis = SnarkJSGroth16Pis::deserialize_pis(&mut get_bytes_field(&proof, "pis").as_slice()).unwrap();
+    let mut pis_bytes = vec![];
+    snarkjs_pis.get_ark_pis_for_snarkjs_groth16_pis().unwrap().serialize_compressed(&mut pis_bytes).unwrap();
+
+    let payload = serde_json::json!({"vkey": vkey, "proof_type": "ArkGroth16"}).to_string();
+    let response = client.post("/register_circuit").header(Header::new("Authorization", format!("Bearer {}", AUTH_TOKEN)))
+                        .header(ContentType::JSON).body(payload).dispatch().await;
+    let res: RegisterCircuitResponse = response.into_json().await.unwrap();
+    let _ = update_circuit_redn_status_user_circuit_data_completed(get_pool().await, &res.circuit_hash).await;
+
+    let payload = serde_json::json!({
+        "proof": ArkGroth16Proof { proof_bytes }.serialize_proof().unwrap(),
+        "pis": ArkGroth16Pis { pis_bytes }.serialize_pis().unwrap(),
+        "circuit_hash": res.circuit_hash,
+        "proof_type": "ArkGroth16",
+    }).to_string();
+    let response = client.post("/proof").header(Header::new("Authorization", format!("Bearer {}", AUTH_TOKEN)))
+                                .header(ContentType::JSON).body(payload).dispatch().await;
+
+    assert_eq!(response.status(), Status::Ok);
+    let res: SubmitProofResponse = response.into_json().await.unwrap();
+    assert!(!res.proof_id.is_empty());
+
+    after_test().await;
+}
+
+#[tokio::test]
+async fn test_submit_proof_batch_with_partial_failure(){
+    let client = setup().await;
+
+    before_test(client).await;
+
+    let payload = include_str!("common/data/proof/snark.json");
+    let invalid_proof_payload = include_str!("common/data/invalid/proof/invalid_proof.json");
+    let proof: serde_json::Value = serde_json::from_str(payload).unwrap();
+    let batch_payload = format!(r#"{{"circuit_hash": {}, "proofs": [{}, {}]}}"#, proof["circuit_hash"], payload, invalid_proof_payload);
+
+    let response = client.post("/proofs/batch").header(Header::new("Authorization", format!("Bearer {}", AUTH_TOKEN)))
+                                .header(ContentType::JSON).body(batch_payload).dispatch().await;
+
+    // invalid proof should not fail the whole batch
+    assert_eq!(response.status(), Status::Ok);
+    assert_eq!(response.content_type().unwrap(), ContentType::JSON);
+
+    let res: SubmitProofBatchResponse = response.into_json().await.unwrap();
+    assert_eq!(res.results.len(), 2);
+    assert!(res.results[0].proof_id.is_some());
+    assert!(res.results[0].error.is_none());
+    assert!(res.results[1].proof_id.is_none());
+    assert!(res.results[1].error.is_some());
+    assert_eq!(res.results[1].error_code, Some(ErrorCode::ProofInvalid));
+
+    after_test().await;
+}
@@ -102,4 +102,14 @@ async fn test_proof_status_with_valid_proof_id(){
     assert_eq!(res.verification_contract, config_data.verification_contract_address.to_string());
 
     after_test().await;
-}
\ No newline at end of file
+}
+
+#[tokio::test]
+async fn test_proof_events_with_unknown_proof_hash(){
+    let client = setup().await;
+
+    let response = client.get("/proof/0x0000/events").header(Header::new("Authorization", format!("Bearer {}", AUTH_TOKEN))).dispatch().await;
+
+    assert_eq!(response.status(), Status::NotFound);
+    assert_eq!(response.content_type().unwrap(), ContentType::JSON);
+}
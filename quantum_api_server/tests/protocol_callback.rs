@@ -0,0 +1,58 @@
+mod common;
+use common::{repository::protocol_repository::clear_protocol_callback, setup};
+use quantum_api_server::{connection::get_pool, types::protocol_callback::RegisterCallbackResponse};
+use rocket::http::{ContentType, Header, Status};
+
+const  AUTH_TOKEN: &str = "b3047d47c5d6551744680f5c3ba77de90acb84055eefdcbb";
+
+async fn after_test() {
+    let _ = clear_protocol_callback(get_pool().await, "electron").await;
+}
+
+#[tokio::test]
+async fn test_register_callback_with_invalid_url(){
+    let client = setup().await;
+    let payload = r##"{
+        "callback_url": "ftp://localhost/callback"
+    }"##;
+
+    let response = client.post("/protocol/callback").header(Header::new("Authorization", format!("Bearer {}", AUTH_TOKEN)))
+                                .header(ContentType::JSON).body(payload).dispatch().await;
+
+    assert_eq!(response.status(), Status::BadRequest);
+    assert_eq!(response.content_type().unwrap(), ContentType::JSON);
+}
+
+#[tokio::test]
+async fn test_register_callback_with_valid_payload(){
+    let client = setup().await;
+    let payload = r##"{
+        "callback_url": "https://93.184.216.34:9000/callback"
+    }"##;
+
+    let response = client.post("/protocol/callback").header(Header::new("Authorization", format!("Bearer {}", AUTH_TOKEN)))
+                                .header(ContentType::JSON).body(payload).dispatch().await;
+
+    assert_eq!(response.status(), Status::Ok);
+    assert_eq!(response.content_type().unwrap(), ContentType::JSON);
+
+    let res: RegisterCallbackResponse = response.into_json().await.unwrap();
+    assert_eq!(res.callback_url, "https://93.184.216.34:9000/callback");
+    assert!(!res.callback_secret.is_empty());
+
+    after_test().await;
+}
+
+#[tokio::test]
+async fn test_register_callback_with_internal_url(){
+    let client = setup().await;
+    let payload = r##"{
+        "callback_url": "https://169.254.169.254/latest/meta-data"
+    }"##;
+
+    let response = client.post("/protocol/callback").header(Header::new("Authorization", format!("Bearer {}", AUTH_TOKEN)))
+                                .header(ContentType::JSON).body(payload).dispatch().await;
+
+    assert_eq!(response.status(), Status::BadRequest);
+    assert_eq!(response.content_type().unwrap(), ContentType::JSON);
+}
@@ -64,7 +64,7 @@ async fn test_get_protocol_proof_with_invalid_proof_id(){
     let invalid_proof_id = "a4896a3f93bf4bf58378e579f3cf193bb4af1022af7d2089f37d8bae7157b85f";
     let response = client.get(format!("/protocol_proof/merkle/{}", invalid_proof_id)).header(Header::new("Authorization", format!("Bearer {}", AUTH_TOKEN))).dispatch().await;
 
-    assert_eq!(response.status(), Status::InternalServerError);
+    assert_eq!(response.status(), Status::NotFound);
     assert_eq!(response.content_type().unwrap(), ContentType::JSON);
 }
 
@@ -80,7 +80,7 @@ async fn test_get_protocol_proof_with_invalid_proof_id(){
 //     let response = client.get(format!("/protocol_proof/merkle/{}", proof_id)).header(Header::new("Authorization", format!("Bearer {}", AUTH_TOKEN))).dispatch().await;
     
 //     println!("response: {:?}", response);
-//     assert_eq!(response.status(), Status::InternalServerError);
+//     assert_eq!(response.status(), Status::NotFound);
 //     assert_eq!(response.content_type().unwrap(), ContentType::JSON);
 
 //     after_test(proof_ids).await;
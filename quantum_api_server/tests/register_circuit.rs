@@ -1,7 +1,9 @@
 mod common;
 
 use common::repository::{task_repository::{delete_all_task_data, get_task_data_count_from_circuit_hash}, user_circuit_data_repository::delete_all_user_circuit_data};
-use quantum_api_server::{connection::get_pool, types::register_circuit::RegisterCircuitResponse};
+use quantum_api_server::{connection::get_pool, error::error::ErrorResponse, types::register_circuit::RegisterCircuitResponse};
+use ark_serialize::CanonicalSerialize;
+use quantum_types::{enums::error_code::ErrorCode, traits::vkey::Vkey, types::{ark_groth16::ArkGroth16Vkey, snarkjs_groth16::SnarkJSGroth16Vkey}};
 use rocket::http::{ContentType, Header, Status};
 
 use crate::common::setup; 
@@ -31,7 +33,7 @@ async fn test_register_circuit_with_missing_data_fields(){
 
     let response = client.post("/register_circuit").header(Header::new("Authorization", format!("Bearer {}", AUTH_TOKEN)))
     .header(ContentType::JSON).body(payload).dispatch().await;
-    assert_eq!(response.status(), Status::InternalServerError);
+    assert_eq!(response.status(), Status::UnprocessableEntity);
     assert_eq!(response.content_type().unwrap(), ContentType::JSON);
 }
 
@@ -66,19 +68,33 @@ async fn test_register_circuit_should_not_register_same_circuit(){
     after_test().await;
 }
 
+// the vkeys themselves are checked in the quantum_types scheme modules, this only covers how registration reports them
 #[tokio::test]
-async fn test_register_circuit_with_invalid_vkey() {
-    let client = setup().await;
-    let payload = include_str!("common/data/invalid/circuit/invalid_vkey.json");
-    let response = client.post("/register_circuit").header(Header::new("Authorization", format!("Bearer {}", AUTH_TOKEN)))
-    .header(ContentType::JSON).body(payload).dispatch().await;
+async fn test_register_circuit_with_rejected_vkey() {
+    let cases = [
+        ("invalid_vkey.json", include_str!("common/data/invalid/circuit/invalid_vkey.json"), Status::UnprocessableEntity, ErrorCode::VkeyInvalid),
+        ("invalid_gnark_groth16_bls12_381_vkey.json", include_str!("common/data/invalid/circuit/invalid_gnark_groth16_bls12_381_vkey.json"), Status::UnprocessableEntity, ErrorCode::VkeyInvalid),
+        ("invalid_ark_groth16_vkey.json", include_str!("common/data/invalid/circuit/invalid_ark_groth16_vkey.json"), Status::UnprocessableEntity, ErrorCode::VkeyInvalid),
+        ("invalid_nitro_att_policy_vkey.json", include_str!("common/data/invalid/circuit/invalid_nitro_att_policy_vkey.json"), Status::UnprocessableEntity, ErrorCode::VkeyInvalid),
+        ("unsupported_risc0_version_vkey.json", include_str!("common/data/invalid/circuit/unsupported_risc0_version_vkey.json"), Status::UnprocessableEntity, ErrorCode::VkeyInvalid),
+        // schemes without a deployed reduction guest are refused before their vkey is looked at
+        ("invalid_halo2_ipa_vkey.json", include_str!("common/data/invalid/circuit/invalid_halo2_ipa_vkey.json"), Status::BadRequest, ErrorCode::UnsupportedScheme),
+        ("insecure_plonky3_vkey.json", include_str!("common/data/invalid/circuit/insecure_plonky3_vkey.json"), Status::BadRequest, ErrorCode::UnsupportedScheme),
+        ("invalid_sp1_groth16_vkey.json", include_str!("common/data/invalid/circuit/invalid_sp1_groth16_vkey.json"), Status::BadRequest, ErrorCode::UnsupportedScheme),
+    ];
 
-    // validating status
-    assert_eq!(response.status(), Status::InternalServerError);
-    assert_eq!(response.content_type().unwrap(), ContentType::JSON);
+    let client = setup().await;
+    for (name, payload, status, error_code) in cases {
+        let response = client.post("/register_circuit").header(Header::new("Authorization", format!("Bearer {}", AUTH_TOKEN)))
+        .header(ContentType::JSON).body(payload).dispatch().await;
+
+        assert_eq!(response.status(), status, "{}", name);
+        assert_eq!(response.content_type().unwrap(), ContentType::JSON, "{}", name);
+        let res: ErrorResponse = response.into_json().await.unwrap();
+        assert_eq!(res.error_code, error_code, "{}", name);
+    }
 }
 
-
 #[tokio::test]
 async fn test_register_circuit_should_return_saved_reduction_circuit(){
     let client = setup().await;
@@ -113,8 +129,11 @@ async fn test_register_circuit_with_invalid_proof_type(){
     
 
     // validating status
-    assert_eq!(response.status(), Status::InternalServerError);
+    assert_eq!(response.status(), Status::UnprocessableEntity);
     assert_eq!(response.content_type().unwrap(), ContentType::JSON);
+    let res: ErrorResponse = response.into_json().await.unwrap();
+    assert_eq!(res.error_code, ErrorCode::InvalidPayload);
+    assert_eq!(res.path, Some("proof_type".to_string()));
 }
 
 
@@ -139,4 +158,58 @@ async fn test_register_circuit_with_valid_data_fields(){
 
     // deleting circuit entry
     after_test().await;
-}
\ No newline at end of file
+}
+
+#[tokio::test]
+async fn test_register_circuit_with_octet_stream_vkey(){
+    let client = setup().await;
+    let payload = include_str!("common/data/circuit/snark.json");
+    let response = client.post("/register_circuit").header(Header::new("Authorization", format!("Bearer {}", AUTH_TOKEN)))
+                       .header(ContentType::JSON).body(payload).dispatch().await;
+    let json_res: RegisterCircuitResponse = response.into_json().await.unwrap();
+
+    // same vkey sent as raw borsh bytes
+    let circuit: serde_json::Value = serde_json::from_str(payload).unwrap();
+    let vkey: Vec<u8> = serde_json::from_value(circuit["vkey"].clone()).unwrap();
+    let response = client.post("/register_circuit?proof_type=Groth16").header(Header::new("Authorization", format!("Bearer {}", AUTH_TOKEN)))
+                       .header(ContentType::Binary).body(vkey).dispatch().await;
+
+    assert_eq!(response.status(), Status::Ok);
+    let binary_res: RegisterCircuitResponse = response.into_json().await.unwrap();
+    assert_eq!(json_res.circuit_hash, binary_res.circuit_hash);
+
+    after_test().await;
+}
+
+// the snarkjs groth16 fixture vkey in the ark-groth16 format
+fn get_ark_groth16_circuit_payload() -> String {
+    let circuit: serde_json::Value = serde_json::from_str(include_str!("common/data/circuit/snark.json")).unwrap();
+    let vkey_bytes: Vec<u8> = serde_json::from_value(circuit["vkey"].clone()).unwrap();
+    let snarkjs_vkey = SnarkJSGroth16Vkey::deserialize_vkey(&mut vkey_bytes.as_slice()).unwrap();
+    let mut ark_vkey_bytes = vec![];
+    snarkjs_vkey.get_ark_vk_for_snarkjs_groth16().unwrap().serialize_compressed(&mut ark_vkey_bytes).unwrap();
+    let vkey = ArkGroth16Vkey { vkey_bytes: ark_vkey_bytes }.serialize_vkey().unwrap();
+    serde_json::json!({"vkey": vkey, "proof_type": "ArkGroth16"}).to_string()
+}
+
+#[tokio::test]
+async fn test_register_ark_groth16_circuit_with_valid_vkey(){
+    let client = setup().await;
+    let response = client.post("/register_circuit").header(Header::new("Authorization", format!("Bearer {}", AUTH_TOKEN)))
+                       .header(ContentType::JSON).body(get_ark_groth16_circuit_payload()).dispatch().await;
+
+    assert_eq!(response.status(), Status::Ok);
+    let res: RegisterCircuitResponse = response.into_json().await.unwrap();
+    assert!(!res.circuit_hash.is_empty());
+
+    // the same circuit can not be registered again in the snarkjs format
+    let payload = include_str!("common/data/circuit/snark.json");
+    let response = client.post("/register_circuit").header(Header::new("Authorization", format!("Bearer {}", AUTH_TOKEN)))
+                       .header(ContentType::JSON).body(payload).dispatch().await;
+
+    assert_eq!(response.status(), Status::BadRequest);
+    let res: ErrorResponse = response.into_json().await.unwrap();
+    assert_eq!(res.error_code, ErrorCode::SchemeMismatch);
+
+    after_test().await;
+}
@@ -1,5 +1,5 @@
 use anyhow::{anyhow, Result as AnyhowResult};
-use quantum_utils::{error_line, keccak::decode_keccak_hex};
+use quantum_utils::{error_line, keccak::decode_keccak_hex, retry::{retry_with_policy, FatalError, RetryPolicy}};
 use tracing::info;
 
 pub fn get_f64_from_json_value_object(json_value: serde_json::Value) -> Option<f64> {
@@ -7,84 +7,65 @@ pub fn get_f64_from_json_value_object(json_value: serde_json::Value) -> Option<f
 }
 
 // gas cost in gwei
-pub async fn get_gas_cost() -> AnyhowResult<f64> {
+pub async fn get_gas_cost(retry_policy: &RetryPolicy) -> AnyhowResult<f64> {
     let gas_cost_rpc = &std::env::var("GAS_COST_RPC")?;
     let gas_cost_api_key = &std::env::var("GAS_COST_API_KEY")?;
 
     info!("{}", format!("Fetching Gas Cost: {gas_cost_rpc}"));
     let client = reqwest::Client::new();
 
-    let mut base_fees: AnyhowResult<f64> = Err(anyhow!(error_line!("missing base fees")));
-
-    for _ in 0..5 {
-        let res = client
-            .get(gas_cost_rpc)
-            .header("Authorization", gas_cost_api_key)
-            .send()
-            .await?;
-        let json: serde_json::Value = res.json().await?;
-        match json["blockPrices"].as_array() {
-            Some(block_prices) => {
-                let base_fees_option =
-                    get_f64_from_json_value_object(block_prices[0]["baseFeePerGas"].clone());
-                match base_fees_option {
-                    Some(some_base_fees) => {
-                        base_fees = Ok(some_base_fees);
-                        break;
-                    },
-                    None => {
-                        info!("not able to get the base fee from get gas api. Trying again...");
-                        base_fees = Err(anyhow!(error_line!(
-                            "not able to get the base fee from get gas api"
-                        )));
-                        continue;
-                    }
-                };
-            }
-            None => {
-                info!(
-                    "not able to find block prices in fetching gas cost response. Trying again..."
-                );
-                base_fees = Err(anyhow!(error_line!(
-                    "not able to find block prices in fetching gas cost response"
-                )));
-                continue;
-            }
-        };
-    }
+    retry_with_policy(retry_policy, "fetching gas cost", || {
+        fetch_gas_cost(&client, gas_cost_rpc, gas_cost_api_key)
+    }).await
+}
 
-    base_fees
+async fn fetch_gas_cost(client: &reqwest::Client, gas_cost_rpc: &str, gas_cost_api_key: &str) -> AnyhowResult<f64> {
+    let res = client
+        .get(gas_cost_rpc)
+        .header("Authorization", gas_cost_api_key)
+        .send()
+        .await?;
+    let json = get_json_from_response(res).await?;
+    let block_prices = json["blockPrices"].as_array().ok_or(anyhow!(error_line!(
+        "not able to find block prices in fetching gas cost response"
+    )))?;
+    let base_fees = block_prices
+        .get(0)
+        .and_then(|block_price| get_f64_from_json_value_object(block_price["baseFeePerGas"].clone()))
+        .ok_or(anyhow!(error_line!("not able to get the base fee from get gas api")))?;
+    Ok(base_fees)
 }
 
 // eth_price in USD
-pub async fn get_eth_price() -> AnyhowResult<f64> {
+pub async fn get_eth_price(retry_policy: &RetryPolicy) -> AnyhowResult<f64> {
     let eth_price_rpc = &std::env::var("ETH_PRICE_RPC")?;
     info!("{}", format!("Fetching Ethereum Price: {eth_price_rpc}"));
 
-    let mut usd_price: AnyhowResult<f64> = Err(anyhow!(error_line!("missing usd_price")));
+    retry_with_policy(retry_policy, "fetching eth price", || {
+        fetch_eth_price(eth_price_rpc)
+    }).await
+}
+
+async fn fetch_eth_price(eth_price_rpc: &str) -> AnyhowResult<f64> {
+    let res = reqwest::get(eth_price_rpc).await?;
+    let json = get_json_from_response(res).await?;
+    info!("eth_price_rpc json: {:?}", json);
 
-    for _ in 0..5 {
-        let res = reqwest::get(eth_price_rpc).await?;
-        let json: serde_json::Value = res.json().await?;
-        info!("eth_price_rpc json: {:?}", json);
+    let usd_price = get_f64_from_json_value_object(json["USD"].clone())
+        .ok_or(anyhow!(error_line!("not able to get the USD fee from get gas api")))?;
+    Ok(usd_price)
+}
 
-        let usd_price_option = get_f64_from_json_value_object(json["USD"].clone());
-        match usd_price_option {
-            Some(some_usd_price) => {
-                usd_price = Ok(some_usd_price);
-                break;
-            },
-            None => {
-                info!("not able to get the USD fee from get gas api. Trying again...");
-                usd_price = Err(anyhow!(error_line!(
-                    "not able to get the USD fee from get gas api"
-                )));
-                continue;
-            }
-        };
+// client errors (4xx) won't go away on retry, everything else is treated as transient
+async fn get_json_from_response(res: reqwest::Response) -> AnyhowResult<serde_json::Value> {
+    let status = res.status();
+    if status.is_client_error() {
+        return Err(anyhow!(FatalError(error_line!(format!("request rejected with status: {}", status)))));
     }
-
-    usd_price
+    if !status.is_success() {
+        return Err(anyhow!(error_line!(format!("request failed with status: {}", status))));
+    }
+    Ok(res.json().await?)
 }
 
 pub fn get_bytes_from_hex_string(value: &str) ->AnyhowResult<[u8; 32]> {
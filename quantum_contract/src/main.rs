@@ -9,6 +9,7 @@ use contract::{gen_quantum_structs, get_quantum_contract, update_quantum_contrac
 use contract_utils::get_bytes_from_hex_string;
 use dotenv::dotenv;
 use ethers::utils::hex::ToHexExt;
+use quantum_db::webhook::run_webhook_dispatcher;
 use quantum_db::repository::{
     cost_saved_repository::udpate_cost_saved_data,
     proof_repository::{get_proofs_in_superproof_id, update_proof_status},
@@ -20,8 +21,9 @@ use quantum_types::{enums::proving_schemes::ProvingSchemes, types::{db::user_cir
 use quantum_types::{
     enums::{proof_status::ProofStatus, superproof_status::SuperproofStatus},
     traits::proof::Proof,
+    types::config::ConfigData,
 };
-use quantum_utils::{error_line, logger::initialize_logger};
+use quantum_utils::{error_line, logger::initialize_logger, retry::{retry_with_policy, RetryPolicy}};
 
 use anyhow::{anyhow, Result as AnyhowResult};
 use sqlx::types::chrono::NaiveDateTime;
@@ -36,12 +38,12 @@ use crate::{
 const SUPERPROOF_SUBMISSION_RETRY: u64 = 5 * 60;
 const SUPERPROOF_SUBMISSION_DURATION: u64 = 15 * 60;
 const SLEEP_DURATION_WHEN_NEW_SUPERPROOF_IS_NOT_VERIFIED: u64 = 30;
-const RETRY_COUNT: u64 = 3;
 const NITRO_PROOF_DIRECT_VERIFICATION_GAS_COST: u64= 20000000 - 300000;
 
 
 async fn initialize_superproof_submission_loop(
     superproof_submission_duration: Duration,
+    config_data: &ConfigData,
 ) -> AnyhowResult<()> {
     loop {
         info!("----checking for new superproof to submit----");
@@ -126,7 +128,7 @@ async fn initialize_superproof_submission_loop(
         println!("batch root {:?}", batch_root);
         println!("gnark proof {:?}", gnark_proof);
 
-        let (transaction_hash, gas_used) = make_smart_contract_call_with_retry(batch_root, &gnark_proof).await?;
+        let (transaction_hash, gas_used) = make_smart_contract_call_with_retry(batch_root, &gnark_proof, &config_data.retry.contract).await?;
 
         // update tx data in DB for superproof
         update_superproof_fields_after_onchain_submission(
@@ -146,8 +148,8 @@ async fn initialize_superproof_submission_loop(
         }
 
         // update gas data in DB for superproof
-        let gas_cost = get_gas_cost().await?;
-        let eth_price = get_eth_price().await?;
+        let gas_cost = get_gas_cost(&config_data.retry.price_feed).await?;
+        let eth_price = get_eth_price(&config_data.retry.price_feed).await?;
         let total_cost_usd = calc_total_cost_usd(gas_used, gas_cost, eth_price);
         update_superproof_gas_data(
             get_pool().await,
@@ -176,31 +178,16 @@ async fn initialize_superproof_submission_loop(
     }
 }
 
-async fn make_smart_contract_call_with_retry(batch_root: [u8; 32], gnark_proof: &SuperproofGnarkGroth16Proof) -> AnyhowResult<(String, u64)> {
-    let mut retry_count = 0;
-    let transaction_hash;
+async fn make_smart_contract_call_with_retry(batch_root: [u8; 32], gnark_proof: &SuperproofGnarkGroth16Proof, retry_policy: &RetryPolicy) -> AnyhowResult<(String, u64)> {
     let quantum_contract = get_quantum_contract()?;
-    let gas_used;
-    let mut error = Err(anyhow!(error_line!("Error initialized")));
-    while retry_count <= RETRY_COUNT {
-        match update_quantum_contract_state(&quantum_contract, batch_root, &gnark_proof).await {
-            Ok(receipt) =>{
-                let transaction_hash_string = receipt.transaction_hash.encode_hex();
-                let transaction_hash_string = String::from("0x") + &transaction_hash_string;
-                transaction_hash = transaction_hash_string;
-                gas_used = receipt.gas_used.ok_or_else(|| anyhow::anyhow!("Gas used is not found"))?.as_u64();
-                return Ok((transaction_hash, gas_used));
-            }
-            Err(e) => {
-                retry_count = retry_count+1;
-                error!("error occured in smart contract call, retrying count {:?}, error: {:?}",retry_count, error_line!(e));
-                info!("Trying again in 10 seconds");
-                sleep(Duration::from_secs(10)).await;
-                error =  Err(anyhow!(error_line!(e)));
-            },
-        }
-    }
-    error
+    let receipt = retry_with_policy(retry_policy, "smart contract call", || {
+        update_quantum_contract_state(&quantum_contract, batch_root, gnark_proof)
+    }).await?;
+
+    let transaction_hash_string = receipt.transaction_hash.encode_hex();
+    let transaction_hash = String::from("0x") + &transaction_hash_string;
+    let gas_used = receipt.gas_used.ok_or_else(|| anyhow::anyhow!("Gas used is not found"))?.as_u64();
+    Ok((transaction_hash, gas_used))
 }
 
 fn calc_total_cost_usd(gas_used: u64, gas_cost: f64, eth_price: f64) -> f64 {
@@ -220,9 +207,16 @@ async fn main() {
     info!(" --- Starting quantum contract --- ");
     let _guard = initialize_logger("quantum_contract.log");
     let _db_pool = get_pool().await;
+    let config_data = ConfigData::new("./config.yaml");
+    tokio::spawn(run_webhook_dispatcher(
+        get_pool().await,
+        format!("contract-{}-{}", std::env::var("HOSTNAME").unwrap_or(String::from("contract")), std::process::id()),
+        Duration::from_secs(config_data.webhook_poll_secs),
+        config_data.retry.webhook.clone(),
+    ));
     let superproof_submission_duration = Duration::from_secs(SUPERPROOF_SUBMISSION_DURATION);
     loop {
-        match initialize_superproof_submission_loop(superproof_submission_duration).await {
+        match initialize_superproof_submission_loop(superproof_submission_duration, &config_data).await {
             Ok(_) => {
                 info!("contract poller exit without any error");
                 info!("Restarting in {} mins...", (SUPERPROOF_SUBMISSION_RETRY/60).to_string());
@@ -3,6 +3,9 @@
 pub enum CustomError {
     // #[resp("{0}")]
     DB(String),
+
+    // lookup by a client supplied key matched no row
+    NotFound(String),
 }
 
 
@@ -10,7 +13,7 @@ impl std::fmt::Display for CustomError {
     fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
         match self {
            CustomError::DB(err_msg) => write!(fmt, "Error {}.", err_msg),
-           _ => write!(fmt, "An unknown error occurred"), 
+           CustomError::NotFound(err_msg) => write!(fmt, "Not found {}.", err_msg),
         }
     }
 }
@@ -1,2 +1,3 @@
 pub mod repository;
-pub mod error;
\ No newline at end of file
+pub mod error;
+pub mod webhook;
\ No newline at end of file
@@ -4,6 +4,7 @@ use anyhow::{anyhow, Result as AnyhowResult, Error as AnyhowError};
 use sqlx::mysql::MySqlRow;
 use tracing::info;
 use quantum_types::enums::proving_schemes::ProvingSchemes;
+use quantum_types::enums::risc0_receipt_kind::Risc0ReceiptKind;
 use quantum_types::types::db::bonsai_image::BonsaiImage;
 use crate::error::error::CustomError;
 use std::str::FromStr;
@@ -58,6 +59,13 @@ fn get_bonsai_image_from_mysql_row(row: &MySqlRow) -> AnyhowResult<BonsaiImage,
         }?);
     }
     
+    // images registered before the column was added take succinct receipts only
+    let accepted_receipt_kinds_string: Option<String> = row.try_get_unchecked("accepted_receipt_kinds")?;
+    let accepted_receipt_kinds = match accepted_receipt_kinds_string {
+        Some(kinds) => parse_receipt_kinds(&kinds)?,
+        None => vec![Risc0ReceiptKind::Succinct],
+    };
+
     let circuit_verifying_id_string: String = row.try_get_unchecked("circuit_verifying_id")?;
     let circuit_verifying_id = parse_string_to_u32_array(&circuit_verifying_id_string)?;
     let bonsai_image = BonsaiImage {
@@ -65,11 +73,27 @@ fn get_bonsai_image_from_mysql_row(row: &MySqlRow) -> AnyhowResult<BonsaiImage,
         elf_file_path: row.try_get_unchecked("elf_file_path")?,
         circuit_verifying_id,
         proving_scheme: proving_scheme,
-        is_aggregation_image_id: row.try_get_unchecked("is_aggregation_image_id")?
+        is_aggregation_image_id: row.try_get_unchecked("is_aggregation_image_id")?,
+        accepted_receipt_kinds,
     };
     Ok(bonsai_image)
 }
 
+// comma separated kinds, e.g. "Composite,Succinct". Groth16 receipts go to a guest taking their seal as input,
+// so an image accepting them accepts no other kind
+fn parse_receipt_kinds(s: &str) -> AnyhowResult<Vec<Risc0ReceiptKind>> {
+    let mut kinds = vec![];
+    for kind in s.split(',').filter(|kind| !kind.trim().is_empty()) {
+        kinds.push(match Risc0ReceiptKind::from_str(kind) {
+            Ok(k) => Ok(k),
+            Err(e) => Err(anyhow!(CustomError::DB(error_line!(e))))
+        }?);
+    }
+    if kinds.contains(&Risc0ReceiptKind::Groth16) && kinds.len() > 1 {
+        return Err(anyhow!(CustomError::DB(error_line!(format!("groth16 receipts can not share a bonsai image with other kinds: {}", s)))));
+    }
+    Ok(kinds)
+}
 
 fn parse_string_to_u32_array(s: &str) -> AnyhowResult<[u32; 8]> {
     // Remove the square brackets and split by commas
@@ -87,4 +111,17 @@ fn parse_string_to_u32_array(s: &str) -> AnyhowResult<[u32; 8]> {
         Ok(_) => Err(anyhow!(CustomError::DB(error_line!("not able to pares array of u32")))),
         Err(_) => Err(anyhow!(CustomError::DB(error_line!("not able to pares array of u32")))),
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_receipt_kinds() {
+        assert_eq!(parse_receipt_kinds("Composite, Succinct").unwrap(), vec![Risc0ReceiptKind::Composite, Risc0ReceiptKind::Succinct]);
+        assert_eq!(parse_receipt_kinds("groth16").unwrap(), vec![Risc0ReceiptKind::Groth16]);
+        assert!(parse_receipt_kinds("Succinct,Groth16").is_err());
+        assert!(parse_receipt_kinds("Succinct,Fake").is_err());
+    }
+}
@@ -7,4 +7,7 @@ pub mod protocol;
 pub mod auth;
 pub mod cost_saved_repository;
 
-pub mod bonsai_image;
\ No newline at end of file
+pub mod bonsai_image;
+pub mod webhook_outbox_repository;
+pub mod status_event_repository;
+pub mod worker_lock_repository;
\ No newline at end of file
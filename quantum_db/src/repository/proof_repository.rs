@@ -1,10 +1,10 @@
-use quantum_types::{enums::{proof_status::ProofStatus, proving_schemes::ProvingSchemes}, types::db::proof::Proof};
+use quantum_types::{enums::{proof_status::ProofStatus, proving_schemes::ProvingSchemes, task_status::TaskStatus, task_type::TaskType}, types::db::proof::Proof};
 use sqlx::{mysql::MySqlRow, Execute, MySql, Pool, Row};
 use quantum_utils::error_line;
 use anyhow::{anyhow, Error, Result as AnyhowResult};
 use tracing::info;
 
-use crate::error::error::CustomError;
+use crate::{error::error::CustomError, repository::{status_event_repository::insert_proof_status_event, webhook_outbox_repository::enqueue_proof_status_event}};
 
 pub async fn get_aggregation_waiting_proof_num(pool: &Pool<MySql>) -> AnyhowResult<u64, Error> {
     let query  = sqlx::query("SELECT Count(*) as reduced_proof_count from proof where proof_status = ?")
@@ -37,6 +37,75 @@ pub async fn insert_proof(pool: &Pool<MySql>, proof_hash: &str, pis_path: &str,
     row_affected
 }
 
+pub struct NewProof {
+    pub proof_hash: String,
+    pub pis_path: String,
+    pub proof_path: String,
+    pub proof_status: ProofStatus,
+    pub user_circuit_hash: String,
+    pub pis_json_string: String,
+}
+
+/*
+    Inserts the proofs of a batch submission, along with their proof generation tasks, in a single transaction.
+    Unless `allow_repeats`, a proof whose hash is already stored is skipped and gets `None` as id. The lookup is a
+    locking read on the proof_hash index, so a concurrent batch inserting the same hash waits for this one to commit,
+    or fails on a deadlock, instead of inserting a duplicate.
+ */
+pub async fn insert_proofs_with_tasks(pool: &Pool<MySql>, proofs: &Vec<NewProof>, allow_repeats: bool) -> AnyhowResult<Vec<Option<u64>>, Error> {
+    let mut tx = match pool.begin().await {
+        Ok(tx) => tx,
+        Err(e) => return Err(anyhow!(CustomError::DB(error_line!(e))))
+    };
+
+    let mut proof_ids = Vec::with_capacity(proofs.len());
+    for proof in proofs {
+        if !allow_repeats {
+            let query  = sqlx::query("SELECT id from proof where proof_hash = ? LIMIT 1 FOR UPDATE").bind(&proof.proof_hash);
+
+            info!("{}", query.sql());
+            info!("arguments: {}", proof.proof_hash);
+
+            let existing_proof = match query.fetch_optional(&mut tx).await {
+                Ok(t) => t,
+                Err(e) => return Err(anyhow!(CustomError::DB(error_line!(e))))
+            };
+            if existing_proof.is_some() {
+                info!("proof {} already exists, skipping it", proof.proof_hash);
+                proof_ids.push(None);
+                continue;
+            }
+        }
+
+        let query  = sqlx::query("INSERT into proof(proof_hash, pis_path, proof_path, proof_status, user_circuit_hash, public_inputs) VALUES(?,?,?,?,?,?)")
+                    .bind(&proof.proof_hash).bind(&proof.pis_path).bind(&proof.proof_path).bind(proof.proof_status.as_u8()).bind(&proof.user_circuit_hash).bind(&proof.pis_json_string);
+
+        info!("{}", query.sql());
+        info!("arguments: {}, {}, {}, {}, {}, {}", proof.proof_hash, proof.pis_path, proof.proof_path, proof.proof_status.as_u8(), proof.user_circuit_hash, proof.pis_json_string);
+
+        let proof_id = match query.execute(&mut tx).await {
+            Ok(t) => t.last_insert_id(),
+            Err(e) => return Err(anyhow!(CustomError::DB(error_line!(e))))
+        };
+
+        let query  = sqlx::query("INSERT into task(user_circuit_hash, task_type, task_status, proof_hash, proof_id) VALUES(?,?,?,?,?)")
+                    .bind(&proof.user_circuit_hash).bind(TaskType::ProofGeneration.as_u8()).bind(TaskStatus::NotPicked.as_u8()).bind(&proof.proof_hash).bind(proof_id);
+
+        info!("{}", query.sql());
+        info!("arguments: {}, {}, {}, {}, {}", proof.user_circuit_hash, TaskType::ProofGeneration.as_u8(), TaskStatus::NotPicked.as_u8(), proof.proof_hash, proof_id);
+
+        if let Err(e) = query.execute(&mut tx).await {
+            return Err(anyhow!(CustomError::DB(error_line!(e))));
+        }
+        proof_ids.push(Some(proof_id));
+    }
+
+    match tx.commit().await {
+        Ok(_) => Ok(proof_ids),
+        Err(e) => Err(anyhow!(CustomError::DB(error_line!(e))))
+    }
+}
+
 pub async fn get_latest_proof_by_circuit_hash(pool: &Pool<MySql>, circuit_hash: &str) -> AnyhowResult<Proof> {
     let query  = sqlx::query("SELECT * from proof where user_circuit_hash = ? order by id desc LIMIT 1").bind(circuit_hash);
 
@@ -73,6 +142,7 @@ pub async fn get_proof_by_proof_hash(pool: &Pool<MySql>, proof_hash: &str) -> An
 
     let proof = match query.fetch_one(pool).await{
         Ok(t) => get_proof_from_mysql_row(&t),
+        Err(sqlx::Error::RowNotFound) => Err(anyhow!(CustomError::NotFound(error_line!(format!("proof {}", proof_hash))))),
         Err(e) => Err(anyhow!(CustomError::DB(error_line!(e))))
     };
     proof
@@ -112,14 +182,29 @@ pub async fn get_proofs_in_superproof_id(pool: &Pool<MySql>, superproof_id: u64)
     return Ok(proofs)
 }
 
+// Updates the proof status and, on an actual transition, records the status event and queues its webhook in the same transaction
 pub async fn update_proof_status(pool: &Pool<MySql>, proof_id: u64, proof_status: ProofStatus) -> AnyhowResult<()>{
-    let query  = sqlx::query("UPDATE proof set proof_status = ? where id = ?")
-                .bind(proof_status.as_u8()).bind(proof_id);
+    let mut tx = match pool.begin().await {
+        Ok(tx) => tx,
+        Err(e) => return Err(anyhow!(CustomError::DB(error_line!(e))))
+    };
+
+    let query  = sqlx::query("UPDATE proof set proof_status = ? where id = ? and proof_status != ?")
+                .bind(proof_status.as_u8()).bind(proof_id).bind(proof_status.as_u8());
 
     info!("{}", query.sql());
-    info!("arguments: {}, {}", proof_status.as_u8(), proof_id);
+    info!("arguments: {}, {}, {}", proof_status.as_u8(), proof_id, proof_status.as_u8());
 
-    let row_affected = match query.execute(pool).await {
+    let rows_affected = match query.execute(&mut tx).await {
+        Ok(t) => t.rows_affected(),
+        Err(e) => return Err(anyhow!(CustomError::DB(error_line!(e))))
+    };
+    if rows_affected == 1 {
+        enqueue_proof_status_event(&mut tx, proof_id, &proof_status).await?;
+        insert_proof_status_event(&mut tx, proof_id, &proof_status).await?;
+    }
+
+    let row_affected = match tx.commit().await {
         Ok(_) => Ok(()),
         Err(e) => Err(anyhow!(CustomError::DB(error_line!(e))))
     };
@@ -140,6 +225,21 @@ pub async fn update_reduction_data(pool: &Pool<MySql>, proof_id: u64, reducded_p
     row_affected
 }
 
+// Proofs which are not reduced on bonsai only have a reduction time, no receipt
+pub async fn update_reduction_time(pool: &Pool<MySql>, proof_id: u64, reduction_time: u64) -> AnyhowResult<()> {
+    let query  = sqlx::query("UPDATE proof set reduction_time = ?  where id = ?")
+                .bind(reduction_time).bind(proof_id);
+
+    info!("{}", query.sql());
+    info!("arguments: {}, {}", reduction_time, proof_id);
+
+    let row_affected = match query.execute(pool).await {
+        Ok(_) => Ok(()),
+        Err(e) => Err(anyhow!(CustomError::DB(error_line!(e))))
+    };
+    row_affected
+}
+
 pub async fn update_superproof_id_in_proof(pool: &Pool<MySql>, proof_id: u64, superproof_id: u64) -> AnyhowResult<()> {
     let query  = sqlx::query("UPDATE proof set superproof_id = ? where id = ?")
                 .bind(superproof_id).bind(proof_id);
@@ -154,6 +254,21 @@ pub async fn update_superproof_id_in_proof(pool: &Pool<MySql>, proof_id: u64, su
     row_affected
 }
 
+// Takes the proof out of the superproof it was picked for
+pub async fn clear_superproof_id_in_proof(pool: &Pool<MySql>, proof_id: u64) -> AnyhowResult<()> {
+    let query  = sqlx::query("UPDATE proof set superproof_id = NULL where id = ?")
+                .bind(proof_id);
+
+    info!("{}", query.sql());
+    info!("arguments: {}", proof_id);
+
+    let row_affected = match query.execute(pool).await {
+        Ok(_) => Ok(()),
+        Err(e) => Err(anyhow!(CustomError::DB(error_line!(e))))
+    };
+    row_affected
+}
+
 pub async fn update_session_id_in_proof(pool: &Pool<MySql>, proof_id: u64, session_id: &str) -> AnyhowResult<()> {
     let query  = sqlx::query("UPDATE proof set session_id = ? where id = ?")
                 .bind(session_id).bind(proof_id);
@@ -168,6 +283,24 @@ pub async fn update_session_id_in_proof(pool: &Pool<MySql>, proof_id: u64, sessi
     row_affected
 }
 
+// Reduction cycles of the proofs waiting for aggregation, shared by all workers
+pub async fn get_reduced_cycle_count(pool: &Pool<MySql>) -> AnyhowResult<u64> {
+    let query  = sqlx::query("SELECT CAST(COALESCE(SUM(cycle_used), 0) AS UNSIGNED) as reduced_cycle_count from proof where proof_status = ?")
+                .bind(ProofStatus::Reduced.as_u8());
+
+    info!("{}", query.sql());
+    info!("arguments: {}", ProofStatus::Reduced.as_u8());
+
+    let reduced_cycle_count = match query.fetch_one(pool).await{
+        Ok(t) =>{
+            let count: u64 = t.try_get_unchecked("reduced_cycle_count")?;
+            Ok(count)
+        }
+        Err(e) => Err(anyhow!(CustomError::DB(error_line!(e))))
+    };
+    reduced_cycle_count
+}
+
 pub async fn update_cycle_used_in_proof(pool: &Pool<MySql>, proof_id: u64, cycle_used: u64) -> AnyhowResult<()> {
     let query  = sqlx::query("UPDATE proof set cycle_used = ? where id = ?")
                 .bind(cycle_used).bind(proof_id);
@@ -182,13 +315,20 @@ pub async fn update_cycle_used_in_proof(pool: &Pool<MySql>, proof_id: u64, cycle
     row_affected
 }
 
-pub async fn get_reduced_proofs_r0(pool: &Pool<MySql>) -> AnyhowResult<Vec<Proof>> {
+// Same fair queuing across protocols as `get_unpicked_tasks`, over the reduced proofs
+pub async fn get_reduced_proofs_r0(pool: &Pool<MySql>, limit: u64) -> AnyhowResult<Vec<Proof>> {
     let query  = sqlx::query("
-        SELECT * from proof join user_circuit_data on proof.user_circuit_hash = user_circuit_data.circuit_hash where proof.proof_status = ? and user_circuit_data.proving_scheme != ? order by id;
-    ").bind(ProofStatus::Reduced.as_u8()).bind(ProvingSchemes::Sp1.to_string());
+        SELECT * from (
+            SELECT proof.*, COALESCE(protocol.priority_tier, 0) as priority_tier,
+                ROW_NUMBER() OVER (PARTITION BY user_circuit_data.protocol_name ORDER BY proof.id) / GREATEST(COALESCE(protocol.fair_share_weight, 1), 1) as fair_share_rank
+            from proof join user_circuit_data on proof.user_circuit_hash = user_circuit_data.circuit_hash
+            left join protocol on user_circuit_data.protocol_name = protocol.protocol_name
+            where proof.proof_status = ? and user_circuit_data.proving_scheme != ?
+        ) as reduced_proof order by priority_tier desc, fair_share_rank, id limit ?;
+    ").bind(ProofStatus::Reduced.as_u8()).bind(ProvingSchemes::Sp1.to_string()).bind(limit);
 
     info!("{}", query.sql());
-    info!("arguments: {}", ProofStatus::Reduced.as_u8());
+    info!("arguments: {}, {}, {}", ProofStatus::Reduced.as_u8(), ProvingSchemes::Sp1.to_string(), limit);
 
     let db_rows = match query.fetch_all(pool).await {
         Ok(t) => Ok(t),
@@ -203,9 +343,16 @@ pub async fn get_reduced_proofs_r0(pool: &Pool<MySql>) -> AnyhowResult<Vec<Proof
     Ok(proofs)
 }
 
+// Proofs with fewer failed aggregations go first within a priority tier
 pub async fn get_reduced_proofs_sp1(pool: &Pool<MySql>, limit: u64) -> AnyhowResult<Vec<Proof>> {
     let query  = sqlx::query("
-        SELECT * from proof join user_circuit_data on proof.user_circuit_hash = user_circuit_data.circuit_hash where proof.proof_status = ? and user_circuit_data.proving_scheme = ? order by id limit ?;
+        SELECT * from (
+            SELECT proof.*, COALESCE(protocol.priority_tier, 0) as priority_tier,
+                ROW_NUMBER() OVER (PARTITION BY user_circuit_data.protocol_name ORDER BY proof.id) / GREATEST(COALESCE(protocol.fair_share_weight, 1), 1) as fair_share_rank
+            from proof join user_circuit_data on proof.user_circuit_hash = user_circuit_data.circuit_hash
+            left join protocol on user_circuit_data.protocol_name = protocol.protocol_name
+            where proof.proof_status = ? and user_circuit_data.proving_scheme = ?
+        ) as reduced_proof order by priority_tier desc, sp1_aggregation_attempts, fair_share_rank, id limit ?;
     ").bind(ProofStatus::Reduced.as_u8()).bind(ProvingSchemes::Sp1.to_string()).bind(limit);
 
     info!("{}", query.sql());
@@ -224,6 +371,51 @@ pub async fn get_reduced_proofs_sp1(pool: &Pool<MySql>, limit: u64) -> AnyhowRes
     Ok(proofs)
 }
 
+// Records a failed or timed out sp1 aggregation of the proof, returns the attempts made so far
+pub async fn increment_sp1_aggregation_attempts(pool: &Pool<MySql>, proof_id: u64) -> AnyhowResult<u64> {
+    increment_attempts(pool, "sp1_aggregation_attempts", proof_id).await
+}
+
+// Records a failed superproof the proof was part of, returns the failed superproofs so far
+pub async fn increment_aggregation_attempts(pool: &Pool<MySql>, proof_id: u64) -> AnyhowResult<u64> {
+    increment_attempts(pool, "aggregation_attempts", proof_id).await
+}
+
+// The update holds the row lock until commit, so the count read back is the one this call wrote
+async fn increment_attempts(pool: &Pool<MySql>, column: &str, proof_id: u64) -> AnyhowResult<u64> {
+    let mut tx = match pool.begin().await {
+        Ok(tx) => tx,
+        Err(e) => return Err(anyhow!(CustomError::DB(error_line!(e))))
+    };
+
+    let update_sql = format!("UPDATE proof set {column} = COALESCE({column}, 0) + 1 where id = ?");
+    let query  = sqlx::query(&update_sql)
+                .bind(proof_id);
+
+    info!("{}", query.sql());
+    info!("arguments: {}", proof_id);
+
+    if let Err(e) = query.execute(&mut tx).await {
+        return Err(anyhow!(CustomError::DB(error_line!(e))));
+    }
+
+    let select_sql = format!("SELECT {column} from proof where id = ?");
+    let query  = sqlx::query(&select_sql)
+                .bind(proof_id);
+
+    info!("{}", query.sql());
+    info!("arguments: {}", proof_id);
+
+    let attempts: u64 = match query.fetch_one(&mut tx).await {
+        Ok(t) => t.try_get_unchecked(column)?,
+        Err(e) => return Err(anyhow!(CustomError::DB(error_line!(e))))
+    };
+
+    match tx.commit().await {
+        Ok(_) => Ok(attempts),
+        Err(e) => Err(anyhow!(CustomError::DB(error_line!(e))))
+    }
+}
 
 pub async fn get_reduced_proofs(pool: &Pool<MySql>) -> AnyhowResult<Vec<Proof>> {
     let query  = sqlx::query("SELECT * from proof where proof_status = ? order by id")
@@ -260,6 +452,8 @@ fn get_proof_from_mysql_row(row: &MySqlRow) -> AnyhowResult<Proof>{
         input_id: row.try_get_unchecked("input_id")?,
         session_id: row.try_get_unchecked("session_id")?,
         cycle_used: row.try_get_unchecked("cycle_used")?,
+        sp1_aggregation_attempts: row.try_get_unchecked("sp1_aggregation_attempts")?,
+        aggregation_attempts: row.try_get_unchecked("aggregation_attempts")?,
     };
     Ok(proof)
 }
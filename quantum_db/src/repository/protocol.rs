@@ -28,8 +28,9 @@ pub async fn get_protocol_by_protocol_name(pool: &Pool<MySql>, protocol_name: &s
     info!("{}", query.sql());
     info!("arguments: {}", protocol_name);
 
-    let protocol = match query.fetch_one(pool).await.map_err(|err| anyhow!(error_line!(err))) {
+    let protocol = match query.fetch_one(pool).await {
         Ok(p) => get_protocol_from_row(p),
+        Err(sqlx::Error::RowNotFound) => Err(anyhow!(CustomError::NotFound(error_line!(format!("protocol {}", protocol_name))))),
         Err(e) => Err(anyhow!(CustomError::DB(error_line!(e))))
     };
     protocol
@@ -42,6 +43,10 @@ pub fn get_protocol_from_row(row: MySqlRow) -> AnyhowResult<Protocol>{
             protocol_name: row.try_get_unchecked("protocol_name").map_err(|err| anyhow!(error_line!(err)))?,
             auth_token: row.try_get_unchecked("auth_token").map_err(|err| anyhow!(error_line!(err)))?,
             is_proof_repeat_allowed: row.try_get_unchecked("is_proof_repeat_allowed").map_err(|err| anyhow!(error_line!(err)))?,
+            callback_url: row.try_get_unchecked("callback_url").map_err(|err| anyhow!(error_line!(err)))?,
+            callback_secret: row.try_get_unchecked("callback_secret").map_err(|err| anyhow!(error_line!(err)))?,
+            priority_tier: row.try_get_unchecked("priority_tier").map_err(|err| anyhow!(error_line!(err)))?,
+            fair_share_weight: row.try_get_unchecked("fair_share_weight").map_err(|err| anyhow!(error_line!(err)))?,
         }
     )
 }
@@ -76,4 +81,18 @@ pub async fn insert_protocol_auth_token(pool: &Pool<MySql>, protocol_name: &str,
         Err(e) => Err(anyhow!(error_line!(e)))
     };
     row_affected
-}
\ No newline at end of file
+}
+
+pub async fn update_protocol_callback(pool: &Pool<MySql>, protocol_name: &str, callback_url: &str, callback_secret: &str) -> AnyhowResult<()> {
+    let query  = sqlx::query("UPDATE protocol set callback_url = ?, callback_secret = ? where protocol_name = ?")
+        .bind(callback_url).bind(callback_secret).bind(protocol_name);
+
+    info!("{}", query.sql());
+    info!("arguments: {}, {}", callback_url, protocol_name);
+
+    let row_affected = match query.execute(pool).await {
+        Ok(_) => Ok(()),
+        Err(e) => Err(anyhow!(CustomError::DB(error_line!(e))))
+    };
+    row_affected
+}
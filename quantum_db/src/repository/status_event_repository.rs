@@ -0,0 +1,105 @@
+use quantum_types::{enums::{proof_status::ProofStatus, superproof_status::SuperproofStatus}, types::db::status_event::StatusEvent};
+use quantum_utils::error_line;
+use sqlx::{mysql::MySqlRow, Execute, MySql, Pool, Row, Transaction};
+use anyhow::{anyhow, Result as AnyhowResult};
+use tracing::info;
+
+use crate::{error::error::CustomError, repository::webhook_outbox_repository::{PROOF_STATUS_EVENT, SUPERPROOF_STATUS_EVENT}};
+
+// Appends a proof status transition to the event log, payload matches the proof status webhook
+pub async fn insert_proof_status_event(tx: &mut Transaction<'_, MySql>, proof_id: u64, proof_status: &ProofStatus) -> AnyhowResult<()> {
+    let query  = sqlx::query("INSERT into status_event(protocol_name, event_type, proof_hash, superproof_id, payload)
+        SELECT user_circuit_data.protocol_name, ?, proof.proof_hash, proof.superproof_id, JSON_OBJECT('event', ?, 'proof_id', proof.id, 'proof_hash', proof.proof_hash, 'circuit_hash', proof.user_circuit_hash, 'status', ?, 'superproof_id', proof.superproof_id)
+        from proof join user_circuit_data on proof.user_circuit_hash = user_circuit_data.circuit_hash
+        where proof.id = ?")
+                .bind(PROOF_STATUS_EVENT).bind(PROOF_STATUS_EVENT).bind(proof_status.to_string()).bind(proof_id);
+
+    info!("{}", query.sql());
+    info!("arguments: {}, {}, {}, {}", PROOF_STATUS_EVENT, PROOF_STATUS_EVENT, proof_status.to_string(), proof_id);
+
+    let row_affected = match query.execute(tx).await {
+        Ok(_) => Ok(()),
+        Err(e) => Err(anyhow!(CustomError::DB(error_line!(e))))
+    };
+    row_affected
+}
+
+// Appends one superproof status transition per protocol having a proof in the superproof
+pub async fn insert_superproof_status_event(tx: &mut Transaction<'_, MySql>, superproof_id: u64, status: &SuperproofStatus, transaction_hash: Option<&str>) -> AnyhowResult<()> {
+    let query  = sqlx::query("INSERT into status_event(protocol_name, event_type, proof_hash, superproof_id, payload)
+        SELECT DISTINCT user_circuit_data.protocol_name, ?, NULL, ?, JSON_OBJECT('event', ?, 'superproof_id', ?, 'status', ?, 'transaction_hash', ?)
+        from proof join user_circuit_data on proof.user_circuit_hash = user_circuit_data.circuit_hash
+        where proof.superproof_id = ?")
+                .bind(SUPERPROOF_STATUS_EVENT).bind(superproof_id).bind(SUPERPROOF_STATUS_EVENT).bind(superproof_id).bind(status.to_string()).bind(transaction_hash).bind(superproof_id);
+
+    info!("{}", query.sql());
+    info!("arguments: {}, {}, {}, {}, {}, {:?}, {}", SUPERPROOF_STATUS_EVENT, superproof_id, SUPERPROOF_STATUS_EVENT, superproof_id, status.to_string(), transaction_hash, superproof_id);
+
+    let row_affected = match query.execute(tx).await {
+        Ok(_) => Ok(()),
+        Err(e) => Err(anyhow!(CustomError::DB(error_line!(e))))
+    };
+    row_affected
+}
+
+// Events of the proof itself and of the superproof it ended up in, with an id in (after_event_id, up_to_event_id]
+pub async fn get_proof_status_events(pool: &Pool<MySql>, proof_hash: &str, after_event_id: u64, up_to_event_id: u64, limit: u64) -> AnyhowResult<Vec<StatusEvent>> {
+    let query  = sqlx::query("SELECT * from status_event where id > ? and id <= ? and (proof_hash = ?
+            or (event_type = ? and superproof_id in (SELECT superproof_id from proof where proof_hash = ? and superproof_id is not NULL)
+                and protocol_name in (SELECT user_circuit_data.protocol_name from proof join user_circuit_data on proof.user_circuit_hash = user_circuit_data.circuit_hash where proof.proof_hash = ?)))
+        order by id LIMIT ?")
+                .bind(after_event_id).bind(up_to_event_id).bind(proof_hash).bind(SUPERPROOF_STATUS_EVENT).bind(proof_hash).bind(proof_hash).bind(limit);
+
+    info!("{}", query.sql());
+    info!("arguments: {}, {}, {}, {}, {}, {}, {}", after_event_id, up_to_event_id, proof_hash, SUPERPROOF_STATUS_EVENT, proof_hash, proof_hash, limit);
+
+    let rows = match query.fetch_all(pool).await {
+        Ok(t) => Ok(t),
+        Err(e) => Err(anyhow!(CustomError::DB(error_line!(e))))
+    }?;
+    get_status_events_from_mysql_rows(rows)
+}
+
+pub async fn get_protocol_status_events(pool: &Pool<MySql>, protocol_name: &str, after_event_id: u64, up_to_event_id: u64, limit: u64) -> AnyhowResult<Vec<StatusEvent>> {
+    let query  = sqlx::query("SELECT * from status_event where id > ? and id <= ? and protocol_name = ? order by id LIMIT ?")
+                .bind(after_event_id).bind(up_to_event_id).bind(protocol_name).bind(limit);
+
+    info!("{}", query.sql());
+    info!("arguments: {}, {}, {}, {}", after_event_id, up_to_event_id, protocol_name, limit);
+
+    let rows = match query.fetch_all(pool).await {
+        Ok(t) => Ok(t),
+        Err(e) => Err(anyhow!(CustomError::DB(error_line!(e))))
+    }?;
+    get_status_events_from_mysql_rows(rows)
+}
+
+pub async fn get_latest_status_event_id(pool: &Pool<MySql>) -> AnyhowResult<u64> {
+    let query  = sqlx::query("SELECT COALESCE(MAX(id), 0) as latest_id from status_event");
+
+    info!("{}", query.sql());
+
+    let latest_id = match query.fetch_one(pool).await {
+        Ok(t) => {
+            let id: u64 = t.try_get_unchecked("latest_id")?;
+            Ok(id)
+        }
+        Err(e) => Err(anyhow!(CustomError::DB(error_line!(e))))
+    };
+    latest_id
+}
+
+fn get_status_events_from_mysql_rows(rows: Vec<MySqlRow>) -> AnyhowResult<Vec<StatusEvent>> {
+    let mut events = vec![];
+    for row in rows {
+        events.push(StatusEvent {
+            id: row.try_get_unchecked("id")?,
+            protocol_name: row.try_get_unchecked("protocol_name")?,
+            event_type: row.try_get_unchecked("event_type")?,
+            proof_hash: row.try_get_unchecked("proof_hash")?,
+            superproof_id: row.try_get_unchecked("superproof_id")?,
+            payload: row.try_get_unchecked("payload")?,
+        });
+    }
+    Ok(events)
+}
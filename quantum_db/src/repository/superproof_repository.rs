@@ -1,11 +1,11 @@
 use chrono::NaiveDateTime;
-use quantum_types::{enums::superproof_status::SuperproofStatus, types::db::superproof::Superproof};
+use quantum_types::{enums::{batch_close_reason::BatchCloseReason, superproof_status::SuperproofStatus}, types::db::superproof::Superproof};
 use quantum_utils::error_line;
 use sqlx::{mysql::MySqlRow , Execute, MySql, Pool, Row};
 use anyhow::{anyhow, Error as AnyhowError, Result as AnyhowResult};
 use tracing::info;
 
-use crate::error::error::CustomError;
+use crate::{error::error::CustomError, repository::{status_event_repository::insert_superproof_status_event, webhook_outbox_repository::enqueue_superproof_status_event}};
 
 pub async fn get_superproof_by_id(pool: &Pool<MySql>, id: u64) -> AnyhowResult<Superproof> {
     let query  = sqlx::query("SELECT * from superproof where id = ?")
@@ -53,13 +53,23 @@ pub async fn update_proof_ids_in_superproof(pool: &Pool<MySql>, proof_ids_string
 }
 
 pub async fn update_superproof_status(pool: &Pool<MySql>, superproof_status: SuperproofStatus, superproof_id: u64) -> AnyhowResult<()>{
+    let mut tx = match pool.begin().await {
+        Ok(tx) => tx,
+        Err(e) => return Err(anyhow!(CustomError::DB(error_line!(e))))
+    };
+
     let query  = sqlx::query("UPDATE superproof set status = ? where id = ?")
                 .bind(superproof_status.as_u8()).bind(superproof_id);
 
     info!("{}", query.sql());
     info!("arguments: {}, {}", superproof_status.as_u8(), superproof_id);
 
-    let row_affected = match query.execute(pool).await {
+    if let Err(e) = query.execute(&mut tx).await {
+        return Err(anyhow!(CustomError::DB(error_line!(e))));
+    }
+    insert_superproof_status_event(&mut tx, superproof_id, &superproof_status, None).await?;
+
+    let row_affected = match tx.commit().await {
         Ok(_) => Ok(()),
         Err(e) => Err(anyhow!(CustomError::DB(error_line!(e))))
     };
@@ -321,8 +331,9 @@ pub async fn update_superproof_total_proving_time(pool: &Pool<MySql>, total_prov
     row_affected
 }
 
+// A new aggregation session invalidates the snark session of the previous one
 pub async fn update_session_id_superproof(pool: &Pool<MySql>, session_id: &str, superproof_id: u64) -> AnyhowResult<()> {
-    let query  = sqlx::query("UPDATE superproof set session_id = ? where id = ?")
+    let query  = sqlx::query("UPDATE superproof set session_id = ?, snark_session_id = NULL where id = ?")
                 .bind(session_id).bind(superproof_id);
 
     info!("{}", query.sql());
@@ -349,11 +360,26 @@ pub async fn update_snark_session_id_superproof(pool: &Pool<MySql>, snark_sessio
     row_affected
 }
 
+pub async fn update_batch_close_reason_in_superproof(pool: &Pool<MySql>, batch_close_reason: BatchCloseReason, superproof_id: u64) -> AnyhowResult<()> {
+    let query  = sqlx::query("UPDATE superproof set batch_close_reason = ? where id = ?")
+                .bind(batch_close_reason.as_u8()).bind(superproof_id);
 
+    info!("{}", query.sql());
+    info!("arguments: {}, {}", batch_close_reason.as_u8(), superproof_id);
+
+    let row_affected = match query.execute(pool).await {
+        Ok(_) => Ok(()),
+        Err(e) => Err(anyhow!(CustomError::DB(error_line!(e))))
+    };
+    row_affected
+}
 
 fn get_superproof_from_row(row: MySqlRow) -> AnyhowResult<Superproof> {
     let superproof_status_as_u8: u8 = row.try_get_unchecked("status")?;
     let superproof_status =  SuperproofStatus::from(superproof_status_as_u8);
+    let batch_close_reason_as_u8: Option<u8> = row.try_get_unchecked("batch_close_reason")?;
+    let batch_close_reason = batch_close_reason_as_u8.map(BatchCloseReason::try_from).transpose()
+        .map_err(|e| anyhow!(CustomError::DB(error_line!(e))))?;
     let superproof = Superproof {
         id: row.try_get_unchecked("id")?,
         proof_ids: row.try_get_unchecked("proof_ids")?,
@@ -373,6 +399,9 @@ fn get_superproof_from_row(row: MySqlRow) -> AnyhowResult<Superproof> {
         imt_pis_path: row.try_get_unchecked("imt_pis_path")?,
         r0_root: row.try_get_unchecked("r0_root")?,
         sp1_root: row.try_get_unchecked("sp1_root")?,
+        session_id: row.try_get_unchecked("session_id")?,
+        snark_session_id: row.try_get_unchecked("snark_session_id")?,
+        batch_close_reason,
     };
 
     Ok(superproof)
@@ -422,6 +451,7 @@ pub async fn get_last_verified_superproof(pool: &Pool<MySql>) -> AnyhowResult<Op
     Ok(superproof)
 }
 
+// Oldest superproof waiting for onchain submission, superproofs still aggregating are skipped
 pub async fn get_first_non_submitted_superproof(pool: &Pool<MySql>) -> AnyhowResult<Option<Superproof>> {
     let query  = sqlx::query("SELECT * from superproof where status = ? order by id LIMIT 1")
                                                     .bind(SuperproofStatus::ProvingDone.as_u8());
@@ -445,14 +475,82 @@ pub async fn get_first_non_submitted_superproof(pool: &Pool<MySql>) -> AnyhowRes
     Ok(superproof)
 }
 
+// Superproofs aggregating or waiting for onchain submission
+pub async fn get_non_submitted_superproof_count(pool: &Pool<MySql>) -> AnyhowResult<u64> {
+    let query  = sqlx::query("SELECT Count(*) as superproof_count from superproof where status in (?, ?)")
+                                                    .bind(SuperproofStatus::InProgress.as_u8()).bind(SuperproofStatus::ProvingDone.as_u8());
+
+    info!("{}", query.sql());
+    info!("arguments: {}, {}", SuperproofStatus::InProgress.as_u8(), SuperproofStatus::ProvingDone.as_u8());
+
+    let count = match query.fetch_one(pool).await{
+        Ok(t) => {
+            let count: u64 = t.try_get_unchecked("superproof_count")?;
+            Ok(count)
+        }
+        Err(e) => Err(anyhow!(CustomError::DB(error_line!(e))))
+    };
+    count
+}
+
+// InProgress superproofs which already started an aggregation session
+pub async fn get_in_flight_superproofs(pool: &Pool<MySql>) -> AnyhowResult<Vec<Superproof>> {
+    let query  = sqlx::query("SELECT * from superproof where status = ? and session_id is not NULL order by id")
+                                                    .bind(SuperproofStatus::InProgress.as_u8());
+
+    info!("{}", query.sql());
+    info!("arguments: {}", SuperproofStatus::InProgress.as_u8());
+
+    let rows = match query.fetch_all(pool).await{
+        Ok(t) => Ok(t),
+        Err(e) => Err(anyhow!(CustomError::DB(error_line!(e))))
+    }?;
+    let mut superproofs = vec![];
+    for row in rows {
+        superproofs.push(get_superproof_from_row(row)?);
+    }
+    Ok(superproofs)
+}
+
+// InProgress superproofs left behind before their aggregation session was created
+pub async fn get_orphaned_superproofs(pool: &Pool<MySql>) -> AnyhowResult<Vec<Superproof>> {
+    let query  = sqlx::query("SELECT * from superproof where status = ? and session_id is NULL order by id")
+                                                    .bind(SuperproofStatus::InProgress.as_u8());
+
+    info!("{}", query.sql());
+    info!("arguments: {}", SuperproofStatus::InProgress.as_u8());
+
+    let rows = match query.fetch_all(pool).await{
+        Ok(t) => Ok(t),
+        Err(e) => Err(anyhow!(CustomError::DB(error_line!(e))))
+    }?;
+    let mut superproofs = vec![];
+    for row in rows {
+        superproofs.push(get_superproof_from_row(row)?);
+    }
+    Ok(superproofs)
+}
+
+// Also records the status event and queues a webhook for every protocol in the superproof, in the same transaction
 pub async fn update_superproof_fields_after_onchain_submission(pool: &Pool<MySql>, transaction_hash: &str, status: SuperproofStatus, gas_used: u64, superproof_id: u64) -> AnyhowResult<()> {
+    let mut tx = match pool.begin().await {
+        Ok(tx) => tx,
+        Err(e) => return Err(anyhow!(CustomError::DB(error_line!(e))))
+    };
+
     let query = sqlx::query("UPDATE superproof SET transaction_hash = ?, status = ?, total_proof_ver_cost = ? WHERE id = ?")
             .bind(transaction_hash).bind(status.as_u8()).bind(gas_used).bind(superproof_id);
 
     info!("{}", query.sql());
     info!("arguments: {}, {}, {}, {}", transaction_hash, status.as_u8(), gas_used, superproof_id);
 
-    let row_affected = match query.execute(pool).await {
+    if let Err(e) = query.execute(&mut tx).await {
+        return Err(anyhow!(CustomError::DB(error_line!(e))));
+    }
+    enqueue_superproof_status_event(&mut tx, superproof_id, &status, transaction_hash).await?;
+    insert_superproof_status_event(&mut tx, superproof_id, &status, Some(transaction_hash)).await?;
+
+    let row_affected = match tx.commit().await {
         Ok(_) => Ok(()),
         Err(e) => Err(anyhow!(CustomError::DB(error_line!(e))))
     };
@@ -1,5 +1,6 @@
-use quantum_types::{enums::{task_status::TaskStatus, task_type::TaskType}, types::db::task::Task};
+use quantum_types::{enums::{proof_status::ProofStatus, task_status::TaskStatus, task_type::TaskType}, types::db::task::Task};
 use quantum_utils::error_line;
+use chrono::NaiveDateTime;
 use sqlx::{mysql::MySqlRow, MySql, Pool, Execute};
 use sqlx::Row;
 use anyhow::{anyhow, Error, Result as AnyhowResult};
@@ -22,13 +23,22 @@ pub async fn create_circuit_reduction_task(pool: &Pool<MySql>,user_circuit_hash:
     row_affected
 }
 
-pub async fn get_unpicked_tasks(pool: &Pool<MySql>, limit: u64) -> Result<Vec<Task>, Error> {
-    // oldest_entry(task_status: TaskStatus::NotPicked)
-    let query  = sqlx::query("SELECT * from task where task_status = ? order by id limit ?")
-                .bind(TaskStatus::NotPicked.as_u8()).bind(limit);
+// Weighted fair queuing across protocols: by priority tier, then by the position of the task in its protocol's queue
+// scaled down by the protocol's fair share weight, then by id
+pub async fn get_unpicked_tasks(pool: &Pool<MySql>, task_type: TaskType, limit: u64) -> Result<Vec<Task>, Error> {
+    let query  = sqlx::query("
+        SELECT * from (
+            SELECT task.*, COALESCE(protocol.priority_tier, 0) as priority_tier,
+                ROW_NUMBER() OVER (PARTITION BY user_circuit_data.protocol_name ORDER BY task.id) / GREATEST(COALESCE(protocol.fair_share_weight, 1), 1) as fair_share_rank
+            from task
+            left join user_circuit_data on task.user_circuit_hash = user_circuit_data.circuit_hash
+            left join protocol on user_circuit_data.protocol_name = protocol.protocol_name
+            where task.task_status = ? and task.task_type = ?
+        ) as unpicked_task order by priority_tier desc, fair_share_rank, id limit ?;
+    ").bind(TaskStatus::NotPicked.as_u8()).bind(task_type.as_u8()).bind(limit);
 
     info!("{}", query.sql());
-    info!("arguments: {}, {}", TaskStatus::NotPicked.as_u8(), limit);
+    info!("arguments: {}, {}, {}", TaskStatus::NotPicked.as_u8(), task_type.as_u8(), limit);
 
     let reduction_circuit = match query.fetch_all(pool).await{
         Ok(t) => {
@@ -43,6 +53,91 @@ pub async fn get_unpicked_tasks(pool: &Pool<MySql>, limit: u64) -> Result<Vec<Ta
     reduction_circuit
 }
 
+/*
+    Claims up to `limit` NotPicked tasks for the worker in `get_unpicked_tasks` order, leasing them as InProgress.
+    One locking read picks the tasks, skipping the ones row-locked by a concurrent claim of another worker instead of
+    waiting on them, then one UPDATE leases them all. The ranking reads every NotPicked task of the type, so they
+    stay locked until this short transaction commits.
+ */
+pub async fn claim_unpicked_tasks(pool: &Pool<MySql>, task_type: TaskType, worker_id: &str, limit: u64, lease_secs: u64) -> AnyhowResult<Vec<Task>> {
+    let mut tx = match pool.begin().await {
+        Ok(tx) => tx,
+        Err(e) => return Err(anyhow!(CustomError::DB(error_line!(e))))
+    };
+
+    let query  = sqlx::query("
+        SELECT task.*, DATE_ADD(NOW(), INTERVAL ? SECOND) as claimed_lease_expires_at, COALESCE(protocol.priority_tier, 0) as priority_tier,
+            ROW_NUMBER() OVER (PARTITION BY user_circuit_data.protocol_name ORDER BY task.id) / GREATEST(COALESCE(protocol.fair_share_weight, 1), 1) as fair_share_rank
+        from task
+        left join user_circuit_data on task.user_circuit_hash = user_circuit_data.circuit_hash
+        left join protocol on user_circuit_data.protocol_name = protocol.protocol_name
+        where task.task_status = ? and task.task_type = ?
+        order by priority_tier desc, fair_share_rank, task.id limit ?
+        FOR UPDATE OF task SKIP LOCKED;
+    ").bind(lease_secs).bind(TaskStatus::NotPicked.as_u8()).bind(task_type.as_u8()).bind(limit);
+
+    info!("{}", query.sql());
+    info!("arguments: {}, {}, {}, {}", lease_secs, TaskStatus::NotPicked.as_u8(), task_type.as_u8(), limit);
+
+    let rows = match query.fetch_all(&mut tx).await {
+        Ok(t) => t,
+        Err(e) => return Err(anyhow!(CustomError::DB(error_line!(e))))
+    };
+    if rows.is_empty() {
+        return Ok(vec![]);
+    }
+    let lease_expires_at: NaiveDateTime = rows[0].try_get_unchecked("claimed_lease_expires_at")?;
+    let mut claimed_tasks = vec![];
+    for row in rows {
+        let mut task = get_task_from_mysql_row(row)?;
+        task.task_status = TaskStatus::InProgress;
+        task.leased_by = Some(worker_id.to_string());
+        task.lease_expires_at = Some(lease_expires_at);
+        claimed_tasks.push(task);
+    }
+
+    let task_ids: Vec<u64> = claimed_tasks.iter().filter_map(|task| task.id).collect();
+    let update_sql = format!(
+        "UPDATE task set task_status = ?, leased_by = ?, lease_expires_at = ? where id in ({})",
+        vec!["?"; task_ids.len()].join(", ")
+    );
+    let mut query  = sqlx::query(&update_sql)
+                .bind(TaskStatus::InProgress.as_u8()).bind(worker_id).bind(lease_expires_at);
+    for task_id in &task_ids {
+        query = query.bind(*task_id);
+    }
+
+    info!("{}", query.sql());
+    info!("arguments: {}, {}, {}, {:?}", TaskStatus::InProgress.as_u8(), worker_id, lease_expires_at, task_ids);
+
+    if let Err(e) = query.execute(&mut tx).await {
+        return Err(anyhow!(CustomError::DB(error_line!(e))));
+    }
+
+    match tx.commit().await {
+        Ok(_) => Ok(claimed_tasks),
+        Err(e) => Err(anyhow!(CustomError::DB(error_line!(e))))
+    }
+}
+
+// InProgress tasks of all workers
+pub async fn get_in_progress_task_count(pool: &Pool<MySql>, task_type: TaskType) -> AnyhowResult<u64> {
+    let query  = sqlx::query("SELECT Count(*) as in_progress_count from task where task_status = ? and task_type = ?")
+                .bind(TaskStatus::InProgress.as_u8()).bind(task_type.as_u8());
+
+    info!("{}", query.sql());
+    info!("arguments: {}, {}", TaskStatus::InProgress.as_u8(), task_type.as_u8());
+
+    let in_progress_count = match query.fetch_one(pool).await{
+        Ok(t) =>{
+            let count: u64 = t.try_get_unchecked("in_progress_count")?;
+            Ok(count)
+        }
+        Err(e) => Err(anyhow!(CustomError::DB(error_line!(e))))
+    };
+    in_progress_count
+}
+
 fn get_task_from_mysql_row(r: MySqlRow) -> AnyhowResult<Task> {
     let task_type: u8 = r.try_get_unchecked("task_type")?;
     let task_status: u8 = r.try_get_unchecked("task_status")?;
@@ -52,7 +147,9 @@ fn get_task_from_mysql_row(r: MySqlRow) -> AnyhowResult<Task> {
         task_type: TaskType::from(task_type),
         proof_hash: r.try_get_unchecked("proof_hash")?,
         proof_id: r.try_get_unchecked("proof_id")?,
-        task_status: TaskStatus::from(task_status)
+        task_status: TaskStatus::from(task_status),
+        leased_by: r.try_get_unchecked("leased_by")?,
+        lease_expires_at: r.try_get_unchecked("lease_expires_at")?,
     };
     Ok(task)
 }
@@ -103,4 +200,104 @@ pub async fn create_proof_task(pool: &Pool<MySql>, user_circuit_hash: &str, task
         }
     };
     row_affected
-}
\ No newline at end of file
+}
+
+pub async fn renew_task_lease(pool: &Pool<MySql>, task_id: u64, worker_id: &str, lease_secs: u64) -> AnyhowResult<bool> {
+    let query  = sqlx::query("UPDATE task set lease_expires_at = DATE_ADD(NOW(), INTERVAL ? SECOND) where id = ? and leased_by = ? and task_status = ?")
+                .bind(lease_secs).bind(task_id).bind(worker_id).bind(TaskStatus::InProgress.as_u8());
+
+    info!("{}", query.sql());
+    info!("arguments: {}, {}, {}, {}", lease_secs, task_id, worker_id, TaskStatus::InProgress.as_u8());
+
+    let is_renewed = match query.execute(pool).await {
+        Ok(t) => Ok(t.rows_affected() == 1),
+        Err(e) => Err(anyhow!(CustomError::DB(error_line!(e))))
+    };
+    is_renewed
+}
+
+pub async fn release_task_lease(pool: &Pool<MySql>, task_id: u64) -> AnyhowResult<()> {
+    let query  = sqlx::query("UPDATE task set leased_by = NULL, lease_expires_at = NULL where id = ?")
+                .bind(task_id);
+
+    info!("{}", query.sql());
+    info!("arguments: {}", task_id);
+
+    let row_affected = match query.execute(pool).await {
+        Ok(_) => Ok(()),
+        Err(e) => Err(anyhow!(CustomError::DB(error_line!(e))))
+    };
+    row_affected
+}
+
+// InProgress tasks whose lease ran out, or which were picked before leasing existed
+pub async fn get_expired_leased_tasks(pool: &Pool<MySql>) -> AnyhowResult<Vec<Task>> {
+    let query  = sqlx::query("SELECT * from task where task_status = ? and (lease_expires_at is NULL or lease_expires_at < NOW()) order by id")
+                .bind(TaskStatus::InProgress.as_u8());
+
+    info!("{}", query.sql());
+    info!("arguments: {}", TaskStatus::InProgress.as_u8());
+
+    let tasks = match query.fetch_all(pool).await{
+        Ok(t) => {
+            let mut rows = vec![];
+            for row in t {
+                rows.push(get_task_from_mysql_row(row)?);
+            }
+            Ok(rows)
+        },
+        Err(e) => Err(anyhow!(CustomError::DB(error_line!(e))))
+    };
+    tasks
+}
+
+// Returns an expired task to NotPicked, unless its lease got renewed in the meantime
+pub async fn reset_expired_task(pool: &Pool<MySql>, task_id: u64) -> AnyhowResult<bool> {
+    let query  = sqlx::query("UPDATE task set task_status = ?, leased_by = NULL, lease_expires_at = NULL where id = ? and task_status = ? and (lease_expires_at is NULL or lease_expires_at < NOW())")
+                .bind(TaskStatus::NotPicked.as_u8()).bind(task_id).bind(TaskStatus::InProgress.as_u8());
+
+    info!("{}", query.sql());
+    info!("arguments: {}, {}, {}", TaskStatus::NotPicked.as_u8(), task_id, TaskStatus::InProgress.as_u8());
+
+    let is_reset = match query.execute(pool).await {
+        Ok(t) => Ok(t.rows_affected() == 1),
+        Err(e) => Err(anyhow!(CustomError::DB(error_line!(e))))
+    };
+    is_reset
+}
+
+// Reduction tasks whose proof already has a bonsai session and whose worker is gone:
+// lease expired, picked before leasing existed, or leased by this worker id in a previous run
+pub async fn get_orphaned_reduction_tasks(pool: &Pool<MySql>, worker_id: &str) -> AnyhowResult<Vec<Task>> {
+    let query  = sqlx::query("SELECT task.* from task join proof on task.proof_id = proof.id where task.task_status = ? and proof.proof_status = ? and proof.session_id is not NULL and (task.leased_by is NULL or task.leased_by = ? or task.lease_expires_at < NOW()) order by task.id")
+                .bind(TaskStatus::InProgress.as_u8()).bind(ProofStatus::Reducing.as_u8()).bind(worker_id);
+
+    info!("{}", query.sql());
+    info!("arguments: {}, {}, {}", TaskStatus::InProgress.as_u8(), ProofStatus::Reducing.as_u8(), worker_id);
+
+    let tasks = match query.fetch_all(pool).await{
+        Ok(t) => {
+            let mut rows = vec![];
+            for row in t {
+                rows.push(get_task_from_mysql_row(row)?);
+            }
+            Ok(rows)
+        },
+        Err(e) => Err(anyhow!(CustomError::DB(error_line!(e))))
+    };
+    tasks
+}
+
+pub async fn take_over_task_lease(pool: &Pool<MySql>, task_id: u64, worker_id: &str, lease_secs: u64) -> AnyhowResult<bool> {
+    let query  = sqlx::query("UPDATE task set leased_by = ?, lease_expires_at = DATE_ADD(NOW(), INTERVAL ? SECOND) where id = ? and task_status = ? and (leased_by is NULL or leased_by = ? or lease_expires_at < NOW())")
+                .bind(worker_id).bind(lease_secs).bind(task_id).bind(TaskStatus::InProgress.as_u8()).bind(worker_id);
+
+    info!("{}", query.sql());
+    info!("arguments: {}, {}, {}, {}, {}", worker_id, lease_secs, task_id, TaskStatus::InProgress.as_u8(), worker_id);
+
+    let is_leased = match query.execute(pool).await {
+        Ok(t) => Ok(t.rows_affected() == 1),
+        Err(e) => Err(anyhow!(CustomError::DB(error_line!(e))))
+    };
+    is_leased
+}
@@ -20,6 +20,7 @@ pub async fn get_user_circuit_data_by_circuit_hash(pool: &Pool<MySql>, circuit_h
 
     let user_circuit_data = match query.fetch_one(pool).await{
         Ok(t) => get_user_circuit_data_from_mysql_row(&t),
+        Err(sqlx::Error::RowNotFound) => Err(anyhow!(CustomError::NotFound(error_line!(format!("circuit {}", circuit_hash))))),
         Err(e) => Err(anyhow!(CustomError::DB(error_line!(e))))
     };
     user_circuit_data
@@ -0,0 +1,128 @@
+use quantum_types::{enums::{proof_status::ProofStatus, superproof_status::SuperproofStatus, webhook_delivery_status::WebhookDeliveryStatus}, types::db::webhook_event::WebhookEvent};
+use quantum_utils::error_line;
+use sqlx::{mysql::MySqlRow, Execute, MySql, Pool, Row, Transaction};
+use anyhow::{anyhow, Result as AnyhowResult};
+use tracing::info;
+
+use crate::error::error::CustomError;
+
+pub const PROOF_STATUS_EVENT: &str = "proof_status";
+pub const SUPERPROOF_STATUS_EVENT: &str = "superproof_status";
+
+// Queues a proof status event for the protocol owning the proof, if it has registered a callback url
+pub async fn enqueue_proof_status_event(tx: &mut Transaction<'_, MySql>, proof_id: u64, proof_status: &ProofStatus) -> AnyhowResult<()> {
+    let query  = sqlx::query("INSERT into webhook_outbox(protocol_name, event_type, payload, status)
+        SELECT protocol.protocol_name, ?, JSON_OBJECT('event', ?, 'proof_id', proof.id, 'proof_hash', proof.proof_hash, 'circuit_hash', proof.user_circuit_hash, 'status', ?, 'superproof_id', proof.superproof_id), ?
+        from proof join user_circuit_data on proof.user_circuit_hash = user_circuit_data.circuit_hash join protocol on user_circuit_data.protocol_name = protocol.protocol_name
+        where proof.id = ? and protocol.callback_url is not NULL")
+                .bind(PROOF_STATUS_EVENT).bind(PROOF_STATUS_EVENT).bind(proof_status.to_string()).bind(WebhookDeliveryStatus::Pending.as_u8()).bind(proof_id);
+
+    info!("{}", query.sql());
+    info!("arguments: {}, {}, {}, {}, {}", PROOF_STATUS_EVENT, PROOF_STATUS_EVENT, proof_status.to_string(), WebhookDeliveryStatus::Pending.as_u8(), proof_id);
+
+    let row_affected = match query.execute(tx).await {
+        Ok(_) => Ok(()),
+        Err(e) => Err(anyhow!(CustomError::DB(error_line!(e))))
+    };
+    row_affected
+}
+
+// Queues one superproof status event per protocol having a proof in the superproof
+pub async fn enqueue_superproof_status_event(tx: &mut Transaction<'_, MySql>, superproof_id: u64, status: &SuperproofStatus, transaction_hash: &str) -> AnyhowResult<()> {
+    let query  = sqlx::query("INSERT into webhook_outbox(protocol_name, event_type, payload, status)
+        SELECT DISTINCT protocol.protocol_name, ?, JSON_OBJECT('event', ?, 'superproof_id', ?, 'status', ?, 'transaction_hash', ?), ?
+        from proof join user_circuit_data on proof.user_circuit_hash = user_circuit_data.circuit_hash join protocol on user_circuit_data.protocol_name = protocol.protocol_name
+        where proof.superproof_id = ? and protocol.callback_url is not NULL")
+                .bind(SUPERPROOF_STATUS_EVENT).bind(SUPERPROOF_STATUS_EVENT).bind(superproof_id).bind(status.to_string()).bind(transaction_hash).bind(WebhookDeliveryStatus::Pending.as_u8()).bind(superproof_id);
+
+    info!("{}", query.sql());
+    info!("arguments: {}, {}, {}, {}, {}, {}, {}", SUPERPROOF_STATUS_EVENT, SUPERPROOF_STATUS_EVENT, superproof_id, status.to_string(), transaction_hash, WebhookDeliveryStatus::Pending.as_u8(), superproof_id);
+
+    let row_affected = match query.execute(tx).await {
+        Ok(_) => Ok(()),
+        Err(e) => Err(anyhow!(CustomError::DB(error_line!(e))))
+    };
+    row_affected
+}
+
+/*
+    Claims up to `limit` due events for `claimed_by` for `claim_secs` and returns them.
+    A claim that is neither delivered nor failed in time (e.g. the claimer died) makes the event due again.
+ */
+pub async fn claim_due_webhook_events(pool: &Pool<MySql>, claimed_by: &str, claim_secs: u64, limit: u64) -> AnyhowResult<Vec<WebhookEvent>> {
+    let query  = sqlx::query("UPDATE webhook_outbox set claimed_by = ?, claimed_until = DATE_ADD(NOW(), INTERVAL ? SECOND) where status = ? and next_attempt_at <= NOW() and (claimed_until is NULL or claimed_until < NOW()) order by id LIMIT ?")
+                .bind(claimed_by).bind(claim_secs).bind(WebhookDeliveryStatus::Pending.as_u8()).bind(limit);
+
+    info!("{}", query.sql());
+    info!("arguments: {}, {}, {}, {}", claimed_by, claim_secs, WebhookDeliveryStatus::Pending.as_u8(), limit);
+
+    if let Err(e) = query.execute(pool).await {
+        return Err(anyhow!(CustomError::DB(error_line!(e))));
+    }
+
+    let query  = sqlx::query("SELECT webhook_outbox.*, protocol.callback_url, protocol.callback_secret from webhook_outbox join protocol on webhook_outbox.protocol_name = protocol.protocol_name where webhook_outbox.claimed_by = ? and webhook_outbox.status = ? and webhook_outbox.claimed_until > NOW() order by webhook_outbox.id")
+                .bind(claimed_by).bind(WebhookDeliveryStatus::Pending.as_u8());
+
+    info!("{}", query.sql());
+    info!("arguments: {}, {}", claimed_by, WebhookDeliveryStatus::Pending.as_u8());
+
+    let rows = match query.fetch_all(pool).await {
+        Ok(t) => Ok(t),
+        Err(e) => Err(anyhow!(CustomError::DB(error_line!(e))))
+    }?;
+    let mut events = vec![];
+    for row in rows {
+        events.push(get_webhook_event_from_mysql_row(row)?);
+    }
+    Ok(events)
+}
+
+pub async fn mark_webhook_event_delivered(pool: &Pool<MySql>, event_id: u64) -> AnyhowResult<()> {
+    let query  = sqlx::query("UPDATE webhook_outbox set status = ?, attempts = attempts + 1, delivered_at = NOW(), claimed_until = NULL where id = ?")
+                .bind(WebhookDeliveryStatus::Delivered.as_u8()).bind(event_id);
+
+    info!("{}", query.sql());
+    info!("arguments: {}, {}", WebhookDeliveryStatus::Delivered.as_u8(), event_id);
+
+    let row_affected = match query.execute(pool).await {
+        Ok(_) => Ok(()),
+        Err(e) => Err(anyhow!(CustomError::DB(error_line!(e))))
+    };
+    row_affected
+}
+
+// Records a failed delivery. The event is retried after `retry_after_secs`, or marked Failed when it is None.
+pub async fn mark_webhook_event_failed(pool: &Pool<MySql>, event_id: u64, last_error: &str, retry_after_secs: Option<u64>) -> AnyhowResult<()> {
+    let status = match retry_after_secs {
+        Some(_) => WebhookDeliveryStatus::Pending,
+        None => WebhookDeliveryStatus::Failed,
+    };
+    let retry_after_secs = retry_after_secs.unwrap_or(0);
+    let query  = sqlx::query("UPDATE webhook_outbox set status = ?, attempts = attempts + 1, last_error = ?, next_attempt_at = DATE_ADD(NOW(), INTERVAL ? SECOND), claimed_until = NULL where id = ?")
+                .bind(status.as_u8()).bind(last_error).bind(retry_after_secs).bind(event_id);
+
+    info!("{}", query.sql());
+    info!("arguments: {}, {}, {}, {}", status.as_u8(), last_error, retry_after_secs, event_id);
+
+    let row_affected = match query.execute(pool).await {
+        Ok(_) => Ok(()),
+        Err(e) => Err(anyhow!(CustomError::DB(error_line!(e))))
+    };
+    row_affected
+}
+
+fn get_webhook_event_from_mysql_row(row: MySqlRow) -> AnyhowResult<WebhookEvent> {
+    let status: u8 = row.try_get_unchecked("status")?;
+    let status = WebhookDeliveryStatus::try_from(status).map_err(|e| anyhow!(CustomError::DB(error_line!(e))))?;
+    let webhook_event = WebhookEvent {
+        id: row.try_get_unchecked("id")?,
+        protocol_name: row.try_get_unchecked("protocol_name")?,
+        event_type: row.try_get_unchecked("event_type")?,
+        payload: row.try_get_unchecked("payload")?,
+        status,
+        attempts: row.try_get_unchecked("attempts")?,
+        callback_url: row.try_get_unchecked("callback_url")?,
+        callback_secret: row.try_get_unchecked("callback_secret")?,
+    };
+    Ok(webhook_event)
+}
@@ -0,0 +1,49 @@
+use quantum_utils::error_line;
+use sqlx::{Execute, MySql, Pool};
+use anyhow::{anyhow, Result as AnyhowResult};
+use tracing::info;
+
+use crate::error::error::CustomError;
+
+// Takes the lock for the worker if it is free, already held by the worker, or its holder stopped renewing it
+pub async fn acquire_worker_lock(pool: &Pool<MySql>, lock_name: &str, worker_id: &str, lease_secs: u64) -> AnyhowResult<bool> {
+    let query  = sqlx::query("UPDATE worker_lock set held_by = ?, lease_expires_at = DATE_ADD(NOW(), INTERVAL ? SECOND) where lock_name = ? and (held_by is NULL or held_by = ? or lease_expires_at < NOW())")
+                .bind(worker_id).bind(lease_secs).bind(lock_name).bind(worker_id);
+
+    info!("{}", query.sql());
+    info!("arguments: {}, {}, {}, {}", worker_id, lease_secs, lock_name, worker_id);
+
+    let is_acquired = match query.execute(pool).await {
+        Ok(t) => Ok(t.rows_affected() == 1),
+        Err(e) => Err(anyhow!(CustomError::DB(error_line!(e))))
+    };
+    is_acquired
+}
+
+pub async fn renew_worker_lock(pool: &Pool<MySql>, lock_name: &str, worker_id: &str, lease_secs: u64) -> AnyhowResult<bool> {
+    let query  = sqlx::query("UPDATE worker_lock set lease_expires_at = DATE_ADD(NOW(), INTERVAL ? SECOND) where lock_name = ? and held_by = ?")
+                .bind(lease_secs).bind(lock_name).bind(worker_id);
+
+    info!("{}", query.sql());
+    info!("arguments: {}, {}, {}", lease_secs, lock_name, worker_id);
+
+    let is_renewed = match query.execute(pool).await {
+        Ok(t) => Ok(t.rows_affected() == 1),
+        Err(e) => Err(anyhow!(CustomError::DB(error_line!(e))))
+    };
+    is_renewed
+}
+
+pub async fn release_worker_lock(pool: &Pool<MySql>, lock_name: &str, worker_id: &str) -> AnyhowResult<()> {
+    let query  = sqlx::query("UPDATE worker_lock set held_by = NULL, lease_expires_at = NULL where lock_name = ? and held_by = ?")
+                .bind(lock_name).bind(worker_id);
+
+    info!("{}", query.sql());
+    info!("arguments: {}, {}", lock_name, worker_id);
+
+    let row_affected = match query.execute(pool).await {
+        Ok(_) => Ok(()),
+        Err(e) => Err(anyhow!(CustomError::DB(error_line!(e))))
+    };
+    row_affected
+}
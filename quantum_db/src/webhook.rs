@@ -0,0 +1,278 @@
+use std::{net::{IpAddr, Ipv4Addr, SocketAddr}, time::Duration};
+
+use anyhow::{anyhow, Result as AnyhowResult};
+use hmac::{Hmac, Mac};
+use quantum_types::types::db::webhook_event::WebhookEvent;
+use quantum_utils::{error_line, retry::RetryPolicy};
+use sha2::Sha256;
+use sqlx::{MySql, Pool};
+use tracing::{error, info};
+
+use crate::repository::webhook_outbox_repository::{claim_due_webhook_events, mark_webhook_event_delivered, mark_webhook_event_failed};
+
+pub const WEBHOOK_SIGNATURE_HEADER: &str = "X-Quantum-Signature";
+pub const WEBHOOK_TIMESTAMP_HEADER: &str = "X-Quantum-Timestamp";
+pub const WEBHOOK_EVENT_ID_HEADER: &str = "X-Quantum-Event-Id";
+
+const WEBHOOK_CLAIM_BATCH_SIZE: u64 = 50;
+const WEBHOOK_REQUEST_TIMEOUT_SECS: u64 = 10;
+
+/*
+    Hex encoded HMAC-SHA256 of "<timestamp>.<payload>" keyed with the protocol's callback secret.
+    Receivers should recompute it, compare, and reject stale timestamps.
+ */
+pub fn sign_webhook_payload(secret: &str, timestamp: i64, payload: &str) -> AnyhowResult<String> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).map_err(|err| anyhow!(error_line!(err)))?;
+    mac.update(format!("{}.{}", timestamp, payload).as_bytes());
+    Ok(hex::encode(mac.finalize().into_bytes()))
+}
+
+fn is_public_ipv4(ip: &Ipv4Addr) -> bool {
+    let octets = ip.octets();
+    // 0.0.0.0/8 and the 100.64.0.0/10 carrier-grade NAT range are not covered by the std helpers
+    !(ip.is_private() || ip.is_loopback() || ip.is_link_local() || ip.is_unspecified() || ip.is_broadcast()
+        || ip.is_documentation() || ip.is_multicast() || octets[0] == 0 || (octets[0] == 100 && (octets[1] & 0xc0) == 64))
+}
+
+fn is_public_ip(ip: &IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => is_public_ipv4(ip),
+        IpAddr::V6(ip) => {
+            if let Some(mapped) = ip.to_ipv4_mapped() {
+                return is_public_ipv4(&mapped);
+            }
+            let first_segment = ip.segments()[0];
+            // fc00::/7 unique local and fe80::/10 link-local
+            !(ip.is_loopback() || ip.is_unspecified() || ip.is_multicast() || (first_segment & 0xfe00) == 0xfc00 || (first_segment & 0xffc0) == 0xfe80)
+        }
+    }
+}
+
+/*
+    Callback urls are fetched by the server, so only https urls whose host resolves to public addresses are allowed.
+    Checked at registration and again before every delivery, as the host may resolve elsewhere later.
+    Returns the checked address, deliveries are sent to it through `get_pinned_webhook_client` so that the host is
+    not resolved a second time, possibly to an internal address.
+ */
+pub async fn check_callback_url(callback_url: &str) -> AnyhowResult<SocketAddr> {
+    let url = reqwest::Url::parse(callback_url).map_err(|err| anyhow!(error_line!(format!("invalid callback_url: {}", err))))?;
+    if url.scheme() != "https" {
+        return Err(anyhow!(error_line!("callback_url must be an https url")));
+    }
+    let host = url.host_str().ok_or(anyhow!(error_line!("callback_url has no host")))?;
+    let port = url.port_or_known_default().unwrap_or(443);
+    let addresses: Vec<IpAddr> = match host.trim_start_matches('[').trim_end_matches(']').parse::<IpAddr>() {
+        Ok(ip) => vec![ip],
+        Err(_) => tokio::net::lookup_host((host, port)).await
+            .map_err(|err| anyhow!(error_line!(format!("not able to resolve callback_url host: {}", err))))?
+            .map(|addr| addr.ip())
+            .collect(),
+    };
+    if addresses.is_empty() {
+        return Err(anyhow!(error_line!("callback_url host does not resolve")));
+    }
+    if let Some(ip) = addresses.iter().find(|ip| !is_public_ip(ip)) {
+        return Err(anyhow!(error_line!(format!("callback_url host resolves to a non public address: {}", ip))));
+    }
+    Ok(SocketAddr::new(addresses[0], port))
+}
+
+// Client connecting to `checked_addr` for the callback_url host instead of resolving it again
+pub fn get_pinned_webhook_client(callback_url: &str, checked_addr: SocketAddr) -> AnyhowResult<reqwest::Client> {
+    let url = reqwest::Url::parse(callback_url).map_err(|err| anyhow!(error_line!(format!("invalid callback_url: {}", err))))?;
+    let host = url.host_str().ok_or(anyhow!(error_line!("callback_url has no host")))?;
+    // redirects are not followed, they could point the request at an internal address
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(WEBHOOK_REQUEST_TIMEOUT_SECS))
+        .redirect(reqwest::redirect::Policy::none())
+        .resolve(host, checked_addr)
+        .build()
+        .map_err(|err| anyhow!(error_line!(format!("not able to build webhook http client: {}", err))))?;
+    Ok(client)
+}
+
+// Event ids are stable across retries, deliveries are at-least-once so receivers should dedupe on it
+pub async fn send_webhook(client: &reqwest::Client, callback_url: &str, callback_secret: &str, event_id: u64, payload: &str) -> AnyhowResult<()> {
+    let timestamp = chrono::Utc::now().timestamp();
+    let signature = sign_webhook_payload(callback_secret, timestamp, payload)?;
+
+    let res = client
+        .post(callback_url)
+        .header("Content-Type", "application/json")
+        .header(WEBHOOK_EVENT_ID_HEADER, event_id.to_string())
+        .header(WEBHOOK_TIMESTAMP_HEADER, timestamp.to_string())
+        .header(WEBHOOK_SIGNATURE_HEADER, format!("sha256={}", signature))
+        .body(payload.to_string())
+        .send()
+        .await?;
+
+    if !res.status().is_success() {
+        return Err(anyhow!(error_line!(format!("callback responded with status: {}", res.status()))));
+    }
+    Ok(())
+}
+
+async fn deliver_webhook_event(pool: &Pool<MySql>, event: &WebhookEvent, retry_policy: &RetryPolicy) -> AnyhowResult<()> {
+    let event_id = event.id.ok_or(anyhow!(error_line!("missing webhook event id")))?;
+    let result = match (&event.callback_url, &event.callback_secret) {
+        (Some(callback_url), Some(callback_secret)) => match check_callback_url(callback_url).await
+            .and_then(|checked_addr| get_pinned_webhook_client(callback_url, checked_addr)) {
+            Ok(client) => send_webhook(&client, callback_url, callback_secret, event_id, &event.payload).await,
+            Err(e) => Err(e),
+        },
+        _ => Err(anyhow!(error_line!("protocol has no callback registered"))),
+    };
+
+    match result {
+        Ok(_) => {
+            info!("delivered webhook event {} to protocol {}", event_id, event.protocol_name);
+            mark_webhook_event_delivered(pool, event_id).await?;
+        }
+        Err(e) => {
+            let attempt = event.attempts as u32 + 1;
+            let retry_after_secs = match attempt >= retry_policy.max_attempts {
+                true => None,
+                false => Some(std::cmp::max(retry_policy.get_delay(attempt).as_secs(), 1)),
+            };
+            error!("webhook event {} delivery attempt {} failed, retry after: {:?}, error: {:?}", event_id, attempt, retry_after_secs, e);
+            mark_webhook_event_failed(pool, event_id, &e.to_string(), retry_after_secs).await?;
+        }
+    }
+    Ok(())
+}
+
+// Delivers the currently due outbox events, returns the number of events attempted
+pub async fn dispatch_due_webhook_events(pool: &Pool<MySql>, claimed_by: &str, retry_policy: &RetryPolicy) -> AnyhowResult<usize> {
+    // claim long enough for every event of the batch to time out once
+    let claim_secs = WEBHOOK_CLAIM_BATCH_SIZE * WEBHOOK_REQUEST_TIMEOUT_SECS;
+    let events = claim_due_webhook_events(pool, claimed_by, claim_secs, WEBHOOK_CLAIM_BATCH_SIZE).await?;
+    for event in &events {
+        deliver_webhook_event(pool, event, retry_policy).await?;
+    }
+    Ok(events.len())
+}
+
+/*
+    Polls the webhook outbox forever. Several dispatchers (worker, quantum_contract) can run together,
+    `claimed_by` must be unique per process so they don't deliver the same event concurrently.
+ */
+pub async fn run_webhook_dispatcher(pool: &'static Pool<MySql>, claimed_by: String, poll_interval: Duration, retry_policy: RetryPolicy) {
+    info!("starting webhook dispatcher: {}", claimed_by);
+    loop {
+        match dispatch_due_webhook_events(pool, &claimed_by, &retry_policy).await {
+            // more events may be due, poll again right away
+            Ok(n) if n as u64 == WEBHOOK_CLAIM_BATCH_SIZE => continue,
+            Ok(_) => {}
+            Err(e) => error!("error in webhook dispatcher: {:?}", e),
+        }
+        tokio::time::sleep(poll_interval).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::{io::{AsyncReadExt, AsyncWriteExt}, net::TcpListener};
+
+    // Local HTTP stand-in for a protocol callback endpoint, returns the raw request it received
+    async fn serve_one_request(listener: TcpListener, response_status: &'static str) -> String {
+        let (mut socket, _) = listener.accept().await.unwrap();
+        let mut request = vec![];
+        let mut buf = [0u8; 4096];
+        loop {
+            let n = socket.read(&mut buf).await.unwrap();
+            request.extend_from_slice(&buf[..n]);
+            let request_str = String::from_utf8_lossy(&request).to_string();
+            if let Some(header_end) = request_str.find("\r\n\r\n") {
+                let content_length = request_str
+                    .lines()
+                    .find_map(|line| line.to_lowercase().strip_prefix("content-length:").map(|v| v.trim().parse::<usize>().unwrap()))
+                    .unwrap_or(0);
+                if request.len() >= header_end + 4 + content_length {
+                    break;
+                }
+            }
+            if n == 0 {
+                break;
+            }
+        }
+        let response = format!("HTTP/1.1 {}\r\ncontent-length: 0\r\nconnection: close\r\n\r\n", response_status);
+        socket.write_all(response.as_bytes()).await.unwrap();
+        String::from_utf8_lossy(&request).to_string()
+    }
+
+    fn get_header(request: &str, name: &str) -> Option<String> {
+        request
+            .lines()
+            .find(|line| line.to_lowercase().starts_with(&format!("{}:", name.to_lowercase())))
+            .map(|line| line.splitn(2, ':').nth(1).unwrap().trim().to_string())
+    }
+
+    #[tokio::test]
+    async fn test_send_webhook_signs_payload() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let callback_url = format!("http://{}/callback", listener.local_addr().unwrap());
+        let server = tokio::spawn(serve_one_request(listener, "200 OK"));
+
+        let payload = r#"{"event": "proof_status", "proof_id": 1, "status": "Verified"}"#;
+        send_webhook(&reqwest::Client::new(), &callback_url, "secret", 7, payload).await.unwrap();
+
+        let request = server.await.unwrap();
+        assert!(request.ends_with(payload));
+        assert_eq!(get_header(&request, WEBHOOK_EVENT_ID_HEADER).unwrap(), "7");
+        let timestamp: i64 = get_header(&request, WEBHOOK_TIMESTAMP_HEADER).unwrap().parse().unwrap();
+        let expected_signature = format!("sha256={}", sign_webhook_payload("secret", timestamp, payload).unwrap());
+        assert_eq!(get_header(&request, WEBHOOK_SIGNATURE_HEADER).unwrap(), expected_signature);
+    }
+
+    #[tokio::test]
+    async fn test_send_webhook_fails_on_error_status() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let callback_url = format!("http://{}/callback", listener.local_addr().unwrap());
+        let server = tokio::spawn(serve_one_request(listener, "503 Service Unavailable"));
+
+        let result = send_webhook(&reqwest::Client::new(), &callback_url, "secret", 7, "{}").await;
+        server.await.unwrap();
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_pinned_webhook_client_does_not_resolve_the_host() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let checked_addr = listener.local_addr().unwrap();
+        // the .invalid tld never resolves, the request can only reach the pinned address
+        let callback_url = format!("http://callback.invalid:{}/callback", checked_addr.port());
+        let server = tokio::spawn(serve_one_request(listener, "200 OK"));
+
+        let client = get_pinned_webhook_client(&callback_url, checked_addr).unwrap();
+        send_webhook(&client, &callback_url, "secret", 7, "{}").await.unwrap();
+        let request = server.await.unwrap();
+        assert_eq!(get_header(&request, "host").unwrap(), format!("callback.invalid:{}", checked_addr.port()));
+    }
+
+    #[tokio::test]
+    async fn test_check_callback_url_rejects_internal_urls() {
+        for callback_url in [
+            "http://93.184.216.34/callback",
+            "https://127.0.0.1/callback",
+            "https://10.0.0.5/callback",
+            "https://192.168.1.1/callback",
+            "https://169.254.169.254/latest/meta-data",
+            "https://100.64.0.1/callback",
+            "https://0.0.0.0/callback",
+            "https://[::1]/callback",
+            "https://[fe80::1]/callback",
+            "https://[fd00::1]/callback",
+            "https://[::ffff:127.0.0.1]/callback",
+            "https://localhost/callback",
+        ] {
+            assert!(check_callback_url(callback_url).await.is_err(), "{} should be rejected", callback_url);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_check_callback_url_accepts_public_https_url() {
+        let checked_addr = check_callback_url("https://93.184.216.34:8443/callback").await.unwrap();
+        assert_eq!(checked_addr, "93.184.216.34:8443".parse().unwrap());
+    }
+}
@@ -0,0 +1,76 @@
+use anyhow::{anyhow, Result as AnyhowResult};
+use quantum_db::error::error::CustomError;
+use quantum_utils::error_line;
+use sqlx::{mysql::{MySqlConnectOptions, MySqlPoolOptions}, ConnectOptions, Execute, MySql, Pool};
+use tokio::sync::OnceCell;
+use tracing::info;
+
+static POOL: OnceCell<Pool<MySql>> = OnceCell::const_new();
+
+pub async fn get_pool() -> &'static Pool<MySql> {
+    POOL.get_or_init(|| async {
+        dotenv::from_filename("../.env.test").ok();
+        let username = std::env::var("DB_USER").expect("DB_USER must be set.");
+        let password = std::env::var("DB_PASSWORD").expect("DB_PASSWORD must be set.");
+        let database = std::env::var("DB_NAME").expect("DB_NAME must be set.");
+
+        let connection_options = MySqlConnectOptions::new()
+            .username(&username)
+            .password(&password)
+            .database(&database)
+            .disable_statement_logging().clone();
+
+        MySqlPoolOptions::new().connect_with(connection_options).await.unwrap()
+    })
+    .await
+}
+
+pub async fn insert_protocol_with_priority(pool: &Pool<MySql>, protocol_name: &str, priority_tier: Option<u8>, fair_share_weight: u32) -> AnyhowResult<()>{
+    let query = sqlx::query("INSERT INTO protocol (protocol_name, priority_tier, fair_share_weight) VALUES (?, ?, ?) ON DUPLICATE KEY UPDATE priority_tier = VALUES(priority_tier), fair_share_weight = VALUES(fair_share_weight)")
+        .bind(protocol_name).bind(priority_tier).bind(fair_share_weight);
+
+    info!("{}", query.sql());
+
+    let row_affected = match query.execute(&mut *pool.acquire().await.unwrap()).await {
+        Ok(_) => Ok(()),
+        Err(e) => Err(anyhow!(CustomError::DB(error_line!(e))))
+    };
+    row_affected
+}
+
+pub async fn delete_protocol_from_protocol_name(pool: &Pool<MySql>, protocol_name: &str) -> AnyhowResult<()>{
+    let query = sqlx::query("DELETE FROM protocol WHERE protocol_name=?").bind(protocol_name);
+
+    info!("{}", query.sql());
+
+    let row_affected = match query.execute(&mut *pool.acquire().await.unwrap()).await {
+        Ok(_) => Ok(()),
+        Err(e) => Err(anyhow!(CustomError::DB(error_line!(e))))
+    };
+    row_affected
+}
+
+pub async fn delete_user_circuit_data_by_circuit_hash(pool: &Pool<MySql>, circuit_hash: &str) -> AnyhowResult<()> {
+    let query  = sqlx::query("DELETE from user_circuit_data WHERE circuit_hash=?").bind(circuit_hash);
+
+    info!("{}", query.sql());
+
+    let row_affected = match query.execute(&mut *pool.acquire().await.unwrap()).await {
+        Ok(_) => Ok(()),
+        Err(e) => Err(anyhow!(CustomError::DB(error_line!(e))))
+    };
+    row_affected
+}
+
+pub async fn delete_proofs_and_tasks_by_circuit_hash(pool: &Pool<MySql>, circuit_hash: &str) -> AnyhowResult<()>{
+    for sql in ["DELETE FROM task WHERE user_circuit_hash=?", "DELETE FROM proof WHERE user_circuit_hash=?"] {
+        let query = sqlx::query(sql).bind(circuit_hash);
+
+        info!("{}", query.sql());
+
+        if let Err(e) = query.execute(&mut *pool.acquire().await.unwrap()).await {
+            return Err(anyhow!(CustomError::DB(error_line!(e))));
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,69 @@
+mod common;
+use common::{delete_proofs_and_tasks_by_circuit_hash, delete_protocol_from_protocol_name, delete_user_circuit_data_by_circuit_hash, get_pool, insert_protocol_with_priority};
+use quantum_db::repository::{proof_repository::{get_reduced_proofs_r0, insert_proofs_with_tasks, update_proof_status, NewProof}, task_repository::get_unpicked_tasks, user_circuit_data_repository::insert_user_circuit_data};
+use quantum_types::enums::{circuit_reduction_status::CircuitReductionStatus, proof_status::ProofStatus, proving_schemes::ProvingSchemes, task_type::TaskType};
+
+// (protocol, priority tier, fair share weight), each protocol registers one circuit named after it
+const PROTOCOLS: [(&str, Option<u8>, u32); 3] = [
+    ("fair_queue_light", None, 1),
+    ("fair_queue_heavy", None, 2),
+    ("fair_queue_tier", Some(1), 1),
+];
+
+fn get_circuit_hash(protocol_name: &str) -> String {
+    format!("0x{}", protocol_name)
+}
+
+async fn cleanup() {
+    for (protocol_name, _, _) in PROTOCOLS {
+        let circuit_hash = get_circuit_hash(protocol_name);
+        delete_proofs_and_tasks_by_circuit_hash(get_pool().await, &circuit_hash).await.unwrap();
+        delete_user_circuit_data_by_circuit_hash(get_pool().await, &circuit_hash).await.unwrap();
+        delete_protocol_from_protocol_name(get_pool().await, protocol_name).await.unwrap();
+    }
+}
+
+fn get_protocol_names(circuit_hashes: Vec<String>) -> Vec<String> {
+    let protocol_circuit_hashes: Vec<String> = PROTOCOLS.iter().map(|(protocol_name, _, _)| get_circuit_hash(protocol_name)).collect();
+    circuit_hashes
+        .into_iter()
+        .filter(|circuit_hash| protocol_circuit_hashes.contains(circuit_hash))
+        .map(|circuit_hash| circuit_hash.trim_start_matches("0x").to_string())
+        .collect()
+}
+
+#[tokio::test]
+async fn test_proofs_and_tasks_are_picked_by_tier_then_weighted_share() {
+    cleanup().await;
+
+    for (protocol_name, priority_tier, fair_share_weight) in PROTOCOLS {
+        insert_protocol_with_priority(get_pool().await, protocol_name, priority_tier, fair_share_weight).await.unwrap();
+        insert_user_circuit_data(get_pool().await, &get_circuit_hash(protocol_name), "vk_path", ProvingSchemes::GnarkGroth16, protocol_name, "image_id", CircuitReductionStatus::Completed).await.unwrap();
+    }
+
+    // submission order: the light protocol first, then the heavy one, the higher tier last
+    let submissions = ["fair_queue_light", "fair_queue_light", "fair_queue_heavy", "fair_queue_heavy", "fair_queue_heavy", "fair_queue_heavy", "fair_queue_tier"];
+    let new_proofs: Vec<NewProof> = submissions.iter().enumerate().map(|(i, protocol_name)| NewProof {
+        proof_hash: format!("0xfair_queue_proof_{}", i),
+        pis_path: "pis_path".to_string(),
+        proof_path: "proof_path".to_string(),
+        proof_status: ProofStatus::Registered,
+        user_circuit_hash: get_circuit_hash(protocol_name),
+        pis_json_string: "[]".to_string(),
+    }).collect();
+    let proof_ids = insert_proofs_with_tasks(get_pool().await, &new_proofs, false).await.unwrap();
+
+    // the tier goes first, then ranks within a tier are queue positions divided by the weight (heavy 0.5, 1, 1.5, 2 and light 1, 2)
+    let expected_order = vec!["fair_queue_tier", "fair_queue_heavy", "fair_queue_light", "fair_queue_heavy", "fair_queue_heavy", "fair_queue_light", "fair_queue_heavy"];
+
+    let tasks = get_unpicked_tasks(get_pool().await, TaskType::ProofGeneration, 1000).await.unwrap();
+    assert_eq!(get_protocol_names(tasks.into_iter().map(|task| task.user_circuit_hash).collect()), expected_order);
+
+    for proof_id in proof_ids {
+        update_proof_status(get_pool().await, proof_id.unwrap(), ProofStatus::Reduced).await.unwrap();
+    }
+    let proofs = get_reduced_proofs_r0(get_pool().await, 1000).await.unwrap();
+    assert_eq!(get_protocol_names(proofs.into_iter().map(|proof| proof.user_circuit_hash).collect()), expected_order);
+
+    cleanup().await;
+}
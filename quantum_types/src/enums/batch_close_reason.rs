@@ -0,0 +1,46 @@
+use serde::{Deserialize, Serialize};
+
+// Limit which closed the risc0 batch of a superproof
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Clone, Copy)]
+pub enum BatchCloseReason {
+    QueueDrained = 1,
+    MaxBatchSize = 2,
+    MaxCycleCount = 3,
+    Isolated = 4,
+}
+
+impl BatchCloseReason {
+    pub fn as_u8(&self) -> u8 {
+        match self {
+            BatchCloseReason::QueueDrained => 1,
+            BatchCloseReason::MaxBatchSize => 2,
+            BatchCloseReason::MaxCycleCount => 3,
+            BatchCloseReason::Isolated => 4,
+        }
+    }
+}
+
+impl TryFrom<u8> for BatchCloseReason {
+    type Error = String;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            1 => Ok(BatchCloseReason::QueueDrained),
+            2 => Ok(BatchCloseReason::MaxBatchSize),
+            3 => Ok(BatchCloseReason::MaxCycleCount),
+            4 => Ok(BatchCloseReason::Isolated),
+            _ => Err(format!("Invalid batch close reason: {}", value)),
+        }
+    }
+}
+
+impl ToString for BatchCloseReason {
+    fn to_string(&self) -> String {
+        match self {
+            BatchCloseReason::QueueDrained => String::from("QueueDrained"),
+            BatchCloseReason::MaxBatchSize => String::from("MaxBatchSize"),
+            BatchCloseReason::MaxCycleCount => String::from("MaxCycleCount"),
+            BatchCloseReason::Isolated => String::from("Isolated"),
+        }
+    }
+}
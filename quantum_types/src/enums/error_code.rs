@@ -0,0 +1,56 @@
+use serde::{Deserialize, Serialize};
+
+// Stable machine-readable codes returned to clients in error responses, do not rename existing variants
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Clone, Copy)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ErrorCode {
+    BadRequest,
+    InvalidPayload,
+    Unauthorized,
+    NotFound,
+    PayloadTooLarge,
+    UnsupportedMediaType,
+    CircuitNotRegistered,
+    CircuitReductionNotCompleted,
+    SchemeMismatch,
+    UnsupportedScheme,
+    ProofDuplicate,
+    ProofInvalid,
+    PisInvalid,
+    VkeyInvalid,
+    ProofNotVerified,
+    ProtocolAlreadyRegistered,
+    DatabaseError,
+    InternalError,
+}
+
+impl ErrorCode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ErrorCode::BadRequest => "BAD_REQUEST",
+            ErrorCode::InvalidPayload => "INVALID_PAYLOAD",
+            ErrorCode::Unauthorized => "UNAUTHORIZED",
+            ErrorCode::NotFound => "NOT_FOUND",
+            ErrorCode::PayloadTooLarge => "PAYLOAD_TOO_LARGE",
+            ErrorCode::UnsupportedMediaType => "UNSUPPORTED_MEDIA_TYPE",
+            ErrorCode::CircuitNotRegistered => "CIRCUIT_NOT_REGISTERED",
+            ErrorCode::CircuitReductionNotCompleted => "CIRCUIT_REDUCTION_NOT_COMPLETED",
+            ErrorCode::SchemeMismatch => "SCHEME_MISMATCH",
+            ErrorCode::UnsupportedScheme => "UNSUPPORTED_SCHEME",
+            ErrorCode::ProofDuplicate => "PROOF_DUPLICATE",
+            ErrorCode::ProofInvalid => "PROOF_INVALID",
+            ErrorCode::PisInvalid => "PIS_INVALID",
+            ErrorCode::VkeyInvalid => "VKEY_INVALID",
+            ErrorCode::ProofNotVerified => "PROOF_NOT_VERIFIED",
+            ErrorCode::ProtocolAlreadyRegistered => "PROTOCOL_ALREADY_REGISTERED",
+            ErrorCode::DatabaseError => "DATABASE_ERROR",
+            ErrorCode::InternalError => "INTERNAL_ERROR",
+        }
+    }
+}
+
+impl ToString for ErrorCode {
+    fn to_string(&self) -> String {
+        String::from(self.as_str())
+    }
+}
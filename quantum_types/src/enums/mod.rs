@@ -3,4 +3,9 @@ pub mod circuit_reduction_status;
 pub mod proof_status;
 pub mod proving_schemes;
 pub mod task_status;
-pub mod superproof_status;
\ No newline at end of file
+pub mod superproof_status;
+pub mod prover_backend;
+pub mod webhook_delivery_status;
+pub mod error_code;
+pub mod risc0_receipt_kind;
+pub mod batch_close_reason;
\ No newline at end of file
@@ -0,0 +1,17 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub enum ProverBackendType {
+    #[default]
+    Bonsai,
+    Local,
+}
+
+impl ToString for ProverBackendType {
+    fn to_string(&self) -> String {
+        match self {
+            ProverBackendType::Bonsai => String::from("Bonsai"),
+            ProverBackendType::Local => String::from("Local"),
+        }
+    }
+}
@@ -12,7 +12,14 @@ pub enum ProvingSchemes {
     Halo2Poseidon,
     Risc0,
     Sp1,
-    NitroAtt
+    NitroAtt,
+    GnarkGroth16Bls12381,
+    GnarkPlonkBls12381,
+    ArkGroth16,
+    Halo2Ipa,
+    Plonky3,
+    Sp1Groth16,
+    Sp1Plonk
 }
 
 impl FromStr for ProvingSchemes {
@@ -35,6 +42,13 @@ impl FromStr for ProvingSchemes {
             "sp1" => Ok(ProvingSchemes::Sp1),
             "risc0" => Ok(ProvingSchemes::Risc0),
             "nitroatt" => Ok(ProvingSchemes::NitroAtt),
+            "gnarkgroth16bls12381" => Ok(ProvingSchemes::GnarkGroth16Bls12381),
+            "gnarkplonkbls12381" => Ok(ProvingSchemes::GnarkPlonkBls12381),
+            "arkgroth16" => Ok(ProvingSchemes::ArkGroth16),
+            "halo2ipa" => Ok(ProvingSchemes::Halo2Ipa),
+            "plonky3" => Ok(ProvingSchemes::Plonky3),
+            "sp1groth16" => Ok(ProvingSchemes::Sp1Groth16),
+            "sp1plonk" => Ok(ProvingSchemes::Sp1Plonk),
             _ => Err(format!("Invalid proving scheme: {}", s)),
         }
     }
@@ -52,6 +66,13 @@ impl ToString for ProvingSchemes {
             ProvingSchemes::Sp1 => String::from("Sp1"),
             ProvingSchemes::Risc0 => String::from("Risc0"),
             ProvingSchemes::NitroAtt => String::from("NitroAtt"),
+            ProvingSchemes::GnarkGroth16Bls12381 => String::from("GnarkGroth16Bls12381"),
+            ProvingSchemes::GnarkPlonkBls12381 => String::from("GnarkPlonkBls12381"),
+            ProvingSchemes::ArkGroth16 => String::from("ArkGroth16"),
+            ProvingSchemes::Halo2Ipa => String::from("Halo2Ipa"),
+            ProvingSchemes::Plonky3 => String::from("Plonky3"),
+            ProvingSchemes::Sp1Groth16 => String::from("Sp1Groth16"),
+            ProvingSchemes::Sp1Plonk => String::from("Sp1Plonk"),
         }
     }
 }
\ No newline at end of file
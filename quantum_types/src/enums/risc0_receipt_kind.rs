@@ -0,0 +1,44 @@
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+// Kinds of risc0 receipts a reduction image can take, `Composite` receipts are compressed to succinct by the worker
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub enum Risc0ReceiptKind {
+    Composite,
+    Succinct,
+    Groth16,
+}
+
+impl Risc0ReceiptKind {
+    pub fn as_u8(&self) -> u8 {
+        match self {
+            Risc0ReceiptKind::Composite => 0,
+            Risc0ReceiptKind::Succinct => 1,
+            Risc0ReceiptKind::Groth16 => 2,
+        }
+    }
+}
+
+impl FromStr for Risc0ReceiptKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "composite" => Ok(Risc0ReceiptKind::Composite),
+            "succinct" => Ok(Risc0ReceiptKind::Succinct),
+            "groth16" => Ok(Risc0ReceiptKind::Groth16),
+            _ => Err(format!("Invalid risc0 receipt kind: {}", s)),
+        }
+    }
+}
+
+impl ToString for Risc0ReceiptKind {
+    fn to_string(&self) -> String {
+        match self {
+            Risc0ReceiptKind::Composite => String::from("Composite"),
+            Risc0ReceiptKind::Succinct => String::from("Succinct"),
+            Risc0ReceiptKind::Groth16 => String::from("Groth16"),
+        }
+    }
+}
@@ -0,0 +1,41 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Clone)]
+pub enum WebhookDeliveryStatus {
+    Pending = 1,
+    Delivered = 2,
+    Failed = 3,
+}
+
+impl WebhookDeliveryStatus {
+    pub fn as_u8(&self) -> u8 {
+        match self {
+            WebhookDeliveryStatus::Pending => 1,
+            WebhookDeliveryStatus::Delivered => 2,
+            WebhookDeliveryStatus::Failed => 3,
+        }
+    }
+}
+
+impl TryFrom<u8> for WebhookDeliveryStatus {
+    type Error = String;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            1 => Ok(WebhookDeliveryStatus::Pending),
+            2 => Ok(WebhookDeliveryStatus::Delivered),
+            3 => Ok(WebhookDeliveryStatus::Failed),
+            _ => Err(format!("Invalid webhook delivery status: {}", value)),
+        }
+    }
+}
+
+impl ToString for WebhookDeliveryStatus {
+    fn to_string(&self) -> String {
+        match self {
+            WebhookDeliveryStatus::Pending => String::from("Pending"),
+            WebhookDeliveryStatus::Delivered => String::from("Delivered"),
+            WebhookDeliveryStatus::Failed => String::from("Failed"),
+        }
+    }
+}
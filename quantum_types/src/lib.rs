@@ -1,3 +1,4 @@
 pub mod types;
 pub mod enums;
-pub mod traits;
\ No newline at end of file
+pub mod traits;
+pub mod schemes;
\ No newline at end of file
@@ -0,0 +1,22 @@
+use anyhow::Result as AnyhowResult;
+
+use crate::{enums::proving_schemes::ProvingSchemes, types::ark_groth16::{ArkGroth16Pis, ArkGroth16Proof, ArkGroth16Vkey}};
+
+use super::{snarkjs_groth16::form_ark_groth16_bonsai_inputs, Scheme};
+
+pub struct ArkGroth16Scheme;
+
+// Reduced and aggregated exactly like snarkjs groth16, only the submission format differs
+impl Scheme for ArkGroth16Scheme {
+    type Vkey = ArkGroth16Vkey;
+    type Proof = ArkGroth16Proof;
+    type Pis = ArkGroth16Pis;
+
+    const PROVING_SCHEME: ProvingSchemes = ProvingSchemes::ArkGroth16;
+    const REDUCTION_PROVING_SCHEME: ProvingSchemes = ProvingSchemes::Groth16;
+    const AGGREGATION_PROTOCOL_ID: Option<u8> = Some(0);
+
+    fn form_bonsai_inputs(vk: &ArkGroth16Vkey, proof: &ArkGroth16Proof, pis: &ArkGroth16Pis) -> AnyhowResult<Vec<u8>> {
+        form_ark_groth16_bonsai_inputs(&vk.get_ark_vk()?, &proof.get_ark_proof()?, &pis.get_ark_pis()?)
+    }
+}
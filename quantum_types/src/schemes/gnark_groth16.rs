@@ -0,0 +1,34 @@
+use anyhow::Result as AnyhowResult;
+use ark_serialize::CanonicalSerialize;
+use risc0_zkvm::serde::to_vec;
+
+use crate::{enums::proving_schemes::ProvingSchemes, types::gnark_groth16::{GnarkGroth16Pis, GnarkGroth16Proof, GnarkGroth16Vkey}};
+
+use super::Scheme;
+
+pub struct GnarkGroth16Scheme;
+
+impl Scheme for GnarkGroth16Scheme {
+    type Vkey = GnarkGroth16Vkey;
+    type Proof = GnarkGroth16Proof;
+    type Pis = GnarkGroth16Pis;
+
+    const PROVING_SCHEME: ProvingSchemes = ProvingSchemes::GnarkGroth16;
+    const AGGREGATION_PROTOCOL_ID: Option<u8> = Some(3);
+
+    fn form_bonsai_inputs(vk: &GnarkGroth16Vkey, proof: &GnarkGroth16Proof, pis: &GnarkGroth16Pis) -> AnyhowResult<Vec<u8>> {
+        let proof_bytes = to_vec(&proof.proof_bytes)?;
+        let vk_bytes = to_vec(&vk.vkey_bytes)?;
+
+        let ark_public_inputs = pis.get_ark_pis_for_gnark_groth16_pis()?;
+        let mut public_inputs_bytes = vec![];
+        ark_public_inputs.serialize_uncompressed(&mut public_inputs_bytes)?;
+        let public_inputs_bytes = to_vec(&public_inputs_bytes)?;
+
+        let mut input_data_vec: Vec<u8> = bytemuck::cast_slice(&vk_bytes).to_vec();
+        input_data_vec.extend_from_slice(bytemuck::cast_slice(&proof_bytes));
+        input_data_vec.extend_from_slice(bytemuck::cast_slice(&public_inputs_bytes));
+
+        Ok(input_data_vec)
+    }
+}
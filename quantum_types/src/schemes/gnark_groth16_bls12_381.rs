@@ -0,0 +1,35 @@
+use anyhow::Result as AnyhowResult;
+use ark_serialize::CanonicalSerialize;
+use risc0_zkvm::serde::to_vec;
+
+use crate::{enums::proving_schemes::ProvingSchemes, types::gnark_groth16_bls12_381::{GnarkGroth16Bls12381Pis, GnarkGroth16Bls12381Proof, GnarkGroth16Bls12381Vkey}};
+
+use super::Scheme;
+
+pub struct GnarkGroth16Bls12381Scheme;
+
+impl Scheme for GnarkGroth16Bls12381Scheme {
+    type Vkey = GnarkGroth16Bls12381Vkey;
+    type Proof = GnarkGroth16Bls12381Proof;
+    type Pis = GnarkGroth16Bls12381Pis;
+
+    const PROVING_SCHEME: ProvingSchemes = ProvingSchemes::GnarkGroth16Bls12381;
+    // the bls12-381 reduction guest commits the same journal as the bn254 one, so its leaves share the gnark groth16 protocol id
+    const AGGREGATION_PROTOCOL_ID: Option<u8> = Some(3);
+
+    fn form_bonsai_inputs(vk: &GnarkGroth16Bls12381Vkey, proof: &GnarkGroth16Bls12381Proof, pis: &GnarkGroth16Bls12381Pis) -> AnyhowResult<Vec<u8>> {
+        let proof_bytes = to_vec(&proof.proof_bytes)?;
+        let vk_bytes = to_vec(&vk.vkey_bytes)?;
+
+        let ark_public_inputs = pis.get_ark_pis()?;
+        let mut public_inputs_bytes = vec![];
+        ark_public_inputs.serialize_uncompressed(&mut public_inputs_bytes)?;
+        let public_inputs_bytes = to_vec(&public_inputs_bytes)?;
+
+        let mut input_data_vec: Vec<u8> = bytemuck::cast_slice(&vk_bytes).to_vec();
+        input_data_vec.extend_from_slice(bytemuck::cast_slice(&proof_bytes));
+        input_data_vec.extend_from_slice(bytemuck::cast_slice(&public_inputs_bytes));
+
+        Ok(input_data_vec)
+    }
+}
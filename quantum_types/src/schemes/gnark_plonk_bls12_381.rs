@@ -0,0 +1,37 @@
+use anyhow::Result as AnyhowResult;
+use ark_serialize::CanonicalSerialize;
+use risc0_zkvm::serde::to_vec;
+
+use crate::{enums::proving_schemes::ProvingSchemes, types::gnark_plonk_bls12_381::{GnarkPlonkBls12381Pis, GnarkPlonkBls12381Proof, GnarkPlonkBls12381Vkey}};
+
+use super::Scheme;
+
+pub struct GnarkPlonkBls12381Scheme;
+
+impl Scheme for GnarkPlonkBls12381Scheme {
+    type Vkey = GnarkPlonkBls12381Vkey;
+    type Proof = GnarkPlonkBls12381Proof;
+    type Pis = GnarkPlonkBls12381Pis;
+
+    const PROVING_SCHEME: ProvingSchemes = ProvingSchemes::GnarkPlonkBls12381;
+    // the bls12-381 reduction guest commits the same journal as the bn254 one, so its leaves share the gnark plonk protocol id
+    const AGGREGATION_PROTOCOL_ID: Option<u8> = Some(4);
+    // until `verify_plonk` passes the gnark fixtures of scripts/gnark_plonk_bls12_381_fixture
+    const SUPPORTED: bool = false;
+
+    fn form_bonsai_inputs(vk: &GnarkPlonkBls12381Vkey, proof: &GnarkPlonkBls12381Proof, pis: &GnarkPlonkBls12381Pis) -> AnyhowResult<Vec<u8>> {
+        let proof_bytes = to_vec(&proof.proof_bytes)?;
+        let vk_bytes = to_vec(&vk.vkey_bytes)?;
+
+        let ark_public_inputs = pis.get_ark_pis()?;
+        let mut public_inputs_bytes = vec![];
+        ark_public_inputs.serialize_uncompressed(&mut public_inputs_bytes)?;
+        let public_inputs_bytes = to_vec(&public_inputs_bytes)?;
+
+        let mut input_data_vec: Vec<u8> = bytemuck::cast_slice(&vk_bytes).to_vec();
+        input_data_vec.extend_from_slice(bytemuck::cast_slice(&proof_bytes));
+        input_data_vec.extend_from_slice(bytemuck::cast_slice(&public_inputs_bytes));
+
+        Ok(input_data_vec)
+    }
+}
@@ -0,0 +1,37 @@
+use anyhow::Result as AnyhowResult;
+use risc0_zkvm::serde::to_vec;
+
+use crate::{enums::proving_schemes::ProvingSchemes, types::halo2_ipa::{Halo2IpaPis, Halo2IpaProof, Halo2IpaVkey}};
+
+use super::Scheme;
+
+pub struct Halo2IpaScheme;
+
+impl Scheme for Halo2IpaScheme {
+    type Vkey = Halo2IpaVkey;
+    type Proof = Halo2IpaProof;
+    type Pis = Halo2IpaPis;
+
+    const PROVING_SCHEME: ProvingSchemes = ProvingSchemes::Halo2Ipa;
+    const AGGREGATION_PROTOCOL_ID: Option<u8> = Some(11);
+    // no reduction guest reads this input layout and the aggregation guest has no protocol id 11 yet
+    const SUPPORTED: bool = false;
+
+    fn form_bonsai_inputs(vk: &Halo2IpaVkey, proof: &Halo2IpaProof, pis: &Halo2IpaPis) -> AnyhowResult<Vec<u8>> {
+        let protocol = vk.get_protocol()?;
+        let instances = pis.get_instance_bytes()?;
+        let proof = &proof.proof_bytes;
+
+        let protocol_bytes = to_vec(&protocol)?;
+        let params_bytes = to_vec(&vk.params_bytes)?;
+        let instances_bytes = to_vec(&instances)?;
+        let proof_bytes = to_vec(&proof)?;
+
+        let mut input_data_vec: Vec<u8> = bytemuck::cast_slice(&protocol_bytes).to_vec();
+        input_data_vec.extend_from_slice(bytemuck::cast_slice(&params_bytes));
+        input_data_vec.extend_from_slice(bytemuck::cast_slice(&instances_bytes));
+        input_data_vec.extend_from_slice(bytemuck::cast_slice(&proof_bytes));
+
+        Ok(input_data_vec)
+    }
+}
@@ -0,0 +1,36 @@
+use anyhow::Result as AnyhowResult;
+use risc0_zkvm::serde::to_vec;
+
+use crate::{enums::proving_schemes::ProvingSchemes, types::halo2_plonk::{Halo2PlonkPis, Halo2PlonkProof, Halo2PlonkVkey}};
+
+use super::Scheme;
+
+pub struct Halo2PlonkScheme;
+
+impl Scheme for Halo2PlonkScheme {
+    type Vkey = Halo2PlonkVkey;
+    type Proof = Halo2PlonkProof;
+    type Pis = Halo2PlonkPis;
+
+    const PROVING_SCHEME: ProvingSchemes = ProvingSchemes::Halo2Plonk;
+    const AGGREGATION_PROTOCOL_ID: Option<u8> = Some(1);
+
+    fn form_bonsai_inputs(vk: &Halo2PlonkVkey, proof: &Halo2PlonkProof, pis: &Halo2PlonkPis) -> AnyhowResult<Vec<u8>> {
+        let protocol = vk.get_protocol()?;
+        let s_g2 = vk.get_sg2()?;
+        let instances = pis.get_instance()?;
+        let proof = &proof.proof_bytes;
+
+        let protocol_bytes = to_vec(&protocol)?;
+        let s_g2_bytes = to_vec(&s_g2)?;
+        let instances_bytes = to_vec(&instances)?;
+        let proof_bytes = to_vec(&proof)?;
+
+        let mut input_data_vec: Vec<u8> = bytemuck::cast_slice(&protocol_bytes).to_vec();
+        input_data_vec.extend_from_slice(bytemuck::cast_slice(&s_g2_bytes));
+        input_data_vec.extend_from_slice(bytemuck::cast_slice(&instances_bytes));
+        input_data_vec.extend_from_slice(bytemuck::cast_slice(&proof_bytes));
+
+        Ok(input_data_vec)
+    }
+}
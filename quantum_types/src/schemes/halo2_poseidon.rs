@@ -0,0 +1,36 @@
+use anyhow::Result as AnyhowResult;
+use risc0_zkvm::serde::to_vec;
+
+use crate::{enums::proving_schemes::ProvingSchemes, types::halo2_poseidon::{Halo2PoseidonPis, Halo2PoseidonProof, Halo2PoseidonVkey}};
+
+use super::Scheme;
+
+pub struct Halo2PoseidonScheme;
+
+impl Scheme for Halo2PoseidonScheme {
+    type Vkey = Halo2PoseidonVkey;
+    type Proof = Halo2PoseidonProof;
+    type Pis = Halo2PoseidonPis;
+
+    const PROVING_SCHEME: ProvingSchemes = ProvingSchemes::Halo2Poseidon;
+    const AGGREGATION_PROTOCOL_ID: Option<u8> = Some(5);
+
+    fn form_bonsai_inputs(vk: &Halo2PoseidonVkey, proof: &Halo2PoseidonProof, pis: &Halo2PoseidonPis) -> AnyhowResult<Vec<u8>> {
+        let protocol = vk.get_protocol()?;
+        let s_g2 = vk.get_sg2()?;
+        let instances = pis.get_instance()?;
+        let proof = &proof.proof_bytes;
+
+        let protocol_bytes = to_vec(&protocol)?;
+        let s_g2_bytes = to_vec(&s_g2)?;
+        let instances_bytes = to_vec(&instances)?;
+        let proof_bytes = to_vec(&proof)?;
+
+        let mut input_data_vec: Vec<u8> = bytemuck::cast_slice(&protocol_bytes).to_vec();
+        input_data_vec.extend_from_slice(bytemuck::cast_slice(&s_g2_bytes));
+        input_data_vec.extend_from_slice(bytemuck::cast_slice(&instances_bytes));
+        input_data_vec.extend_from_slice(bytemuck::cast_slice(&proof_bytes));
+
+        Ok(input_data_vec)
+    }
+}
@@ -0,0 +1,122 @@
+use anyhow::Result as AnyhowResult;
+use risc0_zkvm::Receipt;
+
+use crate::{enums::{proving_schemes::ProvingSchemes, risc0_receipt_kind::Risc0ReceiptKind}, traits::{pis::Pis, proof::Proof, vkey::Vkey}};
+
+pub mod ark_groth16;
+pub mod gnark_groth16;
+pub mod gnark_groth16_bls12_381;
+pub mod gnark_plonk;
+pub mod gnark_plonk_bls12_381;
+pub mod halo2_ipa;
+pub mod halo2_plonk;
+pub mod halo2_poseidon;
+pub mod nitro_att;
+pub mod plonky2;
+pub mod plonky3;
+pub mod risc0;
+pub mod snarkjs_groth16;
+pub mod sp1;
+pub mod sp1_wrapped;
+
+pub use ark_groth16::ArkGroth16Scheme;
+pub use gnark_groth16::GnarkGroth16Scheme;
+pub use gnark_groth16_bls12_381::GnarkGroth16Bls12381Scheme;
+pub use gnark_plonk::GnarkPlonkScheme;
+pub use gnark_plonk_bls12_381::GnarkPlonkBls12381Scheme;
+pub use halo2_ipa::Halo2IpaScheme;
+pub use halo2_plonk::Halo2PlonkScheme;
+pub use halo2_poseidon::Halo2PoseidonScheme;
+pub use nitro_att::NitroAttScheme;
+pub use plonky2::Plonky2Scheme;
+pub use plonky3::Plonky3Scheme;
+pub use risc0::Risc0Scheme;
+pub use snarkjs_groth16::SnarkJSGroth16Scheme;
+pub use sp1::Sp1Scheme;
+pub use sp1_wrapped::{Sp1Groth16Scheme, Sp1PlonkScheme};
+
+/*
+    Everything the api server and the worker need to know about a proving scheme. Dispatch on a
+    `ProvingSchemes` value goes through `dispatch_scheme!`, so a new scheme is a module implementing
+    this trait plus one arm in the macro.
+ */
+pub trait Scheme {
+    type Vkey: Vkey;
+    type Proof: Proof;
+    type Pis: Pis;
+
+    const PROVING_SCHEME: ProvingSchemes;
+
+    // Scheme whose bonsai image reduces this scheme's proofs
+    const REDUCTION_PROVING_SCHEME: ProvingSchemes = Self::PROVING_SCHEME;
+
+    // Leaf type id of the scheme in the risc0 aggregation guest, `None` for schemes aggregated elsewhere
+    const AGGREGATION_PROTOCOL_ID: Option<u8>;
+
+    // Set for schemes whose public inputs are carried inside the proof, see `extract_pis`
+    const PIS_IN_PROOF: bool = false;
+
+    // Unset for schemes whose proofs are only verified locally by the worker's proof generation task, not reduced on bonsai
+    const REDUCED_ON_BONSAI: bool = true;
+
+    // Set for schemes whose proofs are leaves of the sp1 aggregation tree instead of the risc0 one
+    const SP1_AGGREGATED: bool = false;
+
+    // Unset for schemes not ready to take circuits, rejected at registration: their reduction image or aggregation
+    // protocol id is not deployed in the guests yet, or their verifier is not checked against the reference prover
+    const SUPPORTED: bool = true;
+
+    // Unset for schemes whose proof validation is too heavy to run for several proofs of a batch at once
+    const PARALLEL_VALIDATION: bool = true;
+
+    // Rejects vkeys of a variant no deployed reduction guest reads yet, `SUPPORTED` covers whole schemes
+    fn check_vkey_supported(_vk: &Self::Vkey) -> AnyhowResult<()> {
+        Ok(())
+    }
+
+    fn extract_pis(_proof: &Self::Proof, _vk_path: &str) -> AnyhowResult<Self::Pis> {
+        Err(anyhow::anyhow!("{} proofs do not carry their public inputs", Self::PROVING_SCHEME.to_string()))
+    }
+
+    // Input of the scheme's reduction circuit on bonsai
+    fn form_bonsai_inputs(vk: &Self::Vkey, proof: &Self::Proof, pis: &Self::Pis) -> AnyhowResult<Vec<u8>>;
+
+    // Receipts the reduction circuit verifies as assumptions
+    fn get_bonsai_assumptions(_proof: &Self::Proof) -> AnyhowResult<Vec<Receipt>> {
+        Ok(vec![])
+    }
+
+    // Risc0 receipt kind of the proof, checked against the kinds accepted by the circuit's bonsai image
+    fn get_receipt_kind(_proof: &Self::Proof) -> AnyhowResult<Option<Risc0ReceiptKind>> {
+        Ok(None)
+    }
+}
+
+/*
+    The scheme registry. Evaluates `$body` with the type alias `$S` bound to the `Scheme` impl of
+    `$proving_scheme`, e.g. `dispatch_scheme!(data.proof_type, S => submit_proof_exec::<S>(data).await)`.
+    The match is exhaustive, so a `ProvingSchemes` variant without a registered scheme does not compile.
+ */
+#[macro_export]
+macro_rules! dispatch_scheme {
+    ($proving_scheme:expr, $S:ident => $body:expr) => {
+        match $proving_scheme {
+            $crate::enums::proving_schemes::ProvingSchemes::GnarkGroth16 => { type $S = $crate::schemes::GnarkGroth16Scheme; $body }
+            $crate::enums::proving_schemes::ProvingSchemes::Groth16 => { type $S = $crate::schemes::SnarkJSGroth16Scheme; $body }
+            $crate::enums::proving_schemes::ProvingSchemes::Plonky2 => { type $S = $crate::schemes::Plonky2Scheme; $body }
+            $crate::enums::proving_schemes::ProvingSchemes::Halo2Plonk => { type $S = $crate::schemes::Halo2PlonkScheme; $body }
+            $crate::enums::proving_schemes::ProvingSchemes::GnarkPlonk => { type $S = $crate::schemes::GnarkPlonkScheme; $body }
+            $crate::enums::proving_schemes::ProvingSchemes::Halo2Poseidon => { type $S = $crate::schemes::Halo2PoseidonScheme; $body }
+            $crate::enums::proving_schemes::ProvingSchemes::Risc0 => { type $S = $crate::schemes::Risc0Scheme; $body }
+            $crate::enums::proving_schemes::ProvingSchemes::Sp1 => { type $S = $crate::schemes::Sp1Scheme; $body }
+            $crate::enums::proving_schemes::ProvingSchemes::NitroAtt => { type $S = $crate::schemes::NitroAttScheme; $body }
+            $crate::enums::proving_schemes::ProvingSchemes::GnarkGroth16Bls12381 => { type $S = $crate::schemes::GnarkGroth16Bls12381Scheme; $body }
+            $crate::enums::proving_schemes::ProvingSchemes::GnarkPlonkBls12381 => { type $S = $crate::schemes::GnarkPlonkBls12381Scheme; $body }
+            $crate::enums::proving_schemes::ProvingSchemes::ArkGroth16 => { type $S = $crate::schemes::ArkGroth16Scheme; $body }
+            $crate::enums::proving_schemes::ProvingSchemes::Halo2Ipa => { type $S = $crate::schemes::Halo2IpaScheme; $body }
+            $crate::enums::proving_schemes::ProvingSchemes::Plonky3 => { type $S = $crate::schemes::Plonky3Scheme; $body }
+            $crate::enums::proving_schemes::ProvingSchemes::Sp1Groth16 => { type $S = $crate::schemes::Sp1Groth16Scheme; $body }
+            $crate::enums::proving_schemes::ProvingSchemes::Sp1Plonk => { type $S = $crate::schemes::Sp1PlonkScheme; $body }
+        }
+    };
+}
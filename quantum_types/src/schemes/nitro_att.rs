@@ -0,0 +1,27 @@
+use anyhow::Result as AnyhowResult;
+
+use crate::{enums::proving_schemes::ProvingSchemes, traits::vkey::Vkey, types::nitro_att::{NitroAttPis, NitroAttProof, NitroAttVkey}};
+
+use super::Scheme;
+
+pub struct NitroAttScheme;
+
+impl Scheme for NitroAttScheme {
+    type Vkey = NitroAttVkey;
+    type Proof = NitroAttProof;
+    type Pis = NitroAttPis;
+
+    const PROVING_SCHEME: ProvingSchemes = ProvingSchemes::NitroAtt;
+    const AGGREGATION_PROTOCOL_ID: Option<u8> = Some(8);
+    const PIS_IN_PROOF: bool = true;
+
+    fn extract_pis(proof: &NitroAttProof, vk_path: &str) -> AnyhowResult<NitroAttPis> {
+        let vkey = NitroAttVkey::read_vk(vk_path)?;
+        let pis_bytes = proof.get_pis(&vkey)?;
+        Ok(NitroAttPis(vec![hex::encode(pis_bytes)]))
+    }
+
+    fn form_bonsai_inputs(_vk: &NitroAttVkey, proof: &NitroAttProof, _pis: &NitroAttPis) -> AnyhowResult<Vec<u8>> {
+        Ok(proof.att_doc_bytes.clone())
+    }
+}
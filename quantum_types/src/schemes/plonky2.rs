@@ -0,0 +1,70 @@
+use anyhow::{anyhow, Result as AnyhowResult};
+use quantum_utils::error_line;
+use risc0_zkvm::serde::to_vec;
+
+use crate::{enums::proving_schemes::ProvingSchemes, types::plonk2::{Plonky2Config, Plonky2Pis, Plonky2Proof, Plonky2Vkey}};
+
+use super::Scheme;
+
+pub struct Plonky2Scheme;
+
+impl Scheme for Plonky2Scheme {
+    type Vkey = Plonky2Vkey;
+    type Proof = Plonky2Proof;
+    type Pis = Plonky2Pis;
+
+    const PROVING_SCHEME: ProvingSchemes = ProvingSchemes::Plonky2;
+    const AGGREGATION_PROTOCOL_ID: Option<u8> = Some(2);
+    const PIS_IN_PROOF: bool = true;
+
+    fn extract_pis(proof: &Plonky2Proof, vk_path: &str) -> AnyhowResult<Plonky2Pis> {
+        Ok(Plonky2Pis(proof.get_pis_strings(vk_path)?))
+    }
+
+    // only the poseidon reduction guest is deployed, keccak circuits need their own guest reading the config
+    fn check_vkey_supported(vk: &Plonky2Vkey) -> AnyhowResult<()> {
+        match vk.config {
+            Plonky2Config::PoseidonGoldilocks => Ok(()),
+            Plonky2Config::KeccakGoldilocks => Err(anyhow!(error_line!("plonky2 KeccakGoldilocks circuits are not supported yet"))),
+        }
+    }
+
+    // the public inputs are part of the proof, so the pis are not passed separately
+    fn form_bonsai_inputs(vk: &Plonky2Vkey, proof: &Plonky2Proof, _pis: &Plonky2Pis) -> AnyhowResult<Vec<u8>> {
+        let common_bytes = to_vec(&vk.common_bytes)?;
+        let verifier_only_bytes = to_vec(&vk.verifier_only_bytes)?;
+        let proof_bytes = to_vec(&proof.proof_bytes)?;
+
+        let mut input_data_vec: Vec<u8> = bytemuck::cast_slice(&common_bytes).to_vec();
+        input_data_vec.extend_from_slice(bytemuck::cast_slice(&verifier_only_bytes));
+        input_data_vec.extend_from_slice(bytemuck::cast_slice(&proof_bytes));
+        // poseidon inputs stay byte identical for the deployed guest, the keccak guest reads the config after the proof
+        if vk.config != Plonky2Config::PoseidonGoldilocks {
+            let config = to_vec(&vk.config.as_u8())?;
+            input_data_vec.extend_from_slice(bytemuck::cast_slice(&config));
+        }
+
+        Ok(input_data_vec)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_poseidon_bonsai_inputs_keep_their_layout() {
+        let proof = Plonky2Proof { proof_bytes: vec![3, 4] };
+        let poseidon_vkey = Plonky2Vkey { common_bytes: vec![1], verifier_only_bytes: vec![2], config: Plonky2Config::PoseidonGoldilocks };
+
+        let mut expected: Vec<u8> = bytemuck::cast_slice(&to_vec(&poseidon_vkey.common_bytes).unwrap()).to_vec();
+        expected.extend_from_slice(bytemuck::cast_slice(&to_vec(&poseidon_vkey.verifier_only_bytes).unwrap()));
+        expected.extend_from_slice(bytemuck::cast_slice(&to_vec(&proof.proof_bytes).unwrap()));
+        assert_eq!(Plonky2Scheme::form_bonsai_inputs(&poseidon_vkey, &proof, &Plonky2Pis(vec![])).unwrap(), expected);
+        Plonky2Scheme::check_vkey_supported(&poseidon_vkey).unwrap();
+
+        let keccak_vkey = Plonky2Vkey { config: Plonky2Config::KeccakGoldilocks, ..poseidon_vkey };
+        assert!(Plonky2Scheme::form_bonsai_inputs(&keccak_vkey, &proof, &Plonky2Pis(vec![])).unwrap().starts_with(&expected));
+        assert!(Plonky2Scheme::check_vkey_supported(&keccak_vkey).is_err());
+    }
+}
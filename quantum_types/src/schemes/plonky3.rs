@@ -0,0 +1,37 @@
+use anyhow::Result as AnyhowResult;
+use risc0_zkvm::serde::to_vec;
+
+use crate::{enums::proving_schemes::ProvingSchemes, traits::vkey::Vkey, types::plonky3::{Plonky3Pis, Plonky3Proof, Plonky3Vkey}};
+
+use super::Scheme;
+
+pub struct Plonky3Scheme;
+
+impl Scheme for Plonky3Scheme {
+    type Vkey = Plonky3Vkey;
+    type Proof = Plonky3Proof;
+    type Pis = Plonky3Pis;
+
+    const PROVING_SCHEME: ProvingSchemes = ProvingSchemes::Plonky3;
+    const AGGREGATION_PROTOCOL_ID: Option<u8> = Some(12);
+    // no reduction guest reads the borsh air input yet and the aggregation guest has no protocol id 12
+    const SUPPORTED: bool = false;
+    const PIS_IN_PROOF: bool = true;
+
+    fn extract_pis(proof: &Plonky3Proof, vk_path: &str) -> AnyhowResult<Plonky3Pis> {
+        Ok(Plonky3Pis(proof.get_pis_strings(vk_path)?))
+    }
+
+    // the public values are part of the proof, so the pis are not passed separately
+    fn form_bonsai_inputs(vk: &Plonky3Vkey, proof: &Plonky3Proof, _pis: &Plonky3Pis) -> AnyhowResult<Vec<u8>> {
+        let vkey_bytes = to_vec(&vk.serialize_vkey()?)?;
+        let public_values = to_vec(&proof.public_values)?;
+        let proof_bytes = to_vec(&proof.proof_bytes)?;
+
+        let mut input_data_vec: Vec<u8> = bytemuck::cast_slice(&vkey_bytes).to_vec();
+        input_data_vec.extend_from_slice(bytemuck::cast_slice(&public_values));
+        input_data_vec.extend_from_slice(bytemuck::cast_slice(&proof_bytes));
+
+        Ok(input_data_vec)
+    }
+}
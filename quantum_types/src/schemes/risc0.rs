@@ -0,0 +1,60 @@
+use anyhow::Result as AnyhowResult;
+use risc0_zkvm::{serde::to_vec, InnerReceipt, Receipt};
+
+use crate::{enums::{proving_schemes::ProvingSchemes, risc0_receipt_kind::Risc0ReceiptKind}, types::riscs0::{get_receipt_kind, Risc0Pis, Risc0Proof, Risc0Vkey}};
+
+use super::Scheme;
+
+pub struct Risc0Scheme;
+
+impl Scheme for Risc0Scheme {
+    type Vkey = Risc0Vkey;
+    type Proof = Risc0Proof;
+    type Pis = Risc0Pis;
+
+    const PROVING_SCHEME: ProvingSchemes = ProvingSchemes::Risc0;
+    const AGGREGATION_PROTOCOL_ID: Option<u8> = Some(6);
+    const PIS_IN_PROOF: bool = true;
+
+    fn extract_pis(proof: &Risc0Proof, _vk_path: &str) -> AnyhowResult<Risc0Pis> {
+        let pis_bytes = proof.get_receipt()?.journal.bytes;
+        Ok(Risc0Pis(vec![hex::encode(pis_bytes)]))
+    }
+
+    /*
+        Composite and succinct receipts keep the image id and journal layout of the existing reduction guest.
+        Groth16 receipts need the guest that reads their seal after the journal, a bonsai image accepting them
+        accepts no other kind, see `parse_receipt_kinds`.
+     */
+    fn form_bonsai_inputs(vk: &Risc0Vkey, proof: &Risc0Proof, _pis: &Risc0Pis) -> AnyhowResult<Vec<u8>> {
+        let receipt = proof.get_receipt()?;
+        // TODO: to check whether this to_vec is needed, vkey is already u32 type
+        let image_id = to_vec(&vk.vkey_bytes)?;
+        let pis_bytes = to_vec(&receipt.journal.bytes)?;
+
+        let mut input_data_vec: Vec<u8> = bytemuck::cast_slice(&image_id).to_vec();
+        input_data_vec.extend_from_slice(bytemuck::cast_slice(&pis_bytes));
+        if let InnerReceipt::Groth16(groth16_receipt) = &receipt.inner {
+            let seal = to_vec(&groth16_receipt.seal)?;
+            input_data_vec.extend_from_slice(bytemuck::cast_slice(&seal));
+        }
+
+        Ok(input_data_vec)
+    }
+
+    /*
+        The reduction circuit verifies composite and succinct receipts through `env::verify`, the worker compresses
+        composite ones before the upload. Groth16 receipts can not be resolved as assumptions, their seal is an input.
+     */
+    fn get_bonsai_assumptions(proof: &Risc0Proof) -> AnyhowResult<Vec<Receipt>> {
+        let receipt = proof.get_receipt()?;
+        match get_receipt_kind(&receipt)? {
+            Risc0ReceiptKind::Groth16 => Ok(vec![]),
+            _ => Ok(vec![receipt]),
+        }
+    }
+
+    fn get_receipt_kind(proof: &Risc0Proof) -> AnyhowResult<Option<Risc0ReceiptKind>> {
+        Ok(Some(get_receipt_kind(&proof.get_receipt()?)?))
+    }
+}
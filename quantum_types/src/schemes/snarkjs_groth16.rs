@@ -0,0 +1,52 @@
+use anyhow::Result as AnyhowResult;
+use ark_bn254::{Bn254, Fr as ArkFr};
+use ark_groth16::{verifier, Proof as ArkProof, VerifyingKey};
+use ark_serialize::CanonicalSerialize;
+use risc0_zkvm::serde::to_vec;
+
+use crate::{enums::proving_schemes::ProvingSchemes, types::snarkjs_groth16::{SnarkJSGroth16Pis, SnarkJSGroth16Proof, SnarkJSGroth16Vkey}};
+
+use super::Scheme;
+
+pub struct SnarkJSGroth16Scheme;
+
+impl Scheme for SnarkJSGroth16Scheme {
+    type Vkey = SnarkJSGroth16Vkey;
+    type Proof = SnarkJSGroth16Proof;
+    type Pis = SnarkJSGroth16Pis;
+
+    const PROVING_SCHEME: ProvingSchemes = ProvingSchemes::Groth16;
+    const AGGREGATION_PROTOCOL_ID: Option<u8> = Some(0);
+
+    fn form_bonsai_inputs(vk: &SnarkJSGroth16Vkey, proof: &SnarkJSGroth16Proof, pis: &SnarkJSGroth16Pis) -> AnyhowResult<Vec<u8>> {
+        let ark_vk = vk.get_ark_vk_for_snarkjs_groth16()?;
+        let ark_proof = proof.get_ark_proof_for_snarkjs_groth16_proof()?;
+        let ark_public_inputs = pis.get_ark_pis_for_snarkjs_groth16_pis()?;
+        form_ark_groth16_bonsai_inputs(&ark_vk, &ark_proof, &ark_public_inputs)
+    }
+}
+
+// Input of the bn254 groth16 reduction circuit, shared by every scheme reduced by it
+pub(crate) fn form_ark_groth16_bonsai_inputs(ark_vk: &VerifyingKey<Bn254>, ark_proof: &ArkProof<Bn254>, ark_public_inputs: &Vec<ArkFr>) -> AnyhowResult<Vec<u8>> {
+    let pvk = verifier::prepare_verifying_key(ark_vk);
+
+    let mut pvk_bytes = vec![];
+    pvk.serialize_uncompressed(&mut pvk_bytes)?;
+
+    let mut proof_bytes = vec![];
+    ark_proof.serialize_uncompressed(&mut proof_bytes)?;
+
+    let mut public_inputs_bytes = vec![];
+    ark_public_inputs.serialize_uncompressed(&mut public_inputs_bytes)?;
+
+    let input_data = to_vec(&pvk_bytes)?;
+    let mut input_data_vec: Vec<u8> = bytemuck::cast_slice(&input_data).to_vec();
+
+    let input_data = to_vec(&proof_bytes)?;
+    input_data_vec.extend_from_slice(bytemuck::cast_slice(&input_data));
+
+    let input_data = to_vec(&public_inputs_bytes)?;
+    input_data_vec.extend_from_slice(bytemuck::cast_slice(&input_data));
+
+    Ok(input_data_vec)
+}
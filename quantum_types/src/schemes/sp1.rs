@@ -0,0 +1,32 @@
+use anyhow::{anyhow, Result as AnyhowResult};
+use quantum_utils::error_line;
+
+use crate::{enums::proving_schemes::ProvingSchemes, types::sp1::{Sp1Pis, Sp1Proof, Sp1Vkey}};
+
+use super::Scheme;
+
+// Sp1 proofs skip the bonsai reduction, the worker verifies the compressed proof locally and they are aggregated by the sp1 aggregation circuit
+pub struct Sp1Scheme;
+
+impl Scheme for Sp1Scheme {
+    type Vkey = Sp1Vkey;
+    type Proof = Sp1Proof;
+    type Pis = Sp1Pis;
+
+    const PROVING_SCHEME: ProvingSchemes = ProvingSchemes::Sp1;
+    const AGGREGATION_PROTOCOL_ID: Option<u8> = None;
+    const PIS_IN_PROOF: bool = true;
+    const REDUCED_ON_BONSAI: bool = false;
+    const SP1_AGGREGATED: bool = true;
+    // verification spins up a full `ProverClient`
+    const PARALLEL_VALIDATION: bool = false;
+
+    fn extract_pis(proof: &Sp1Proof, _vk_path: &str) -> AnyhowResult<Sp1Pis> {
+        let pis_bytes = proof.get_proof_with_public_inputs()?.public_values.to_vec();
+        Ok(Sp1Pis(vec![hex::encode(pis_bytes)]))
+    }
+
+    fn form_bonsai_inputs(_vk: &Sp1Vkey, _proof: &Sp1Proof, _pis: &Sp1Pis) -> AnyhowResult<Vec<u8>> {
+        Err(anyhow!(error_line!("sp1 proofs are not reduced on bonsai")))
+    }
+}
@@ -0,0 +1,62 @@
+use std::marker::PhantomData;
+
+use anyhow::Result as AnyhowResult;
+use risc0_zkvm::serde::to_vec;
+
+use crate::{enums::proving_schemes::ProvingSchemes, traits::vkey::Vkey, types::{sp1::Sp1Pis, sp1_wrapped::{Sp1Groth16Verifier, Sp1PlonkVerifier, Sp1WrapVerifier, Sp1WrappedProof, Sp1WrappedVkey}}};
+
+use super::Scheme;
+
+pub trait Sp1WrappedKind: Sp1WrapVerifier {
+    const PROVING_SCHEME: ProvingSchemes;
+    const AGGREGATION_PROTOCOL_ID: u8;
+}
+
+impl Sp1WrappedKind for Sp1Groth16Verifier {
+    const PROVING_SCHEME: ProvingSchemes = ProvingSchemes::Sp1Groth16;
+    const AGGREGATION_PROTOCOL_ID: u8 = 13;
+}
+
+impl Sp1WrappedKind for Sp1PlonkVerifier {
+    const PROVING_SCHEME: ProvingSchemes = ProvingSchemes::Sp1Plonk;
+    const AGGREGATION_PROTOCOL_ID: u8 = 14;
+}
+
+// Groth16/Plonk wrapped sp1 proofs, reduced on bonsai against the sp1 wrapper circuit vkey
+pub struct Sp1WrappedScheme<W>(PhantomData<W>);
+
+pub type Sp1Groth16Scheme = Sp1WrappedScheme<Sp1Groth16Verifier>;
+pub type Sp1PlonkScheme = Sp1WrappedScheme<Sp1PlonkVerifier>;
+
+impl<W: Sp1WrappedKind> Scheme for Sp1WrappedScheme<W> {
+    type Vkey = Sp1WrappedVkey;
+    type Proof = Sp1WrappedProof<W>;
+    type Pis = Sp1Pis;
+
+    const PROVING_SCHEME: ProvingSchemes = W::PROVING_SCHEME;
+    const AGGREGATION_PROTOCOL_ID: Option<u8> = Some(W::AGGREGATION_PROTOCOL_ID);
+    const PIS_IN_PROOF: bool = true;
+    // no reduction guest verifies the sp1 wrapper circuits yet and the aggregation guest has no protocol ids 13/14
+    const SUPPORTED: bool = false;
+
+    fn extract_pis(proof: &Sp1WrappedProof<W>, _vk_path: &str) -> AnyhowResult<Sp1Pis> {
+        let pis_bytes = proof.get_proof_with_public_inputs()?.public_values.to_vec();
+        Ok(Sp1Pis(vec![hex::encode(pis_bytes)]))
+    }
+
+    // the guest checks the proof's public inputs against the program vkey hash and the sha256 of the public values
+    fn form_bonsai_inputs(vk: &Sp1WrappedVkey, proof: &Sp1WrappedProof<W>, _pis: &Sp1Pis) -> AnyhowResult<Vec<u8>> {
+        let proof_with_public_values = proof.get_proof_with_public_inputs()?;
+        let circuit_vk_bytes = to_vec(&W::circuit_vk_bytes())?;
+        let proof_bytes = to_vec(&proof_with_public_values.bytes())?;
+        let vkey_hash = to_vec(&vk.keccak_hash()?)?;
+        let public_values = to_vec(&proof_with_public_values.public_values.to_vec())?;
+
+        let mut input_data_vec: Vec<u8> = bytemuck::cast_slice(&circuit_vk_bytes).to_vec();
+        input_data_vec.extend_from_slice(bytemuck::cast_slice(&proof_bytes));
+        input_data_vec.extend_from_slice(bytemuck::cast_slice(&vkey_hash));
+        input_data_vec.extend_from_slice(bytemuck::cast_slice(&public_values));
+
+        Ok(input_data_vec)
+    }
+}
@@ -12,4 +12,10 @@ pub trait Vkey: Sized {
     fn validate(&self) -> AnyhowResult<()>;
     fn keccak_hash(&self) -> AnyhowResult<[u8;32]>;
     fn compute_circuit_hash(&self, circuit_verifying_id: [u32;8]) -> AnyhowResult<[u8;32]>;
+
+    // Hash of what the vkey checks off-chain on top of its leaf vkey hash, e.g. the nitro attestation policy.
+    // It is not part of the circuit hash, vkeys registered under one circuit hash must agree on it
+    fn policy_hash(&self) -> AnyhowResult<Option<[u8;32]>> {
+        Ok(None)
+    }
 }
\ No newline at end of file
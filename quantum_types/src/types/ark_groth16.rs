@@ -0,0 +1,222 @@
+use agg_core::inputs::compute_combined_vkey_hash;
+use anyhow::{anyhow, Result as AnyhowResult};
+use ark_bn254::{Bn254, Fr as ArkFr};
+use ark_ff::PrimeField;
+use ark_groth16::{verifier, Groth16, Proof as ArkProof, VerifyingKey};
+use ark_serialize::CanonicalDeserialize;
+use borsh::{BorshDeserialize, BorshSerialize};
+use groth16_core::utils::groth16_vkey_hash;
+use num_bigint::BigUint;
+use quantum_utils::{
+    error_line,
+    file::{read_bytes_from_file, write_bytes_to_file},
+};
+use serde::{Deserialize, Serialize};
+use utils::{hash::KeccakHasher, public_inputs_hash};
+
+use crate::traits::{pis::Pis, proof::Proof, vkey::Vkey};
+
+// Reads a value in arkworks compressed form, trailing bytes are rejected
+fn deserialize_ark_compressed<T: CanonicalDeserialize>(bytes: &[u8], name: &str) -> AnyhowResult<T> {
+    let mut reader = bytes;
+    let value = T::deserialize_compressed(&mut reader)
+        .map_err(|e| anyhow!(error_line!(format!("failed to deserialize ark-groth16 {}: {}", name, e))))?;
+    if !reader.is_empty() {
+        return Err(anyhow!(error_line!(format!("ark-groth16 {} has {} trailing bytes", name, reader.len()))));
+    }
+    Ok(value)
+}
+
+// `VerifyingKey<Bn254>` in arkworks `CanonicalSerialize` compressed form
+#[derive(Clone, BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, PartialEq)]
+pub struct ArkGroth16Vkey {
+    pub vkey_bytes: Vec<u8>
+}
+
+impl ArkGroth16Vkey {
+    pub fn get_ark_vk(&self) -> AnyhowResult<VerifyingKey<Bn254>> {
+        let ark_vk: VerifyingKey<Bn254> = deserialize_ark_compressed(&self.vkey_bytes, "vkey")?;
+        if ark_vk.gamma_abc_g1.is_empty() {
+            return Err(anyhow!(error_line!("ark-groth16 vkey has no gamma_abc_g1 points")));
+        }
+        Ok(ark_vk)
+    }
+}
+
+impl Vkey for ArkGroth16Vkey {
+    fn serialize_vkey(&self) -> AnyhowResult<Vec<u8>> {
+        let mut buffer: Vec<u8> = Vec::new();
+        BorshSerialize::serialize(&self, &mut buffer).map_err(|err| anyhow!(error_line!(err)))?;
+        Ok(buffer)
+    }
+
+    fn deserialize_vkey(bytes: &mut &[u8]) -> AnyhowResult<Self> {
+        let key: ArkGroth16Vkey =
+            BorshDeserialize::deserialize(bytes).map_err(|err| anyhow!(error_line!(err)))?;
+        Ok(key)
+    }
+
+    fn dump_vk(&self, path: &str) -> AnyhowResult<()> {
+        let vkey_bytes = self.serialize_vkey()?;
+        write_bytes_to_file(&vkey_bytes, path)?;
+        Ok(())
+    }
+
+    fn read_vk(full_path: &str) -> AnyhowResult<Self> {
+        let vkey_bytes = read_bytes_from_file(full_path)?;
+        let ark_vkey = ArkGroth16Vkey::deserialize_vkey(&mut vkey_bytes.as_slice())?;
+        Ok(ark_vkey)
+    }
+
+    fn validate(&self) -> AnyhowResult<()> {
+        self.get_ark_vk()?;
+        Ok(())
+    }
+
+    // same as the snarkjs groth16 vkey hash, both are reduced by the same circuit
+    fn keccak_hash(&self) -> AnyhowResult<[u8; 32]> {
+        let pvk = verifier::prepare_verifying_key(&self.get_ark_vk()?);
+        let pvk_hash = groth16_vkey_hash::<KeccakHasher>(&pvk);
+        Ok(pvk_hash)
+    }
+
+    fn compute_circuit_hash(&self, circuit_verifying_id: [u32; 8]) -> AnyhowResult<[u8; 32]> {
+        let pvk_hash = self.keccak_hash()?;
+        let circuit_hash = compute_combined_vkey_hash::<KeccakHasher>(&pvk_hash, &circuit_verifying_id)?;
+        Ok(circuit_hash)
+    }
+}
+
+// `Proof<Bn254>` in arkworks `CanonicalSerialize` compressed form
+#[derive(Clone, BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, PartialEq)]
+pub struct ArkGroth16Proof {
+    pub proof_bytes: Vec<u8>,
+}
+
+impl ArkGroth16Proof {
+    pub fn get_ark_proof(&self) -> AnyhowResult<ArkProof<Bn254>> {
+        deserialize_ark_compressed(&self.proof_bytes, "proof")
+    }
+}
+
+impl Proof for ArkGroth16Proof {
+    fn serialize_proof(&self) -> AnyhowResult<Vec<u8>> {
+        let mut buffer: Vec<u8> = Vec::new();
+        BorshSerialize::serialize(&self, &mut buffer)?;
+        Ok(buffer)
+    }
+
+    fn deserialize_proof(bytes: &mut &[u8]) -> AnyhowResult<Self> {
+        let key: ArkGroth16Proof =
+            BorshDeserialize::deserialize(bytes).map_err(|err| anyhow!(error_line!(err)))?;
+        Ok(key)
+    }
+
+    fn dump_proof(&self, path: &str) -> AnyhowResult<()> {
+        let proof_bytes = self.serialize_proof()?;
+        write_bytes_to_file(&proof_bytes, path)?;
+        Ok(())
+    }
+
+    fn read_proof(full_path: &str) -> AnyhowResult<Self> {
+        let proof_bytes = read_bytes_from_file(full_path)?;
+        let ark_proof = ArkGroth16Proof::deserialize_proof(&mut proof_bytes.as_slice())?;
+        Ok(ark_proof)
+    }
+
+    fn validate_proof(&self, vkey_path: &str, mut pis_bytes: &[u8]) -> AnyhowResult<()> {
+        let vkey = ArkGroth16Vkey::read_vk(vkey_path)?;
+        let pis = ArkGroth16Pis::deserialize_pis(&mut pis_bytes)?;
+        let pvk = verifier::prepare_verifying_key(&vkey.get_ark_vk()?);
+
+        let res = Groth16::<Bn254>::verify_proof(&pvk, &self.get_ark_proof()?, &pis.get_ark_pis()?)
+            .map_err(|e| anyhow!(error_line!(format!("error while validating proof: {}", e))))?;
+        if !res {
+            return Err(anyhow!(error_line!("ark-groth16 proof validation failed")))
+        }
+        Ok(())
+    }
+
+    fn get_proof_bytes(&self) -> AnyhowResult<Vec<u8>> {
+        Ok(self.proof_bytes.clone())
+    }
+}
+
+// `Vec<Fr>` in arkworks `CanonicalSerialize` compressed form
+#[derive(Clone, BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, PartialEq)]
+pub struct ArkGroth16Pis {
+    pub pis_bytes: Vec<u8>,
+}
+
+impl Pis for ArkGroth16Pis {
+    fn serialize_pis(&self) -> AnyhowResult<Vec<u8>> {
+        let mut buffer: Vec<u8> = Vec::new();
+        BorshSerialize::serialize(&self, &mut buffer)?;
+        Ok(buffer)
+    }
+
+    fn deserialize_pis(bytes: &mut &[u8]) -> AnyhowResult<Self> {
+        let key: ArkGroth16Pis =
+            BorshDeserialize::deserialize(bytes).map_err(|err| anyhow!(error_line!(err)))?;
+        Ok(key)
+    }
+
+    fn dump_pis(&self, path: &str) -> AnyhowResult<()> {
+        let pis_bytes = self.serialize_pis()?;
+        write_bytes_to_file(&pis_bytes, path)?;
+        Ok(())
+    }
+
+    fn read_pis(full_path: &str) -> AnyhowResult<Self> {
+        let pis_bytes = read_bytes_from_file(full_path)?;
+        let ark_pis = ArkGroth16Pis::deserialize_pis(&mut pis_bytes.as_slice())?;
+        Ok(ark_pis)
+    }
+
+    // same as the snarkjs groth16 pis hash, both are reduced by the same circuit
+    fn keccak_hash(&self) -> AnyhowResult<[u8; 32]> {
+        let ark_pis = self.get_ark_pis()?;
+        let hash = public_inputs_hash::<KeccakHasher>(&ark_pis);
+        Ok(hash)
+    }
+
+    fn get_data(&self) -> AnyhowResult<Vec<String>> {
+        Ok(self.get_ark_pis()?.iter().map(|p| BigUint::from(p.into_bigint()).to_string()).collect())
+    }
+}
+
+impl ArkGroth16Pis {
+    pub fn get_ark_pis(&self) -> AnyhowResult<Vec<ArkFr>> {
+        deserialize_ark_compressed(&self.pis_bytes, "pis")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bn254::G1Affine;
+    use ark_ec::AffineRepr;
+    use ark_serialize::CanonicalSerialize;
+
+    fn get_vkey(vk: &VerifyingKey<Bn254>) -> ArkGroth16Vkey {
+        let mut vkey_bytes = vec![];
+        vk.serialize_compressed(&mut vkey_bytes).unwrap();
+        ArkGroth16Vkey { vkey_bytes }
+    }
+
+    #[test]
+    fn test_validate_ark_groth16_vkey() {
+        let mut vk = VerifyingKey::<Bn254>::default();
+        assert!(get_vkey(&vk).validate().is_err());
+
+        vk.gamma_abc_g1 = vec![G1Affine::generator(); 2];
+        let vkey = get_vkey(&vk);
+        vkey.validate().unwrap();
+
+        let truncated_vkey = ArkGroth16Vkey { vkey_bytes: vkey.vkey_bytes[..vkey.vkey_bytes.len() - 1].to_vec() };
+        assert!(truncated_vkey.validate().is_err());
+        let padded_vkey = ArkGroth16Vkey { vkey_bytes: [vkey.vkey_bytes.clone(), vec![0]].concat() };
+        assert!(padded_vkey.validate().is_err());
+        assert!(ArkGroth16Vkey { vkey_bytes: vec![1, 2, 3] }.validate().is_err());
+    }
+}
@@ -3,6 +3,9 @@ use std::fs;
 use serde::{Deserialize, Serialize};
 use tracing::info;
 use dotenv::dotenv;
+use quantum_utils::retry::RetryPolicy;
+
+use crate::enums::prover_backend::ProverBackendType;
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct ConfigData {
@@ -26,7 +29,146 @@ pub struct ConfigData {
     pub sp1_snark_reduction_data_path: String,
     pub parallel_bonsai_session_limit: u64,
     pub pr_batch_max_cycle_count: u64,
-    pub sp1_folder_path: String
+    pub sp1_folder_path: String,
+    #[serde(default)]
+    pub prover_backend: ProverBackendType,
+    #[serde(default = "default_task_lease_secs")]
+    pub task_lease_secs: u64,
+    #[serde(default)]
+    pub retry: RetryConfig,
+    #[serde(default = "default_max_proofs_per_batch_request")]
+    pub max_proofs_per_batch_request: u64,
+    #[serde(default = "default_webhook_poll_secs")]
+    pub webhook_poll_secs: u64,
+    #[serde(default = "default_event_stream_poll_millis")]
+    pub event_stream_poll_millis: u64,
+    #[serde(default = "default_event_stream_resume_window")]
+    pub event_stream_resume_window: u64,
+    #[serde(default = "default_sp1_max_batch_size")]
+    pub sp1_max_batch_size: u64,
+    #[serde(default = "default_sp1_aggregation_timeout_secs")]
+    pub sp1_aggregation_timeout_secs: u64,
+    #[serde(default = "default_sp1_max_aggregation_attempts")]
+    pub sp1_max_aggregation_attempts: u64,
+    #[serde(default = "default_aggregation_isolation_attempts")]
+    pub aggregation_isolation_attempts: u64,
+    #[serde(default = "default_max_superproofs_in_flight")]
+    pub max_superproofs_in_flight: u64,
+    #[serde(default = "default_max_batch_cycle_count")]
+    pub max_batch_cycle_count: u64,
+}
+
+fn default_task_lease_secs() -> u64 {
+    300
+}
+
+fn default_max_proofs_per_batch_request() -> u64 {
+    500
+}
+
+fn default_webhook_poll_secs() -> u64 {
+    5
+}
+
+fn default_event_stream_poll_millis() -> u64 {
+    1000
+}
+
+fn default_event_stream_resume_window() -> u64 {
+    200
+}
+
+fn default_sp1_max_batch_size() -> u64 {
+    5
+}
+
+fn default_sp1_aggregation_timeout_secs() -> u64 {
+    420
+}
+
+fn default_sp1_max_aggregation_attempts() -> u64 {
+    3
+}
+
+fn default_aggregation_isolation_attempts() -> u64 {
+    2
+}
+
+fn default_max_superproofs_in_flight() -> u64 {
+    1
+}
+
+fn default_max_batch_cycle_count() -> u64 {
+    3_400_000_000
+}
+
+// Retry policies of the external calls, defaults keep the previous fixed retry behaviour
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct RetryConfig {
+    #[serde(default = "default_bonsai_retry_policy")]
+    pub bonsai: RetryPolicy,
+    #[serde(default = "default_contract_retry_policy")]
+    pub contract: RetryPolicy,
+    #[serde(default = "default_price_feed_retry_policy")]
+    pub price_feed: RetryPolicy,
+    #[serde(default = "default_webhook_retry_policy")]
+    pub webhook: RetryPolicy,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        RetryConfig {
+            bonsai: default_bonsai_retry_policy(),
+            contract: default_contract_retry_policy(),
+            price_feed: default_price_feed_retry_policy(),
+            webhook: default_webhook_retry_policy(),
+        }
+    }
+}
+
+fn default_bonsai_retry_policy() -> RetryPolicy {
+    RetryPolicy {
+        max_attempts: 3,
+        initial_delay_ms: 120_000,
+        max_delay_ms: 600_000,
+        multiplier: 2.0,
+        jitter: 0.1,
+        max_elapsed_secs: None,
+    }
+}
+
+fn default_contract_retry_policy() -> RetryPolicy {
+    RetryPolicy {
+        max_attempts: 4,
+        initial_delay_ms: 10_000,
+        max_delay_ms: 60_000,
+        multiplier: 2.0,
+        jitter: 0.1,
+        max_elapsed_secs: None,
+    }
+}
+
+fn default_price_feed_retry_policy() -> RetryPolicy {
+    RetryPolicy {
+        max_attempts: 5,
+        initial_delay_ms: 1_000,
+        max_delay_ms: 10_000,
+        multiplier: 2.0,
+        jitter: 0.2,
+        max_elapsed_secs: Some(60),
+    }
+}
+
+// webhook deliveries are retried across dispatcher polls, max_attempts is the total number of deliveries tried
+fn default_webhook_retry_policy() -> RetryPolicy {
+    RetryPolicy {
+        max_attempts: 10,
+        initial_delay_ms: 5_000,
+        max_delay_ms: 3_600_000,
+        multiplier: 2.0,
+        jitter: 0.2,
+        max_elapsed_secs: None,
+    }
 }
 
 impl ConfigData {
@@ -1,5 +1,5 @@
 use serde::{Deserialize, Serialize};
-use crate::enums::proving_schemes::ProvingSchemes;
+use crate::enums::{proving_schemes::ProvingSchemes, risc0_receipt_kind::Risc0ReceiptKind};
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct BonsaiImage {
@@ -7,5 +7,6 @@ pub struct BonsaiImage {
     pub elf_file_path: String,
     pub circuit_verifying_id: [u32;8],
     pub proving_scheme: Option<ProvingSchemes>,
-    pub is_aggregation_image_id: u8
+    pub is_aggregation_image_id: u8,
+    pub accepted_receipt_kinds: Vec<Risc0ReceiptKind>,
 }
\ No newline at end of file
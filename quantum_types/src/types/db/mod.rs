@@ -4,5 +4,7 @@ pub mod reduction_circuit;
 pub mod superproof;
 pub mod task;
 pub mod protocol;
+pub mod webhook_event;
+pub mod status_event;
 
 pub mod bonsai_image;
\ No newline at end of file
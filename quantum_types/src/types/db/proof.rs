@@ -15,5 +15,7 @@ pub struct Proof {
     pub reduction_time: Option<u64>,
     pub proof_status: ProofStatus,
     pub user_circuit_hash: String,
-    pub cycle_used: Option<u64>
+    pub cycle_used: Option<u64>,
+    pub sp1_aggregation_attempts: Option<u64>,
+    pub aggregation_attempts: Option<u64>,
 }
\ No newline at end of file
@@ -5,4 +5,10 @@ pub struct Protocol {
     pub protocol_name:  String,
     pub auth_token: String,
     pub is_proof_repeat_allowed: u8,
+    pub callback_url: Option<String>,
+    pub callback_secret: Option<String>,
+    // higher tiers are always picked first, NULL is tier 0
+    pub priority_tier: Option<u8>,
+    // share of the queue taken by the protocol against others of the same tier
+    pub fair_share_weight: Option<u32>,
 }
\ No newline at end of file
@@ -0,0 +1,12 @@
+use serde::{Deserialize, Serialize};
+
+// A proof or superproof status transition, its id is the event id of the api event streams
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct StatusEvent {
+    pub id: u64,
+    pub protocol_name: String,
+    pub event_type: String,
+    pub proof_hash: Option<String>,
+    pub superproof_id: Option<u64>,
+    pub payload: String,
+}
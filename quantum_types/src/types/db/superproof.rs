@@ -2,7 +2,7 @@ use chrono::NaiveDateTime;
 use sqlx::types::Decimal;
 use serde::{Deserialize, Serialize};
 
-use crate::enums::superproof_status::SuperproofStatus;
+use crate::enums::{batch_close_reason::BatchCloseReason, superproof_status::SuperproofStatus};
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct Superproof {
@@ -24,4 +24,7 @@ pub struct Superproof {
     pub imt_pis_path: Option<String>,
     pub r0_root: Option<String>,
     pub sp1_root: Option<String>,
+    pub session_id: Option<String>,
+    pub snark_session_id: Option<String>,
+    pub batch_close_reason: Option<BatchCloseReason>,
 }
\ No newline at end of file
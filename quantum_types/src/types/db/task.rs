@@ -1,3 +1,4 @@
+use chrono::NaiveDateTime;
 use serde::{Deserialize, Serialize};
 
 use crate::enums::{task_status::TaskStatus, task_type::TaskType};
@@ -9,5 +10,7 @@ pub struct Task {
     pub task_type: TaskType,
     pub proof_hash: Option<String>,
     pub proof_id: Option<u64>,
-    pub task_status: TaskStatus
+    pub task_status: TaskStatus,
+    pub leased_by: Option<String>,
+    pub lease_expires_at: Option<NaiveDateTime>,
 }
\ No newline at end of file
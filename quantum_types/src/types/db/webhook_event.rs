@@ -0,0 +1,16 @@
+use serde::{Deserialize, Serialize};
+
+use crate::enums::webhook_delivery_status::WebhookDeliveryStatus;
+
+// An outbox entry, joined with the callback details of its protocol
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct WebhookEvent {
+    pub id: Option<u64>,
+    pub protocol_name: String,
+    pub event_type: String,
+    pub payload: String,
+    pub status: WebhookDeliveryStatus,
+    pub attempts: u64,
+    pub callback_url: Option<String>,
+    pub callback_secret: Option<String>,
+}
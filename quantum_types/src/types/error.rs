@@ -0,0 +1,34 @@
+use anyhow::Result as AnyhowResult;
+
+use crate::enums::error_code::ErrorCode;
+
+/*
+    An error tagged with a client facing error code. It is attached as context, so the original error
+    stays in the chain and `anyhow::Error::downcast_ref::<CodedError>()` finds the outermost tag.
+ */
+#[derive(Debug, Clone)]
+pub struct CodedError {
+    pub code: ErrorCode,
+    pub message: String,
+}
+
+impl std::fmt::Display for CodedError {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        write!(fmt, "{}: {}", self.code.as_str(), self.message)
+    }
+}
+
+impl std::error::Error for CodedError {}
+
+pub trait WithErrorCode<T> {
+    fn with_error_code(self, code: ErrorCode) -> AnyhowResult<T>;
+}
+
+impl<T> WithErrorCode<T> for AnyhowResult<T> {
+    fn with_error_code(self, code: ErrorCode) -> AnyhowResult<T> {
+        self.map_err(|e| {
+            let message = e.root_cause().to_string();
+            e.context(CodedError { code, message })
+        })
+    }
+}
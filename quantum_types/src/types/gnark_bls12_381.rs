@@ -0,0 +1,355 @@
+use anyhow::{anyhow, Result as AnyhowResult};
+use ark_bls12_381::{Bls12_381, Fr, G1Affine, G1Projective, G2Affine};
+use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup, VariableBaseMSM};
+use ark_ff::{BigInteger, Field, One, PrimeField, Zero};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use quantum_utils::error_line;
+use sha2::{Digest, Sha256};
+
+/*
+    Helpers shared by the gnark bls12-381 schemes: a reader for gnark's binary `WriteTo` encoding, gnark's
+    sha256 fiat-shamir transcript and its KZG batch opening verification.
+    gnark encodes bls12-381 points in the zcash format, the same format ark-bls12-381 uses, so points are
+    decoded with arkworks (including the on-curve and subgroup checks).
+ */
+
+const G1_COMPRESSED_SIZE: usize = 48;
+const G2_COMPRESSED_SIZE: usize = 96;
+const FR_SIZE: usize = 32;
+// the most significant bit of an encoded point is set for the compressed form
+const COMPRESSED_FLAG: u8 = 0b1000_0000;
+
+pub struct GnarkBls12381Reader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> GnarkBls12381Reader<'a> {
+    pub fn new(bytes: &'a [u8]) -> Self {
+        GnarkBls12381Reader { bytes, offset: 0 }
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.bytes.len() - self.offset
+    }
+
+    fn take(&mut self, n: usize) -> AnyhowResult<&'a [u8]> {
+        if n > self.remaining() {
+            return Err(anyhow!(error_line!(format!("unexpected end of gnark bytes at offset {}", self.offset))));
+        }
+        let slice = &self.bytes[self.offset..self.offset + n];
+        self.offset += n;
+        Ok(slice)
+    }
+
+    pub fn skip(&mut self, n: usize) -> AnyhowResult<()> {
+        self.take(n)?;
+        Ok(())
+    }
+
+    pub fn peek_u32(&self) -> AnyhowResult<u32> {
+        let bytes = self.bytes.get(self.offset..self.offset + 4).ok_or(anyhow!(error_line!("unexpected end of gnark bytes")))?;
+        Ok(u32::from_be_bytes(bytes.try_into()?))
+    }
+
+    pub fn read_u32(&mut self) -> AnyhowResult<u32> {
+        Ok(u32::from_be_bytes(self.take(4)?.try_into()?))
+    }
+
+    pub fn read_u64(&mut self) -> AnyhowResult<u64> {
+        Ok(u64::from_be_bytes(self.take(8)?.try_into()?))
+    }
+
+    // gnark rejects non canonical field elements, so does this
+    pub fn read_fr(&mut self) -> AnyhowResult<Fr> {
+        let bytes = self.take(FR_SIZE)?;
+        let fr = Fr::from_be_bytes_mod_order(bytes);
+        if fr_to_bytes(&fr) != bytes {
+            return Err(anyhow!(error_line!("gnark field element is not canonical")));
+        }
+        Ok(fr)
+    }
+
+    pub fn read_g1(&mut self) -> AnyhowResult<G1Affine> {
+        let compressed = self.bytes.get(self.offset).ok_or(anyhow!(error_line!("unexpected end of gnark bytes")))? & COMPRESSED_FLAG != 0;
+        let point = if compressed {
+            G1Affine::deserialize_compressed(self.take(G1_COMPRESSED_SIZE)?)
+        } else {
+            G1Affine::deserialize_uncompressed(self.take(2 * G1_COMPRESSED_SIZE)?)
+        };
+        point.map_err(|e| anyhow!(error_line!(format!("invalid bls12-381 g1 point: {}", e))))
+    }
+
+    pub fn read_g2(&mut self) -> AnyhowResult<G2Affine> {
+        let compressed = self.bytes.get(self.offset).ok_or(anyhow!(error_line!("unexpected end of gnark bytes")))? & COMPRESSED_FLAG != 0;
+        let point = if compressed {
+            G2Affine::deserialize_compressed(self.take(G2_COMPRESSED_SIZE)?)
+        } else {
+            G2Affine::deserialize_uncompressed(self.take(2 * G2_COMPRESSED_SIZE)?)
+        };
+        point.map_err(|e| anyhow!(error_line!(format!("invalid bls12-381 g2 point: {}", e))))
+    }
+
+    pub fn read_g1_vec(&mut self) -> AnyhowResult<Vec<G1Affine>> {
+        let len = self.read_u32()? as usize;
+        let mut points = Vec::with_capacity(len.min(self.remaining() / G1_COMPRESSED_SIZE));
+        for _ in 0..len {
+            points.push(self.read_g1()?);
+        }
+        Ok(points)
+    }
+
+    pub fn read_fr_vec(&mut self) -> AnyhowResult<Vec<Fr>> {
+        let len = self.read_u32()? as usize;
+        let mut elements = Vec::with_capacity(len.min(self.remaining() / FR_SIZE));
+        for _ in 0..len {
+            elements.push(self.read_fr()?);
+        }
+        Ok(elements)
+    }
+
+    pub fn read_u64_vec(&mut self) -> AnyhowResult<Vec<u64>> {
+        let len = self.read_u32()? as usize;
+        let mut values = Vec::with_capacity(len.min(self.remaining() / 8));
+        for _ in 0..len {
+            values.push(self.read_u64()?);
+        }
+        Ok(values)
+    }
+
+    pub fn ensure_consumed(&self) -> AnyhowResult<()> {
+        if self.remaining() != 0 {
+            return Err(anyhow!(error_line!(format!("{} trailing bytes after gnark object", self.remaining()))));
+        }
+        Ok(())
+    }
+}
+
+// `fr.Element.Marshal()`: 32 bytes big endian
+pub fn fr_to_bytes(fr: &Fr) -> Vec<u8> {
+    fr.into_bigint().to_bytes_be()
+}
+
+// `G1Affine.Marshal()`: the uncompressed (raw) encoding
+pub fn g1_to_bytes(point: &G1Affine) -> AnyhowResult<Vec<u8>> {
+    let mut bytes = vec![];
+    point.serialize_uncompressed(&mut bytes)?;
+    Ok(bytes)
+}
+
+pub fn msm(points: &[G1Affine], scalars: &[Fr]) -> AnyhowResult<G1Affine> {
+    let result = G1Projective::msm(points, scalars).map_err(|_| anyhow!(error_line!("msm points and scalars length mismatch")))?;
+    Ok(result.into_affine())
+}
+
+struct TranscriptChallenge {
+    id: &'static str,
+    bindings: Vec<Vec<u8>>,
+    value: Option<[u8; 32]>,
+}
+
+// gnark's `fiat-shamir.Transcript` over sha256. Challenges must be computed in the order they were declared.
+pub struct Transcript {
+    challenges: Vec<TranscriptChallenge>,
+    previous: Option<usize>,
+}
+
+impl Transcript {
+    pub fn new(challenge_ids: &[&'static str]) -> Self {
+        let challenges = challenge_ids
+            .iter()
+            .map(|id| TranscriptChallenge { id, bindings: vec![], value: None })
+            .collect();
+        Transcript { challenges, previous: None }
+    }
+
+    fn position(&self, challenge_id: &str) -> AnyhowResult<usize> {
+        self.challenges
+            .iter()
+            .position(|c| c.id == challenge_id)
+            .ok_or(anyhow!(error_line!(format!("unknown transcript challenge {}", challenge_id))))
+    }
+
+    pub fn bind(&mut self, challenge_id: &str, bytes: &[u8]) -> AnyhowResult<()> {
+        let position = self.position(challenge_id)?;
+        let challenge = &mut self.challenges[position];
+        if challenge.value.is_some() {
+            return Err(anyhow!(error_line!(format!("challenge {} is already computed", challenge_id))));
+        }
+        challenge.bindings.push(bytes.to_vec());
+        Ok(())
+    }
+
+    pub fn compute_challenge(&mut self, challenge_id: &str) -> AnyhowResult<[u8; 32]> {
+        let position = self.position(challenge_id)?;
+        if let Some(value) = self.challenges[position].value {
+            return Ok(value);
+        }
+
+        let mut hasher = Sha256::new();
+        hasher.update(challenge_id.as_bytes());
+        if position != 0 {
+            match self.previous {
+                Some(previous) if previous + 1 == position => {
+                    hasher.update(self.challenges[previous].value.unwrap());
+                }
+                _ => return Err(anyhow!(error_line!(format!("challenge before {} is not computed", challenge_id)))),
+            }
+        }
+        for binding in &self.challenges[position].bindings {
+            hasher.update(binding);
+        }
+        let value: [u8; 32] = hasher.finalize().into();
+
+        self.challenges[position].value = Some(value);
+        self.previous = Some(position);
+        Ok(value)
+    }
+
+    pub fn compute_fr_challenge(&mut self, challenge_id: &str) -> AnyhowResult<Fr> {
+        Ok(Fr::from_be_bytes_mod_order(&self.compute_challenge(challenge_id)?))
+    }
+}
+
+// `expand_message_xmd` of RFC 9380 with sha256
+fn expand_message_xmd(msg: &[u8], dst: &[u8], len_in_bytes: usize) -> AnyhowResult<Vec<u8>> {
+    let ell = (len_in_bytes + 31) / 32;
+    if ell > 255 || dst.len() > 255 {
+        return Err(anyhow!(error_line!("invalid expand_message_xmd length")));
+    }
+    let mut dst_prime = dst.to_vec();
+    dst_prime.push(dst.len() as u8);
+
+    let mut hasher = Sha256::new();
+    hasher.update([0u8; 64]);
+    hasher.update(msg);
+    hasher.update((len_in_bytes as u16).to_be_bytes());
+    hasher.update([0u8]);
+    hasher.update(&dst_prime);
+    let b_0: [u8; 32] = hasher.finalize().into();
+
+    let mut hasher = Sha256::new();
+    hasher.update(b_0);
+    hasher.update([1u8]);
+    hasher.update(&dst_prime);
+    let mut b_i: [u8; 32] = hasher.finalize().into();
+
+    let mut uniform_bytes = b_i.to_vec();
+    for i in 2..=ell {
+        let mut hasher = Sha256::new();
+        hasher.update(b_0.iter().zip(b_i.iter()).map(|(a, b)| a ^ b).collect::<Vec<u8>>());
+        hasher.update([i as u8]);
+        hasher.update(&dst_prime);
+        b_i = hasher.finalize().into();
+        uniform_bytes.extend_from_slice(&b_i);
+    }
+    uniform_bytes.truncate(len_in_bytes);
+    Ok(uniform_bytes)
+}
+
+// gnark-crypto `fr.Hash(msg, dst, 1)`: 16 extra bytes on top of the field size for 128 bit security
+pub fn hash_to_fr(msg: &[u8], dst: &[u8]) -> AnyhowResult<Fr> {
+    let bytes = expand_message_xmd(msg, dst, FR_SIZE + 16)?;
+    Ok(Fr::from_be_bytes_mod_order(&bytes))
+}
+
+pub struct KzgVerifyingKey {
+    pub g1: G1Affine,
+    pub g2: [G2Affine; 2],
+}
+
+pub struct OpeningProof {
+    pub h: G1Affine,
+    pub claimed_value: Fr,
+}
+
+pub struct BatchOpeningProof {
+    pub h: G1Affine,
+    pub claimed_values: Vec<Fr>,
+}
+
+// `kzg.FoldProof`: folds openings of several digests at the same point into one opening
+pub fn fold_proof(digests: &[G1Affine], batch_opening_proof: &BatchOpeningProof, point: &Fr, data_transcript: &[u8]) -> AnyhowResult<(OpeningProof, G1Affine)> {
+    if digests.len() != batch_opening_proof.claimed_values.len() {
+        return Err(anyhow!(error_line!("number of digests and claimed values mismatch")));
+    }
+
+    let mut transcript = Transcript::new(&["gamma"]);
+    transcript.bind("gamma", &fr_to_bytes(point))?;
+    for digest in digests {
+        transcript.bind("gamma", &g1_to_bytes(digest)?)?;
+    }
+    for claimed_value in &batch_opening_proof.claimed_values {
+        transcript.bind("gamma", &fr_to_bytes(claimed_value))?;
+    }
+    transcript.bind("gamma", data_transcript)?;
+    let gamma = transcript.compute_fr_challenge("gamma")?;
+
+    let mut gammai = Vec::with_capacity(digests.len());
+    let mut power = Fr::one();
+    for _ in 0..digests.len() {
+        gammai.push(power);
+        power *= gamma;
+    }
+
+    let folded_digest = msm(digests, &gammai)?;
+    let folded_evaluation = batch_opening_proof
+        .claimed_values
+        .iter()
+        .zip(gammai.iter())
+        .fold(Fr::zero(), |acc, (value, gamma_i)| acc + *value * gamma_i);
+
+    Ok((OpeningProof { h: batch_opening_proof.h, claimed_value: folded_evaluation }, folded_digest))
+}
+
+/*
+    `kzg.BatchVerifyMultiPoints`. gnark samples the folding coefficients at random, here they are derived
+    from everything being verified, which the prover cannot influence after the fact.
+ */
+pub fn batch_verify_multi_points(digests: &[G1Affine], proofs: &[OpeningProof], points: &[Fr], vk: &KzgVerifyingKey) -> AnyhowResult<()> {
+    if digests.len() != proofs.len() || digests.len() != points.len() || digests.is_empty() {
+        return Err(anyhow!(error_line!("invalid number of kzg openings")));
+    }
+
+    let mut hasher = Sha256::new();
+    for ((digest, proof), point) in digests.iter().zip(proofs).zip(points) {
+        hasher.update(g1_to_bytes(digest)?);
+        hasher.update(g1_to_bytes(&proof.h)?);
+        hasher.update(fr_to_bytes(&proof.claimed_value));
+        hasher.update(fr_to_bytes(point));
+    }
+    let seed = Fr::from_be_bytes_mod_order(&hasher.finalize());
+    let mut random_numbers = Vec::with_capacity(digests.len());
+    let mut power = Fr::one();
+    for _ in 0..digests.len() {
+        random_numbers.push(power);
+        power *= seed;
+    }
+
+    let quotients: Vec<G1Affine> = proofs.iter().map(|p| p.h).collect();
+    let folded_quotients = msm(&quotients, &random_numbers)?;
+
+    // ∑ᵢλᵢ[fᵢ(α)]G₁ - [∑ᵢλᵢfᵢ(aᵢ)]G₁ + ∑ᵢλᵢ[pᵢ]([Hᵢ(α)]G₁)
+    let folded_evaluations = proofs
+        .iter()
+        .zip(random_numbers.iter())
+        .fold(Fr::zero(), |acc, (proof, lambda)| acc + proof.claimed_value * lambda);
+    let folded_points: Vec<Fr> = random_numbers.iter().zip(points).map(|(lambda, point)| *lambda * point).collect();
+    let folded_digests = msm(digests, &random_numbers)?.into_group()
+        - vk.g1 * folded_evaluations
+        + msm(&quotients, &folded_points)?.into_group();
+
+    // e(folded_digests, [1]G₂)·e(-folded_quotients, [α]G₂) == 1
+    let check = Bls12_381::multi_pairing(
+        [folded_digests.into_affine(), (-folded_quotients.into_group()).into_affine()],
+        [vk.g2[0], vk.g2[1]],
+    );
+    if !check.is_zero() {
+        return Err(anyhow!(error_line!("kzg pairing check failed")));
+    }
+    Ok(())
+}
+
+pub fn pow_u64(base: &Fr, exponent: u64) -> Fr {
+    base.pow([exponent])
+}
@@ -0,0 +1,281 @@
+use agg_core::inputs::compute_combined_vkey_hash;
+use anyhow::{anyhow, Result as AnyhowResult};
+use ark_bls12_381::{Bls12_381, Fr as ArkFr};
+use ark_groth16::{verifier, Groth16, Proof as ArkProof, VerifyingKey};
+use borsh::{BorshDeserialize, BorshSerialize};
+use quantum_utils::{
+    error_line,
+    file::{read_bytes_from_file, write_bytes_to_file},
+};
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+use utils::hash::{Hasher, KeccakHasher};
+
+use crate::traits::{pis::Pis, proof::Proof, vkey::Vkey};
+use crate::types::gnark_bls12_381::{fr_to_bytes, GnarkBls12381Reader};
+
+// gnark groth16 verifying key over bls12-381, as written by gnark's `VerifyingKey.WriteTo`
+#[derive(Clone, BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, PartialEq)]
+pub struct GnarkGroth16Bls12381Vkey {
+    pub vkey_bytes: Vec<u8>
+}
+
+impl GnarkGroth16Bls12381Vkey {
+    /*
+        [α]₁, [β]₁, [β]₂, [γ]₂, [δ]₁, [δ]₂, [Kvk]₁, then the commitment data of newer gnark versions.
+        Circuits using gnark's pedersen commitments are not supported.
+     */
+    pub fn get_ark_vk(&self) -> AnyhowResult<VerifyingKey<Bls12_381>> {
+        let mut reader = GnarkBls12381Reader::new(&self.vkey_bytes);
+        let alpha_g1 = reader.read_g1()?;
+        let _beta_g1 = reader.read_g1()?;
+        let beta_g2 = reader.read_g2()?;
+        let gamma_g2 = reader.read_g2()?;
+        let _delta_g1 = reader.read_g1()?;
+        let delta_g2 = reader.read_g2()?;
+        let gamma_abc_g1 = reader.read_g1_vec()?;
+        if gamma_abc_g1.is_empty() {
+            return Err(anyhow!(error_line!("gnark-groth16-bls12-381 vkey has no K points")));
+        }
+
+        if reader.remaining() != 0 {
+            let public_and_commitment_committed_len = reader.read_u32()?;
+            for _ in 0..public_and_commitment_committed_len {
+                reader.read_u64_vec()?;
+            }
+            let nb_commitment_keys = reader.read_u32()?;
+            if nb_commitment_keys != 0 {
+                return Err(anyhow!(error_line!("gnark-groth16-bls12-381 circuits with commitments are not supported")));
+            }
+        }
+        reader.ensure_consumed()?;
+
+        Ok(VerifyingKey { alpha_g1, beta_g2, gamma_g2, delta_g2, gamma_abc_g1 })
+    }
+}
+
+impl Vkey for GnarkGroth16Bls12381Vkey {
+    fn serialize_vkey(&self) -> AnyhowResult<Vec<u8>> {
+        let mut buffer: Vec<u8> = Vec::new();
+        BorshSerialize::serialize(&self, &mut buffer).map_err(|err| anyhow!(error_line!(err)))?;
+        Ok(buffer)
+    }
+
+    fn deserialize_vkey(bytes: &mut &[u8]) -> AnyhowResult<Self> {
+        let key: GnarkGroth16Bls12381Vkey =
+            BorshDeserialize::deserialize(bytes).map_err(|err| anyhow!(error_line!(err)))?;
+        Ok(key)
+    }
+
+    fn dump_vk(&self, path: &str) -> AnyhowResult<()> {
+        let vkey_bytes = self.serialize_vkey()?;
+        write_bytes_to_file(&vkey_bytes, path)?;
+        Ok(())
+    }
+
+    fn read_vk(full_path: &str) -> AnyhowResult<Self> {
+        let vkey_bytes = read_bytes_from_file(full_path)?;
+        let gnark_vkey = GnarkGroth16Bls12381Vkey::deserialize_vkey(&mut vkey_bytes.as_slice())?;
+        Ok(gnark_vkey)
+    }
+
+    fn validate(&self) -> AnyhowResult<()> {
+        self.get_ark_vk()?;
+        Ok(())
+    }
+
+    fn keccak_hash(&self) -> AnyhowResult<[u8; 32]> {
+        let hash = KeccakHasher::hash_out(&self.vkey_bytes);
+        Ok(hash)
+    }
+
+    fn compute_circuit_hash(&self, circuit_verifying_id: [u32; 8]) -> AnyhowResult<[u8; 32]> {
+        let protocol_hash = self.keccak_hash()?;
+        let circuit_hash = compute_combined_vkey_hash::<KeccakHasher>(&protocol_hash, &circuit_verifying_id)?;
+        Ok(circuit_hash)
+    }
+}
+
+#[derive(Clone, BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, PartialEq)]
+pub struct GnarkGroth16Bls12381Proof {
+    pub proof_bytes: Vec<u8>,
+}
+
+impl GnarkGroth16Bls12381Proof {
+    // Ar, Bs, Krs, then the (empty) commitments and their proof of knowledge of newer gnark versions
+    pub fn get_ark_proof(&self) -> AnyhowResult<ArkProof<Bls12_381>> {
+        let mut reader = GnarkBls12381Reader::new(&self.proof_bytes);
+        let a = reader.read_g1()?;
+        let b = reader.read_g2()?;
+        let c = reader.read_g1()?;
+        if reader.remaining() != 0 {
+            if !reader.read_g1_vec()?.is_empty() {
+                return Err(anyhow!(error_line!("gnark-groth16-bls12-381 proofs with commitments are not supported")));
+            }
+            let _commitment_pok = reader.read_g1()?;
+        }
+        reader.ensure_consumed()?;
+        Ok(ArkProof { a, b, c })
+    }
+}
+
+impl Proof for GnarkGroth16Bls12381Proof {
+    fn serialize_proof(&self) -> AnyhowResult<Vec<u8>> {
+        let mut buffer: Vec<u8> = Vec::new();
+        BorshSerialize::serialize(&self, &mut buffer)?;
+        Ok(buffer)
+    }
+
+    fn deserialize_proof(bytes: &mut &[u8]) -> AnyhowResult<Self> {
+        let key: GnarkGroth16Bls12381Proof =
+            BorshDeserialize::deserialize(bytes).map_err(|err| anyhow!(error_line!(err)))?;
+        Ok(key)
+    }
+
+    fn dump_proof(&self, path: &str) -> AnyhowResult<()> {
+        let proof_bytes = self.serialize_proof()?;
+        write_bytes_to_file(&proof_bytes, path)?;
+        Ok(())
+    }
+
+    fn read_proof(full_path: &str) -> AnyhowResult<Self> {
+        let proof_bytes = read_bytes_from_file(full_path)?;
+        let gnark_proof = GnarkGroth16Bls12381Proof::deserialize_proof(&mut proof_bytes.as_slice())?;
+        Ok(gnark_proof)
+    }
+
+    fn validate_proof(&self, vkey_path: &str, mut pis_bytes: &[u8]) -> AnyhowResult<()> {
+        let vk = GnarkGroth16Bls12381Vkey::read_vk(vkey_path)?;
+        let pis = GnarkGroth16Bls12381Pis::deserialize_pis(&mut pis_bytes)?;
+
+        let pvk = verifier::prepare_verifying_key(&vk.get_ark_vk()?);
+        let is_verified = Groth16::<Bls12_381>::verify_proof(&pvk, &self.get_ark_proof()?, &pis.get_ark_pis()?)
+            .map_err(|e| anyhow!(error_line!(format!("gnark-groth16-bls12-381 proof validation failed: {}", e))))?;
+        if !is_verified {
+            return Err(anyhow!(error_line!("gnark-groth16-bls12-381 proof validation failed")))
+        }
+        Ok(())
+    }
+
+    fn get_proof_bytes(&self) -> AnyhowResult<Vec<u8>> {
+        Ok(self.proof_bytes.clone())
+    }
+}
+
+#[derive(Clone, BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, PartialEq)]
+pub struct GnarkGroth16Bls12381Pis(pub Vec<String>);
+
+impl Pis for GnarkGroth16Bls12381Pis {
+    fn serialize_pis(&self) -> AnyhowResult<Vec<u8>> {
+        let mut buffer: Vec<u8> = Vec::new();
+        BorshSerialize::serialize(&self, &mut buffer)?;
+        Ok(buffer)
+    }
+
+    fn deserialize_pis(bytes: &mut &[u8]) -> AnyhowResult<Self> {
+        let key: GnarkGroth16Bls12381Pis =
+            BorshDeserialize::deserialize(bytes).map_err(|err| anyhow!(error_line!(err)))?;
+        Ok(key)
+    }
+
+    fn dump_pis(&self, path: &str) -> AnyhowResult<()> {
+        let pis_bytes = self.serialize_pis()?;
+        write_bytes_to_file(&pis_bytes, path)?;
+        Ok(())
+    }
+
+    fn read_pis(full_path: &str) -> AnyhowResult<Self> {
+        let pis_bytes = read_bytes_from_file(full_path)?;
+        let gnark_pis = GnarkGroth16Bls12381Pis::deserialize_pis(&mut pis_bytes.as_slice())?;
+        Ok(gnark_pis)
+    }
+
+    // keccak of the big endian encoded public inputs
+    fn keccak_hash(&self) -> AnyhowResult<[u8; 32]> {
+        let pis_bytes: Vec<u8> = self.get_ark_pis()?.iter().flat_map(fr_to_bytes).collect();
+        Ok(KeccakHasher::hash_out(&pis_bytes))
+    }
+
+    fn get_data(&self) -> AnyhowResult<Vec<String>> {
+        Ok(self.0.clone())
+    }
+}
+
+impl GnarkGroth16Bls12381Pis {
+    pub fn get_ark_pis(&self) -> AnyhowResult<Vec<ArkFr>> {
+        let mut ark_pis = vec![];
+        for p in &self.0 {
+            ark_pis.push(ArkFr::from_str(&p).map_err(|_| anyhow!(error_line!("failed to form ark pis from gnark-groth16-bls12-381 pis")))?)
+        }
+        Ok(ark_pis)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_groth16::ProvingKey;
+    use ark_relations::{lc, r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError}};
+    use ark_serialize::CanonicalSerialize;
+    use ark_std::test_rng;
+
+    // knowledge of x with x * x = y, y public
+    struct SquareCircuit {
+        x: Option<ArkFr>,
+    }
+
+    impl ConstraintSynthesizer<ArkFr> for SquareCircuit {
+        fn generate_constraints(self, cs: ConstraintSystemRef<ArkFr>) -> Result<(), SynthesisError> {
+            let y = cs.new_input_variable(|| self.x.map(|x| x * x).ok_or(SynthesisError::AssignmentMissing))?;
+            let x = cs.new_witness_variable(|| self.x.ok_or(SynthesisError::AssignmentMissing))?;
+            cs.enforce_constraint(lc!() + x, lc!() + x, lc!() + y)
+        }
+    }
+
+    // gnark's `WriteTo` with compressed points
+    fn write_point<P: CanonicalSerialize>(bytes: &mut Vec<u8>, point: &P) {
+        point.serialize_compressed(&mut *bytes).unwrap();
+    }
+
+    fn get_gnark_vkey(pk: &ProvingKey<Bls12_381>) -> GnarkGroth16Bls12381Vkey {
+        let mut vkey_bytes = vec![];
+        write_point(&mut vkey_bytes, &pk.vk.alpha_g1);
+        write_point(&mut vkey_bytes, &pk.beta_g1);
+        write_point(&mut vkey_bytes, &pk.vk.beta_g2);
+        write_point(&mut vkey_bytes, &pk.vk.gamma_g2);
+        write_point(&mut vkey_bytes, &pk.delta_g1);
+        write_point(&mut vkey_bytes, &pk.vk.delta_g2);
+        vkey_bytes.extend_from_slice(&(pk.vk.gamma_abc_g1.len() as u32).to_be_bytes());
+        for point in &pk.vk.gamma_abc_g1 {
+            write_point(&mut vkey_bytes, point);
+        }
+        GnarkGroth16Bls12381Vkey { vkey_bytes }
+    }
+
+    #[test]
+    fn test_validate_gnark_groth16_bls12_381_proof() {
+        let mut rng = test_rng();
+        let pk = Groth16::<Bls12_381>::generate_random_parameters_with_reduction(SquareCircuit { x: None }, &mut rng).unwrap();
+        let ark_proof = Groth16::<Bls12_381>::create_random_proof_with_reduction(SquareCircuit { x: Some(ArkFr::from(3u64)) }, &pk, &mut rng).unwrap();
+
+        let vkey = get_gnark_vkey(&pk);
+        vkey.validate().unwrap();
+        let truncated_vkey = GnarkGroth16Bls12381Vkey { vkey_bytes: vkey.vkey_bytes[..vkey.vkey_bytes.len() - 1].to_vec() };
+        assert!(truncated_vkey.validate().is_err());
+        let vk_file = tempfile::NamedTempFile::new().unwrap();
+        let vk_path = vk_file.path().to_str().unwrap();
+        vkey.dump_vk(vk_path).unwrap();
+
+        let mut proof_bytes = vec![];
+        write_point(&mut proof_bytes, &ark_proof.a);
+        write_point(&mut proof_bytes, &ark_proof.b);
+        write_point(&mut proof_bytes, &ark_proof.c);
+        let proof = GnarkGroth16Bls12381Proof { proof_bytes };
+
+        let pis = GnarkGroth16Bls12381Pis(vec![String::from("9")]);
+        proof.validate_proof(vk_path, &pis.serialize_pis().unwrap()).unwrap();
+
+        let wrong_pis = GnarkGroth16Bls12381Pis(vec![String::from("10")]);
+        assert!(proof.validate_proof(vk_path, &wrong_pis.serialize_pis().unwrap()).is_err());
+    }
+}
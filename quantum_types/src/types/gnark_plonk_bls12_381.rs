@@ -0,0 +1,420 @@
+use agg_core::inputs::compute_combined_vkey_hash;
+use anyhow::{anyhow, Result as AnyhowResult};
+use ark_bls12_381::{Fr as ArkFr, G1Affine};
+use ark_ff::{Field, One, Zero};
+use borsh::{BorshDeserialize, BorshSerialize};
+use quantum_utils::{
+    error_line,
+    file::{read_bytes_from_file, write_bytes_to_file},
+};
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+use utils::hash::{Hasher, KeccakHasher};
+
+use crate::traits::{pis::Pis, proof::Proof, vkey::Vkey};
+use crate::types::gnark_bls12_381::{
+    batch_verify_multi_points, fold_proof, fr_to_bytes, g1_to_bytes, hash_to_fr, msm, pow_u64,
+    BatchOpeningProof, GnarkBls12381Reader, KzgVerifyingKey, OpeningProof, Transcript,
+};
+
+// size of the precomputed pairing lines gnark >= 0.10 appends to the kzg verifying key: 2x2x63 lines of two E2
+const KZG_LINES_SIZE: usize = 2 * 2 * 63 * 2 * 96;
+const BSB22_DST: &[u8] = b"BSB22-Plonk";
+
+// gnark plonk verifying key over bls12-381, as written by gnark's `VerifyingKey.WriteTo`
+#[derive(Clone, BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, PartialEq)]
+pub struct GnarkPlonkBls12381Vkey {
+    pub vkey_bytes: Vec<u8>
+}
+
+pub struct PlonkVerifyingKey {
+    pub size: u64,
+    pub size_inv: ArkFr,
+    pub generator: ArkFr,
+    pub nb_public_variables: u64,
+    pub coset_shift: ArkFr,
+    pub s: [G1Affine; 3],
+    pub ql: G1Affine,
+    pub qr: G1Affine,
+    pub qm: G1Affine,
+    pub qo: G1Affine,
+    pub qk: G1Affine,
+    pub qcp: Vec<G1Affine>,
+    pub kzg: KzgVerifyingKey,
+    pub commitment_constraint_indexes: Vec<u64>,
+}
+
+impl GnarkPlonkBls12381Vkey {
+    pub fn get_plonk_vk(&self) -> AnyhowResult<PlonkVerifyingKey> {
+        let mut reader = GnarkBls12381Reader::new(&self.vkey_bytes);
+        let size = reader.read_u64()?;
+        let size_inv = reader.read_fr()?;
+        let generator = reader.read_fr()?;
+        let nb_public_variables = reader.read_u64()?;
+        let coset_shift = reader.read_fr()?;
+        let s = [reader.read_g1()?, reader.read_g1()?, reader.read_g1()?];
+        let ql = reader.read_g1()?;
+        let qr = reader.read_g1()?;
+        let qm = reader.read_g1()?;
+        let qo = reader.read_g1()?;
+        let qk = reader.read_g1()?;
+        let qcp = reader.read_g1_vec()?;
+        let kzg_g1 = reader.read_g1()?;
+        let kzg_g2 = [reader.read_g2()?, reader.read_g2()?];
+        // older gnark versions do not write the pairing lines
+        let no_lines_size = 4 + 8 * reader.peek_u32()? as usize;
+        if reader.remaining() != no_lines_size {
+            reader.skip(KZG_LINES_SIZE)?;
+        }
+        let commitment_constraint_indexes = reader.read_u64_vec()?;
+        reader.ensure_consumed()?;
+
+        if !size.is_power_of_two() || ArkFr::from(size) * size_inv != ArkFr::one() {
+            return Err(anyhow!(error_line!("gnark-plonk-bls12-381 vkey has an invalid domain size")));
+        }
+        if pow_u64(&generator, size) != ArkFr::one() {
+            return Err(anyhow!(error_line!("gnark-plonk-bls12-381 vkey has an invalid domain generator")));
+        }
+        if nb_public_variables > size || qcp.len() != commitment_constraint_indexes.len() {
+            return Err(anyhow!(error_line!("gnark-plonk-bls12-381 vkey is inconsistent")));
+        }
+
+        Ok(PlonkVerifyingKey {
+            size,
+            size_inv,
+            generator,
+            nb_public_variables,
+            coset_shift,
+            s,
+            ql,
+            qr,
+            qm,
+            qo,
+            qk,
+            qcp,
+            kzg: KzgVerifyingKey { g1: kzg_g1, g2: kzg_g2 },
+            commitment_constraint_indexes,
+        })
+    }
+}
+
+impl Vkey for GnarkPlonkBls12381Vkey {
+    fn serialize_vkey(&self) -> AnyhowResult<Vec<u8>> {
+        let mut buffer: Vec<u8> = Vec::new();
+        BorshSerialize::serialize(&self, &mut buffer).map_err(|err| anyhow!(error_line!(err)))?;
+        Ok(buffer)
+    }
+
+    fn deserialize_vkey(bytes: &mut &[u8]) -> AnyhowResult<Self> {
+        let key: GnarkPlonkBls12381Vkey =
+            BorshDeserialize::deserialize(bytes).map_err(|err| anyhow!(error_line!(err)))?;
+        Ok(key)
+    }
+
+    fn dump_vk(&self, path: &str) -> AnyhowResult<()> {
+        let vkey_bytes = self.serialize_vkey()?;
+        write_bytes_to_file(&vkey_bytes, path)?;
+        Ok(())
+    }
+
+    fn read_vk(full_path: &str) -> AnyhowResult<Self> {
+        let vkey_bytes = read_bytes_from_file(full_path)?;
+        let vkey = GnarkPlonkBls12381Vkey::deserialize_vkey(&mut vkey_bytes.as_slice())?;
+        Ok(vkey)
+    }
+
+    fn validate(&self) -> AnyhowResult<()> {
+        self.get_plonk_vk()?;
+        Ok(())
+    }
+
+    fn keccak_hash(&self) -> AnyhowResult<[u8; 32]> {
+        let hash = KeccakHasher::hash_out(&self.vkey_bytes);
+        Ok(hash)
+    }
+
+    fn compute_circuit_hash(&self, circuit_verifying_id: [u32;8]) -> AnyhowResult<[u8;32]> {
+        let protocol_hash = self.keccak_hash()?;
+        let circuit_hash = compute_combined_vkey_hash::<KeccakHasher>(&protocol_hash, &circuit_verifying_id)?;
+        Ok(circuit_hash)
+    }
+}
+
+// gnark plonk proof over bls12-381, as written by gnark's `Proof.WriteTo`
+#[derive(Clone, BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, PartialEq)]
+pub struct GnarkPlonkBls12381Proof {
+    pub proof_bytes: Vec<u8>,
+}
+
+pub struct PlonkProof {
+    pub lro: [G1Affine; 3],
+    pub z: G1Affine,
+    pub h: [G1Affine; 3],
+    pub batched_proof: BatchOpeningProof,
+    pub z_shifted_opening: OpeningProof,
+    pub bsb22_commitments: Vec<G1Affine>,
+}
+
+impl GnarkPlonkBls12381Proof {
+    pub fn get_plonk_proof(&self) -> AnyhowResult<PlonkProof> {
+        let mut reader = GnarkBls12381Reader::new(&self.proof_bytes);
+        let lro = [reader.read_g1()?, reader.read_g1()?, reader.read_g1()?];
+        let z = reader.read_g1()?;
+        let h = [reader.read_g1()?, reader.read_g1()?, reader.read_g1()?];
+        let batched_proof = BatchOpeningProof { h: reader.read_g1()?, claimed_values: reader.read_fr_vec()? };
+        let z_shifted_opening = OpeningProof { h: reader.read_g1()?, claimed_value: reader.read_fr()? };
+        let bsb22_commitments = reader.read_g1_vec()?;
+        reader.ensure_consumed()?;
+        Ok(PlonkProof { lro, z, h, batched_proof, z_shifted_opening, bsb22_commitments })
+    }
+}
+
+fn derive_randomness(transcript: &mut Transcript, challenge: &str, points: &[G1Affine]) -> AnyhowResult<ArkFr> {
+    for point in points {
+        transcript.bind(challenge, &g1_to_bytes(point)?)?;
+    }
+    transcript.compute_fr_challenge(challenge)
+}
+
+// Port of gnark's (v0.10) `plonk.Verify` for bls12-381
+pub fn verify_plonk(vk: &PlonkVerifyingKey, proof: &PlonkProof, public_inputs: &[ArkFr]) -> AnyhowResult<()> {
+    if proof.bsb22_commitments.len() != vk.qcp.len() {
+        return Err(anyhow!(error_line!("bsb22 commitment number mismatch")));
+    }
+    if public_inputs.len() as u64 != vk.nb_public_variables {
+        return Err(anyhow!(error_line!("invalid number of public inputs")));
+    }
+    if proof.batched_proof.claimed_values.len() != 6 + vk.qcp.len() {
+        return Err(anyhow!(error_line!("invalid number of claimed values")));
+    }
+
+    // derive gamma, beta, alpha, zeta from the public data and the proof
+    let mut transcript = Transcript::new(&["gamma", "beta", "alpha", "zeta"]);
+    let public_points = [&vk.s[..], &[vk.ql, vk.qr, vk.qm, vk.qo, vk.qk], &vk.qcp[..]].concat();
+    for point in &public_points {
+        transcript.bind("gamma", &g1_to_bytes(point)?)?;
+    }
+    for public_input in public_inputs {
+        transcript.bind("gamma", &fr_to_bytes(public_input))?;
+    }
+    let gamma = derive_randomness(&mut transcript, "gamma", &proof.lro)?;
+    let beta = derive_randomness(&mut transcript, "beta", &[])?;
+    let alpha_deps = [&proof.bsb22_commitments[..], &[proof.z]].concat();
+    let alpha = derive_randomness(&mut transcript, "alpha", &alpha_deps)?;
+    let zeta = derive_randomness(&mut transcript, "zeta", &proof.h)?;
+
+    // ζⁿ-1 and L₁(ζ) = (ζⁿ-1)/(n(ζ-1))
+    let one = ArkFr::one();
+    let zh_zeta = pow_u64(&zeta, vk.size) - one;
+    let lagrange_one = (zeta - one).inverse().ok_or(anyhow!(error_line!("zeta is 1")))? * zh_zeta * vk.size_inv;
+
+    // PI(ζ) = ∑ᵢ xᵢ·Lᵢ(ζ), with Lᵢ(ζ) = wⁱ(ζⁿ-1)/(n(ζ-wⁱ))
+    let mut pi = ArkFr::zero();
+    let mut accw = one;
+    for public_input in public_inputs {
+        let den = (zeta - accw).inverse().ok_or(anyhow!(error_line!("zeta is a root of unity")))?;
+        pi += zh_zeta * den * vk.size_inv * accw * public_input;
+        accw *= vk.generator;
+    }
+    for (i, index) in vk.commitment_constraint_indexes.iter().enumerate() {
+        let hashed_commitment = hash_to_fr(&g1_to_bytes(&proof.bsb22_commitments[i])?, BSB22_DST)?;
+        let w_pow_i = pow_u64(&vk.generator, vk.nb_public_variables + index);
+        let den = (zeta - w_pow_i).inverse().ok_or(anyhow!(error_line!("zeta is a root of unity")))?;
+        pi += zh_zeta * w_pow_i * den * vk.size_inv * hashed_commitment;
+    }
+
+    let claimed_values = &proof.batched_proof.claimed_values;
+    let (l, r, o, s1, s2) = (claimed_values[1], claimed_values[2], claimed_values[3], claimed_values[4], claimed_values[5]);
+    let zu = proof.z_shifted_opening.claimed_value;
+    let alpha_square_lagrange_one = lagrange_one * alpha * alpha;
+
+    // -[PI(ζ) - α²*L₁(ζ) + α(l(ζ)+β*s1(ζ)+γ)(r(ζ)+β*s2(ζ)+γ)(o(ζ)+γ)*z(ωζ)] must be the opening of the linearised polynomial
+    let const_lin = -((l + beta * s1 + gamma) * (r + beta * s2 + gamma) * (o + gamma) * alpha * zu - alpha_square_lagrange_one + pi);
+    if const_lin != claimed_values[0] {
+        return Err(anyhow!(error_line!("plonk algebraic relation does not hold")));
+    }
+
+    // α*(l(ζ)+β*s1(ζ)+γ)*(r(ζ)+β*s2(ζ)+γ)*β*Z(μζ)
+    let s1_coeff = (l + beta * s1 + gamma) * (r + beta * s2 + gamma) * beta * alpha * zu;
+    // α²*L₁(ζ) - α*(l(ζ)+β*ζ+γ)*(r(ζ)+β*u*ζ+γ)*(o(ζ)+β*u²*ζ+γ)
+    let coeff_z = alpha_square_lagrange_one
+        - (l + beta * zeta + gamma)
+            * (r + beta * vk.coset_shift * zeta + gamma)
+            * (o + beta * vk.coset_shift * vk.coset_shift * zeta + gamma)
+            * alpha;
+
+    // -ζⁿ⁺²*(ζⁿ-1), -ζ²⁽ⁿ⁺²⁾*(ζⁿ-1), -(ζⁿ-1)
+    let zeta_n_plus_two = pow_u64(&zeta, vk.size + 2);
+    let zeta_n_plus_two_zh = -(zeta_n_plus_two * zh_zeta);
+    let zeta_n_plus_two_square_zh = -(zeta_n_plus_two * zeta_n_plus_two * zh_zeta);
+    let zh = -zh_zeta;
+
+    let points = [
+        &proof.bsb22_commitments[..],
+        &[vk.ql, vk.qr, vk.qm, vk.qo, vk.qk, vk.s[2], proof.z, proof.h[0], proof.h[1], proof.h[2]],
+    ]
+    .concat();
+    let scalars = [
+        &claimed_values[6..],
+        &[l, r, l * r, o, one, s1_coeff, coeff_z, zh, zeta_n_plus_two_zh, zeta_n_plus_two_square_zh],
+    ]
+    .concat();
+    let linearized_polynomial_digest = msm(&points, &scalars)?;
+
+    let digests_to_fold = [
+        &[linearized_polynomial_digest, proof.lro[0], proof.lro[1], proof.lro[2], vk.s[0], vk.s[1]],
+        &vk.qcp[..],
+    ]
+    .concat();
+    let (folded_proof, folded_digest) = fold_proof(&digests_to_fold, &proof.batched_proof, &zeta, &fr_to_bytes(&zu))?;
+
+    let shifted_zeta = zeta * vk.generator;
+    batch_verify_multi_points(
+        &[folded_digest, proof.z],
+        &[folded_proof, OpeningProof { h: proof.z_shifted_opening.h, claimed_value: zu }],
+        &[zeta, shifted_zeta],
+        &vk.kzg,
+    )
+}
+
+impl Proof for GnarkPlonkBls12381Proof {
+    fn serialize_proof(&self) -> AnyhowResult<Vec<u8>> {
+        let mut buffer: Vec<u8> = Vec::new();
+        BorshSerialize::serialize(&self, &mut buffer)?;
+        Ok(buffer)
+    }
+
+    fn deserialize_proof(bytes: &mut &[u8]) -> AnyhowResult<Self> {
+        let key: GnarkPlonkBls12381Proof =
+            BorshDeserialize::deserialize(bytes).map_err(|err| anyhow!(error_line!(err)))?;
+        Ok(key)
+    }
+
+    fn dump_proof(&self, path: &str) -> AnyhowResult<()> {
+        let proof_bytes = self.serialize_proof()?;
+        write_bytes_to_file(&proof_bytes, path)?;
+        Ok(())
+    }
+
+    fn read_proof(full_path: &str) -> AnyhowResult<Self> {
+        let proof_bytes = read_bytes_from_file(full_path)?;
+        let gnark_proof = GnarkPlonkBls12381Proof::deserialize_proof(&mut proof_bytes.as_slice())?;
+        Ok(gnark_proof)
+    }
+
+    fn validate_proof(&self, vkey_path: &str, mut pis_bytes: &[u8]) -> AnyhowResult<()> {
+        let vk = GnarkPlonkBls12381Vkey::read_vk(vkey_path)?;
+        let pis = GnarkPlonkBls12381Pis::deserialize_pis(&mut pis_bytes)?;
+
+        verify_plonk(&vk.get_plonk_vk()?, &self.get_plonk_proof()?, &pis.get_ark_pis()?)
+            .map_err(|e| anyhow!(error_line!(format!("gnark-plonk-bls12-381 proof validation failed: {}", e))))
+    }
+
+    fn get_proof_bytes(&self) -> AnyhowResult<Vec<u8>> {
+        Ok(self.proof_bytes.clone())
+    }
+}
+
+#[derive(Clone, BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, PartialEq)]
+pub struct GnarkPlonkBls12381Pis(pub Vec<String>);
+
+impl Pis for GnarkPlonkBls12381Pis {
+    fn serialize_pis(&self) -> AnyhowResult<Vec<u8>> {
+        let mut buffer: Vec<u8> = Vec::new();
+        BorshSerialize::serialize(&self, &mut buffer)?;
+        Ok(buffer)
+    }
+
+    fn deserialize_pis(bytes: &mut &[u8]) -> AnyhowResult<Self> {
+        let key: GnarkPlonkBls12381Pis =
+            BorshDeserialize::deserialize(bytes).map_err(|err| anyhow!(error_line!(err)))?;
+        Ok(key)
+    }
+
+    fn dump_pis(&self, path: &str) -> AnyhowResult<()> {
+        let pis_bytes = self.serialize_pis()?;
+        write_bytes_to_file(&pis_bytes, path)?;
+        Ok(())
+    }
+
+    fn read_pis(full_path: &str) -> AnyhowResult<Self> {
+        let pis_bytes = read_bytes_from_file(full_path)?;
+        let gnark_pis = GnarkPlonkBls12381Pis::deserialize_pis(&mut pis_bytes.as_slice())?;
+        Ok(gnark_pis)
+    }
+
+    // keccak of the big endian encoded public inputs
+    fn keccak_hash(&self) -> AnyhowResult<[u8; 32]> {
+        let pis_bytes: Vec<u8> = self.get_ark_pis()?.iter().flat_map(fr_to_bytes).collect();
+        Ok(KeccakHasher::hash_out(&pis_bytes))
+    }
+
+    fn get_data(&self) -> AnyhowResult<Vec<String>> {
+        Ok(self.0.clone())
+    }
+}
+
+impl GnarkPlonkBls12381Pis {
+    pub fn get_ark_pis(&self) -> AnyhowResult<Vec<ArkFr>> {
+        let mut ark_pis = vec![];
+        for p in &self.0 {
+            ark_pis.push(ArkFr::from_str(&p).map_err(|_| anyhow!(error_line!("failed to form ark pis from gnark-plonk-bls12-381 pis")))?)
+        }
+        Ok(ark_pis)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /*
+        Proof of `x * x == y` with a bsb22 commitment, by gnark v0.10 itself so that the transcript, hash to field
+        and kzg batch opening of `verify_plonk` are checked against gnark. Written into
+        `test_data/gnark_plonk_bls12_381` by `scripts/gnark_plonk_bls12_381_fixture`.
+     */
+    fn read_fixtures() -> (GnarkPlonkBls12381Vkey, GnarkPlonkBls12381Proof, GnarkPlonkBls12381Pis) {
+        let fixtures_dir = format!("{}/test_data/gnark_plonk_bls12_381", env!("CARGO_MANIFEST_DIR"));
+        let vkey = GnarkPlonkBls12381Vkey { vkey_bytes: read_bytes_from_file(&format!("{}/vk.bin", fixtures_dir)).unwrap() };
+        let proof = GnarkPlonkBls12381Proof { proof_bytes: read_bytes_from_file(&format!("{}/proof.bin", fixtures_dir)).unwrap() };
+        let pis_bytes = read_bytes_from_file(&format!("{}/pis.json", fixtures_dir)).unwrap();
+        let pis = GnarkPlonkBls12381Pis(serde_json::from_slice(&pis_bytes).unwrap());
+        (vkey, proof, pis)
+    }
+
+    #[test]
+    #[ignore = "needs the gnark fixtures of scripts/gnark_plonk_bls12_381_fixture"]
+    fn test_verify_gnark_plonk_bls12_381_proof() {
+        let (vkey, proof, pis) = read_fixtures();
+        vkey.validate().unwrap();
+        let vk = vkey.get_plonk_vk().unwrap();
+        let public_inputs = pis.get_ark_pis().unwrap();
+        verify_plonk(&vk, &proof.get_plonk_proof().unwrap(), &public_inputs).unwrap();
+
+        assert!(verify_plonk(&vk, &proof.get_plonk_proof().unwrap(), &[ArkFr::from(10u64)]).is_err());
+    }
+
+    #[test]
+    #[ignore = "needs the gnark fixtures of scripts/gnark_plonk_bls12_381_fixture"]
+    fn test_tampered_gnark_plonk_bls12_381_proof_is_rejected() {
+        let (vkey, proof, pis) = read_fixtures();
+        let vk = vkey.get_plonk_vk().unwrap();
+        let public_inputs = pis.get_ark_pis().unwrap();
+
+        let mut tampered_proof = proof.get_plonk_proof().unwrap();
+        tampered_proof.batched_proof.claimed_values[0] += ArkFr::one();
+        assert!(verify_plonk(&vk, &tampered_proof, &public_inputs).is_err());
+
+        let mut tampered_proof = proof.get_plonk_proof().unwrap();
+        tampered_proof.z_shifted_opening.claimed_value += ArkFr::one();
+        assert!(verify_plonk(&vk, &tampered_proof, &public_inputs).is_err());
+
+        let mut tampered_proof = proof.get_plonk_proof().unwrap();
+        tampered_proof.bsb22_commitments[0] = tampered_proof.lro[0];
+        assert!(verify_plonk(&vk, &tampered_proof, &public_inputs).is_err());
+
+        let mut tampered_proof = proof.get_plonk_proof().unwrap();
+        tampered_proof.h.swap(0, 1);
+        assert!(verify_plonk(&vk, &tampered_proof, &public_inputs).is_err());
+    }
+}
@@ -0,0 +1,378 @@
+use crate::traits::{pis::Pis, proof::Proof, vkey::Vkey};
+use agg_core::inputs::compute_combined_vkey_hash;
+use anyhow::anyhow;
+use anyhow::Result as AnyhowResult;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use quantum_utils::error_line;
+use quantum_utils::file::read_bytes_from_file;
+use quantum_utils::file::write_bytes_to_file;
+
+use serde::{Deserialize, Serialize};
+use snark_verifier::halo2_base::halo2_proofs::halo2curves::ff::PrimeField;
+use snark_verifier::halo2_base::halo2_proofs::halo2curves::group::GroupEncoding;
+use snark_verifier::halo2_base::halo2_proofs::halo2curves::pasta::{EqAffine, Fp};
+use snark_verifier::halo2_base::halo2_proofs::transcript::{
+    Blake2bRead, Challenge255, EncodedChallenge, Transcript as Halo2Transcript, TranscriptRead as Halo2TranscriptRead,
+    TranscriptReadBuffer,
+};
+use snark_verifier::loader::native::NativeLoader;
+use snark_verifier::pcs::ipa::{Bgh19, IpaAs, IpaDecidingKey, IpaSuccinctVerifyingKey};
+use snark_verifier::util::transcript::{Transcript, TranscriptRead};
+use snark_verifier::verifier::plonk::{PlonkProtocol, PlonkVerifier};
+use snark_verifier::verifier::SnarkVerifier;
+use snark_verifier::Error as SnarkVerifierError;
+use utils::hash::{Hasher, KeccakHasher};
+
+type Halo2IpaVerifier = PlonkVerifier<IpaAs<EqAffine, Bgh19>>;
+
+const POINT_SIZE: usize = 32;
+
+/*
+    IPA over the vesta curve (`EqAffine`), with `Fp` instances.
+    protocol_bytes: json of the snark-verifier `PlonkProtocol<EqAffine>` compiled from the halo2 vk
+    params_bytes: compressed w || u || g_0 .. g_{n-1} of the halo2 `ParamsIPA`
+ */
+#[derive(Clone, BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, PartialEq)]
+pub struct Halo2IpaVkey {
+    pub protocol_bytes: Vec<u8>,
+    pub params_bytes: Vec<u8>,
+}
+
+pub struct Halo2IpaParams {
+    pub w: EqAffine,
+    pub u: EqAffine,
+    pub g: Vec<EqAffine>,
+}
+
+impl Vkey for Halo2IpaVkey {
+    fn serialize_vkey(&self) -> AnyhowResult<Vec<u8>> {
+        let mut buffer: Vec<u8> = Vec::new();
+        BorshSerialize::serialize(&self, &mut buffer).map_err(|err| anyhow!(error_line!(err)))?;
+        Ok(buffer)
+    }
+
+    fn deserialize_vkey(bytes: &mut &[u8]) -> AnyhowResult<Self> {
+        let key: Halo2IpaVkey =
+            BorshDeserialize::deserialize(bytes).map_err(|err| anyhow!(error_line!(err)))?;
+        Ok(key)
+    }
+
+    fn dump_vk(&self, path: &str) -> AnyhowResult<()> {
+        let vkey_bytes = self.serialize_vkey()?;
+        write_bytes_to_file(&vkey_bytes, path)?;
+        Ok(())
+    }
+
+    fn read_vk(full_path: &str) -> AnyhowResult<Self> {
+        let vkey_bytes = read_bytes_from_file(full_path)?;
+        let vkey = Halo2IpaVkey::deserialize_vkey(&mut vkey_bytes.as_slice())?;
+        Ok(vkey)
+    }
+
+    fn validate(&self) -> AnyhowResult<()> {
+        let protocol = self.get_protocol()?;
+        let params = self.get_params()?;
+        if params.g.len() != protocol.domain.n {
+            return Err(anyhow!(error_line!(format!("halo2-ipa params have {} generators, domain size is {}", params.g.len(), protocol.domain.n))));
+        }
+        Ok(())
+    }
+
+    fn keccak_hash(&self) -> AnyhowResult<[u8; 32]> {
+        let hash = KeccakHasher::hash_out(&[self.protocol_bytes.as_slice(), self.params_bytes.as_slice()].concat());
+        Ok(hash)
+    }
+
+    fn compute_circuit_hash(&self, circuit_verifying_id: [u32; 8]) -> AnyhowResult<[u8; 32]> {
+        let protocol_hash = self.keccak_hash()?;
+        let circuit_hash = compute_combined_vkey_hash::<KeccakHasher>(&protocol_hash, &circuit_verifying_id)?;
+        Ok(circuit_hash)
+    }
+}
+
+impl Halo2IpaVkey {
+    pub fn get_protocol(&self) -> AnyhowResult<PlonkProtocol<EqAffine>> {
+        let protocol: PlonkProtocol<EqAffine> = serde_json::from_slice(&self.protocol_bytes)?;
+        Ok(protocol)
+    }
+
+    pub fn get_params(&self) -> AnyhowResult<Halo2IpaParams> {
+        if self.params_bytes.len() % POINT_SIZE != 0 || self.params_bytes.len() < 3 * POINT_SIZE {
+            return Err(anyhow!(error_line!("halo2-ipa params have an invalid length")));
+        }
+        let mut points = vec![];
+        for chunk in self.params_bytes.chunks(POINT_SIZE) {
+            let mut repr = [0u8; POINT_SIZE];
+            repr.copy_from_slice(chunk);
+            let point: Option<EqAffine> = EqAffine::from_bytes(&repr).into();
+            points.push(point.ok_or(anyhow!(error_line!("halo2-ipa params contain an invalid point")))?);
+        }
+        Ok(Halo2IpaParams { w: points[0], u: points[1], g: points[2..].to_vec() })
+    }
+}
+
+// snark-verifier transcript over halo2's blake2b transcript, the one zcash-style IPA provers write
+struct Blake2bTranscript<R: std::io::Read>(Blake2bRead<R, EqAffine, Challenge255<EqAffine>>);
+
+fn to_transcript_error(e: std::io::Error) -> SnarkVerifierError {
+    SnarkVerifierError::Transcript(e.kind(), e.to_string())
+}
+
+impl<R: std::io::Read> Transcript<EqAffine, NativeLoader> for Blake2bTranscript<R> {
+    fn loader(&self) -> &NativeLoader {
+        &NativeLoader
+    }
+
+    fn squeeze_challenge(&mut self) -> Fp {
+        Halo2Transcript::squeeze_challenge(&mut self.0).get_scalar()
+    }
+
+    fn common_ec_point(&mut self, ec_point: &EqAffine) -> Result<(), SnarkVerifierError> {
+        Halo2Transcript::common_point(&mut self.0, *ec_point).map_err(to_transcript_error)
+    }
+
+    fn common_scalar(&mut self, scalar: &Fp) -> Result<(), SnarkVerifierError> {
+        Halo2Transcript::common_scalar(&mut self.0, *scalar).map_err(to_transcript_error)
+    }
+}
+
+impl<R: std::io::Read> TranscriptRead<EqAffine, NativeLoader> for Blake2bTranscript<R> {
+    fn read_scalar(&mut self) -> Result<Fp, SnarkVerifierError> {
+        Halo2TranscriptRead::read_scalar(&mut self.0).map_err(to_transcript_error)
+    }
+
+    fn read_ec_point(&mut self) -> Result<EqAffine, SnarkVerifierError> {
+        Halo2TranscriptRead::read_point(&mut self.0).map_err(to_transcript_error)
+    }
+}
+
+#[derive(Clone, BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, PartialEq)]
+pub struct Halo2IpaProof {
+    pub proof_bytes: Vec<u8>,
+}
+
+impl Proof for Halo2IpaProof {
+    fn serialize_proof(&self) -> AnyhowResult<Vec<u8>> {
+        let mut buffer: Vec<u8> = Vec::new();
+        BorshSerialize::serialize(&self, &mut buffer)?;
+        Ok(buffer)
+    }
+
+    fn deserialize_proof(bytes: &mut &[u8]) -> AnyhowResult<Self> {
+        let key: Halo2IpaProof =
+            BorshDeserialize::deserialize(bytes).map_err(|err| anyhow!(error_line!(err)))?;
+        Ok(key)
+    }
+
+    fn dump_proof(&self, path: &str) -> AnyhowResult<()> {
+        let proof_bytes = self.serialize_proof()?;
+        write_bytes_to_file(&proof_bytes, path)?;
+        Ok(())
+    }
+
+    fn read_proof(full_path: &str) -> AnyhowResult<Self> {
+        let proof_bytes = read_bytes_from_file(full_path)?;
+        let halo2_proof = Halo2IpaProof::deserialize_proof(&mut proof_bytes.as_slice())?;
+        Ok(halo2_proof)
+    }
+
+    fn validate_proof(&self, vkey_path: &str, mut pis_bytes: &[u8]) -> AnyhowResult<()> {
+        let vkey = Halo2IpaVkey::read_vk(vkey_path)?;
+        let pis = Halo2IpaPis::deserialize_pis(&mut pis_bytes)?;
+
+        let protocol = vkey.get_protocol()?;
+        let params = vkey.get_params()?;
+        let instances = pis.get_instance()?;
+
+        let svk = IpaSuccinctVerifyingKey::new(protocol.domain.clone(), params.g[0], params.u, Some(params.w));
+        let dk = IpaDecidingKey::new(svk, params.g);
+        let loader = NativeLoader;
+        let protocol = protocol.loaded(&loader);
+        let mut transcript = Blake2bTranscript(Blake2bRead::init(self.proof_bytes.as_slice()));
+
+        let proof_ = Halo2IpaVerifier::read_proof(&dk, &protocol, &instances, &mut transcript).map_err(|e| {anyhow!(error_line!(format!("error in halo2-ipa proof validation {:?}", e)))})?;
+        Halo2IpaVerifier::verify(&dk, &protocol, &instances, &proof_).map_err(|e| {anyhow!(error_line!(format!("Halo2Ipa proof validation failed: {:?}", e)))})?;
+        Ok(())
+    }
+
+    fn get_proof_bytes(&self) -> AnyhowResult<Vec<u8>> {
+        Ok(self.proof_bytes.clone())
+    }
+}
+
+// decimal `Fp` instances, one vector per instance column
+#[derive(Clone, BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, PartialEq)]
+pub struct Halo2IpaPis(pub Vec<Vec<String>>);
+
+impl Pis for Halo2IpaPis {
+    fn serialize_pis(&self) -> AnyhowResult<Vec<u8>> {
+        let mut buffer: Vec<u8> = Vec::new();
+        BorshSerialize::serialize(&self, &mut buffer)?;
+        Ok(buffer)
+    }
+
+    fn deserialize_pis(bytes: &mut &[u8]) -> AnyhowResult<Self> {
+        let key: Halo2IpaPis =
+            BorshDeserialize::deserialize(bytes).map_err(|err| anyhow!(error_line!(err)))?;
+        Ok(key)
+    }
+
+    fn dump_pis(&self, path: &str) -> AnyhowResult<()> {
+        let pis_bytes = self.serialize_pis()?;
+        write_bytes_to_file(&pis_bytes, path)?;
+        Ok(())
+    }
+
+    fn read_pis(full_path: &str) -> AnyhowResult<Self> {
+        let pis_bytes = read_bytes_from_file(full_path)?;
+        let halo2_pis = Halo2IpaPis::deserialize_pis(&mut pis_bytes.as_slice())?;
+        Ok(halo2_pis)
+    }
+
+    // keccak of the little endian encoded instances
+    fn keccak_hash(&self) -> AnyhowResult<[u8; 32]> {
+        let instances_bytes = self.get_instance_bytes()?.concat().concat();
+        Ok(KeccakHasher::hash_out(&instances_bytes))
+    }
+
+    fn get_data(&self) -> AnyhowResult<Vec<String>> {
+        Ok(self.0.concat())
+    }
+}
+
+impl Halo2IpaPis {
+    pub fn get_instance(&self) -> AnyhowResult<Vec<Vec<Fp>>> {
+        let mut instances = vec![];
+        for column in &self.0 {
+            let mut values = vec![];
+            for value in column {
+                values.push(Fp::from_str_vartime(value).ok_or(anyhow!(error_line!("failed to form instances from halo2-ipa pis")))?);
+            }
+            instances.push(values);
+        }
+        Ok(instances)
+    }
+
+    pub fn get_instance_bytes(&self) -> AnyhowResult<Vec<Vec<[u8; 32]>>> {
+        let instances = self.get_instance()?;
+        Ok(instances.iter().map(|column| column.iter().map(|value| value.to_repr()).collect()).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand_chacha::{rand_core::SeedableRng, ChaCha20Rng};
+    use snark_verifier::halo2_base::halo2_proofs::circuit::{Layouter, SimpleFloorPlanner, Value};
+    use snark_verifier::halo2_base::halo2_proofs::plonk::{
+        create_proof, keygen_pk, keygen_vk, Advice, Circuit, Column, ConstraintSystem, Error as PlonkError, Instance, Selector,
+    };
+    use snark_verifier::halo2_base::halo2_proofs::poly::commitment::{Params, ParamsProver};
+    use snark_verifier::halo2_base::halo2_proofs::poly::ipa::commitment::{IPACommitmentScheme, ParamsIPA};
+    use snark_verifier::halo2_base::halo2_proofs::poly::ipa::multiopen::ProverIPA;
+    use snark_verifier::halo2_base::halo2_proofs::poly::Rotation;
+    use snark_verifier::halo2_base::halo2_proofs::transcript::{Blake2bWrite, TranscriptWriterBuffer};
+    use snark_verifier::system::halo2::{compile, Config};
+
+    const K: u32 = 4;
+
+    #[derive(Clone)]
+    struct SquareConfig {
+        advice: Column<Advice>,
+        instance: Column<Instance>,
+        selector: Selector,
+    }
+
+    // knowledge of x with x * x = y, y public
+    #[derive(Default)]
+    struct SquareCircuit {
+        x: Value<Fp>,
+    }
+
+    impl Circuit<Fp> for SquareCircuit {
+        type Config = SquareConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> SquareConfig {
+            let advice = meta.advice_column();
+            let instance = meta.instance_column();
+            let selector = meta.selector();
+            meta.enable_equality(advice);
+            meta.enable_equality(instance);
+            meta.create_gate("square", |meta| {
+                let s = meta.query_selector(selector);
+                let x = meta.query_advice(advice, Rotation::cur());
+                let y = meta.query_advice(advice, Rotation::next());
+                vec![s * (x.clone() * x - y)]
+            });
+            SquareConfig { advice, instance, selector }
+        }
+
+        fn synthesize(&self, config: SquareConfig, mut layouter: impl Layouter<Fp>) -> Result<(), PlonkError> {
+            let y = layouter.assign_region(|| "square", |mut region| {
+                config.selector.enable(&mut region, 0)?;
+                region.assign_advice(|| "x", config.advice, 0, || self.x)?;
+                region.assign_advice(|| "y", config.advice, 1, || self.x * self.x)
+            })?;
+            layouter.constrain_instance(y.cell(), config.instance, 0)
+        }
+    }
+
+    // `ParamsIPA::write` is k || g || g_lagrange || w || u, the vkey takes w || u || g
+    fn get_params_bytes(params: &ParamsIPA<EqAffine>) -> Vec<u8> {
+        let mut written = vec![];
+        params.write(&mut written).unwrap();
+        let n = 1usize << params.k();
+        let g = &written[4..4 + n * POINT_SIZE];
+        let w_u = &written[4 + 2 * n * POINT_SIZE..];
+        [w_u, g].concat()
+    }
+
+    #[test]
+    fn test_validate_halo2_ipa_proof() {
+        let params = ParamsIPA::<EqAffine>::new(K);
+        let vk = keygen_vk(&params, &SquareCircuit::default()).unwrap();
+        let pk = keygen_pk(&params, vk, &SquareCircuit::default()).unwrap();
+
+        let instance = vec![Fp::from(9u64)];
+        let mut transcript = Blake2bWrite::<Vec<u8>, EqAffine, Challenge255<EqAffine>>::init(vec![]);
+        create_proof::<IPACommitmentScheme<EqAffine>, ProverIPA<EqAffine>, _, _, _, _>(
+            &params,
+            &pk,
+            &[SquareCircuit { x: Value::known(Fp::from(3u64)) }],
+            &[&[instance.as_slice()]],
+            ChaCha20Rng::seed_from_u64(0),
+            &mut transcript,
+        )
+        .unwrap();
+        let proof = Halo2IpaProof { proof_bytes: transcript.finalize() };
+
+        let protocol = compile(&params, pk.get_vk(), Config::ipa().with_num_instance(vec![1]));
+        let vkey = Halo2IpaVkey {
+            protocol_bytes: serde_json::to_vec(&protocol).unwrap(),
+            params_bytes: get_params_bytes(&params),
+        };
+        vkey.validate().unwrap();
+        // params of another size do not match the domain of the protocol
+        let mismatched_vkey = Halo2IpaVkey {
+            protocol_bytes: vkey.protocol_bytes.clone(),
+            params_bytes: get_params_bytes(&ParamsIPA::<EqAffine>::new(K + 1)),
+        };
+        assert!(mismatched_vkey.validate().is_err());
+        let vk_file = tempfile::NamedTempFile::new().unwrap();
+        let vk_path = vk_file.path().to_str().unwrap();
+        vkey.dump_vk(vk_path).unwrap();
+
+        let pis = Halo2IpaPis(vec![vec![String::from("9")]]);
+        proof.validate_proof(vk_path, &pis.serialize_pis().unwrap()).unwrap();
+
+        let wrong_pis = Halo2IpaPis(vec![vec![String::from("10")]]);
+        assert!(proof.validate_proof(vk_path, &wrong_pis.serialize_pis().unwrap()).is_err());
+    }
+}
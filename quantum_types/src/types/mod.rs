@@ -3,12 +3,21 @@ pub mod config;
 pub mod db;
 pub mod gnark_groth16;
 pub mod halo2_plonk;
+pub mod halo2_ipa;
 pub mod snarkjs_groth16;
+pub mod ark_groth16;
 pub mod imt;
 pub mod hash;
 pub mod gnark_plonk;
+pub mod gnark_bls12_381;
+pub mod gnark_groth16_bls12_381;
+pub mod gnark_plonk_bls12_381;
 pub mod halo2_poseidon;
 pub mod plonk2;
+pub mod plonky3;
+pub mod plonky3_air;
 pub mod riscs0;
 pub mod sp1;
-pub mod nitro_att;
\ No newline at end of file
+pub mod sp1_wrapped;
+pub mod nitro_att;
+pub mod error;
\ No newline at end of file
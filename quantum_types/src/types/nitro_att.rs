@@ -1,19 +1,114 @@
+use std::{io::Read, time::{SystemTime, UNIX_EPOCH}};
+
 use agg_core::inputs::compute_combined_vkey_hash;
 use anyhow::{anyhow, Result as AnyhowResult};
 use borsh::{BorshDeserialize, BorshSerialize};
-use oyster::attestation::AttestationExpectations;
+use oyster::attestation::{AttestationDecoded, AttestationExpectations};
 use quantum_utils::{
     error_line,
     file::{read_bytes_from_file, write_bytes_to_file},
 };
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use utils::hash::{Hasher, KeccakHasher};
 
 use crate::traits::{pis::Pis, proof::Proof, vkey::Vkey};
 
-#[derive(Clone, BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
+const PCR_LENGTH: usize = 48;
+
+// raw P-384 public key of the AWS Nitro Enclaves root certificate
+const AWS_NITRO_ROOT_PUBLIC_KEY: &str = "fc0254eba608c1f36870e29ada90be46383292736e894bfff672d989444b5051e534a4b1f6dbe3c0bc581a32b7b176070ede12d69a3fea211b66e752cf7dd1dd095f6f1370f4170843d9dc100121e4cf63012809664487c9796284304dc53ff4";
+
+/*
+    What an attestation document must satisfy, on top of PCR0 and the AWS Nitro root, to be accepted under the circuit.
+    Every constraint is optional. `root_public_key_fingerprint` is the sha256 of the root public key the certificate
+    chain ends in, an extra check on top of the AWS root one. `user_data_prefix` lets a protocol bind a domain tag or nonce.
+    The policy is only checked off-chain, the leaf vkey hash stays the PCR0 one the aggregation guest expects.
+ */
+#[derive(Clone, Default, BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, PartialEq)]
+pub struct NitroAttPolicy {
+    pub pcr1_bytes: Option<Vec<u8>>,
+    pub pcr2_bytes: Option<Vec<u8>>,
+    pub root_public_key_fingerprint: Option<Vec<u8>>,
+    pub max_age_secs: Option<u64>,
+    pub user_data_prefix: Option<Vec<u8>>,
+}
+
+#[derive(Clone, BorshSerialize, Serialize, Deserialize)]
 pub struct NitroAttVkey {
     pub pcr0_bytes: Vec<u8>,
+    #[serde(default)]
+    pub policy: NitroAttPolicy,
+}
+
+impl BorshDeserialize for NitroAttVkey {
+    fn deserialize_reader<R: Read>(reader: &mut R) -> std::io::Result<Self> {
+        let pcr0_bytes = Vec::<u8>::deserialize_reader(reader)?;
+        // vkeys serialised before the policy was added end here, they only pin PCR0
+        let mut policy_bytes = vec![];
+        reader.read_to_end(&mut policy_bytes)?;
+        let policy = match policy_bytes.is_empty() {
+            true => NitroAttPolicy::default(),
+            false => NitroAttPolicy::deserialize(&mut policy_bytes.as_slice())?,
+        };
+        Ok(NitroAttVkey { pcr0_bytes, policy })
+    }
+}
+
+fn to_pcr(pcr_bytes: &[u8]) -> AnyhowResult<[u8; PCR_LENGTH]> {
+    pcr_bytes.try_into().map_err(|_| anyhow!(error_line!("invalid PCR bytes length")))
+}
+
+impl NitroAttVkey {
+    // Verifies the document signature and a certificate chain ending in the AWS Nitro root, then the policy
+    pub fn verify_attestation(&self, att_doc_bytes: &[u8]) -> AnyhowResult<AttestationDecoded> {
+        let aws_root_public_key = hex::decode(AWS_NITRO_ROOT_PUBLIC_KEY)?;
+        let pcrs = match (&self.policy.pcr1_bytes, &self.policy.pcr2_bytes) {
+            (Some(pcr1), Some(pcr2)) => Some([to_pcr(&self.pcr0_bytes)?, to_pcr(pcr1)?, to_pcr(pcr2)?]),
+            _ => None,
+        };
+        let expectations = AttestationExpectations {
+            pcrs,
+            root_public_key: Some(aws_root_public_key.as_slice()),
+            ..Default::default()
+        };
+        let att_decoded = oyster::attestation::verify(att_doc_bytes, expectations)?;
+        self.check_attestation(&att_decoded)?;
+        Ok(att_decoded)
+    }
+
+    pub fn check_attestation(&self, att_decoded: &AttestationDecoded) -> AnyhowResult<()> {
+        let pcrs = [Some(&self.pcr0_bytes), self.policy.pcr1_bytes.as_ref(), self.policy.pcr2_bytes.as_ref()];
+        for (i, expected_pcr) in pcrs.iter().enumerate() {
+            if let Some(expected_pcr) = expected_pcr {
+                if att_decoded.pcrs[i].as_slice() != expected_pcr.as_slice() {
+                    return Err(anyhow!(error_line!(format!("attestation PCR{} does not match the registered one", i))));
+                }
+            }
+        }
+
+        if let Some(fingerprint) = &self.policy.root_public_key_fingerprint {
+            let root_fingerprint = Sha256::digest(&att_decoded.root_public_key);
+            if root_fingerprint.as_slice() != fingerprint.as_slice() {
+                return Err(anyhow!(error_line!("attestation root public key does not match the registered fingerprint")));
+            }
+        }
+
+        if let Some(max_age_secs) = self.policy.max_age_secs {
+            let now_ms = SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis() as u64;
+            let age_ms = now_ms.saturating_sub(att_decoded.timestamp as u64);
+            if age_ms > max_age_secs.saturating_mul(1000) {
+                return Err(anyhow!(error_line!(format!("attestation is {}ms old, at most {}s is allowed", age_ms, max_age_secs))));
+            }
+        }
+
+        if let Some(user_data_prefix) = &self.policy.user_data_prefix {
+            if !att_decoded.user_data.starts_with(user_data_prefix) {
+                return Err(anyhow!(error_line!("attestation user data does not carry the registered prefix")));
+            }
+        }
+        Ok(())
+    }
 }
 
 impl Vkey for NitroAttVkey {
@@ -42,16 +137,29 @@ impl Vkey for NitroAttVkey {
     }
 
     fn validate(&self) -> AnyhowResult<()> {
-        if self.pcr0_bytes.len() != 48 {
+        if self.pcr0_bytes.len() != PCR_LENGTH {
             return Err(anyhow!("Invalid PCR0 bytes length"));
         }
+        if self.policy.pcr1_bytes.as_ref().is_some_and(|pcr| pcr.len() != PCR_LENGTH) {
+            return Err(anyhow!("Invalid PCR1 bytes length"));
+        }
+        if self.policy.pcr2_bytes.as_ref().is_some_and(|pcr| pcr.len() != PCR_LENGTH) {
+            return Err(anyhow!("Invalid PCR2 bytes length"));
+        }
+        if self.policy.root_public_key_fingerprint.as_ref().is_some_and(|fingerprint| fingerprint.len() != 32) {
+            return Err(anyhow!("Invalid root public key fingerprint length"));
+        }
+        if self.policy.max_age_secs == Some(0) {
+            return Err(anyhow!("Invalid attestation max age"));
+        }
         Ok(())
     }
 
     fn keccak_hash(&self) -> AnyhowResult<[u8; 32]> {
-        Ok(self.pcr0_bytes[..32]
+        let pcr0_hash: [u8; 32] = self.pcr0_bytes[..32]
             .try_into()
-            .map_err(|e| anyhow!("invalid pcr0_bytes, {}", e))?)
+            .map_err(|e| anyhow!("invalid pcr0_bytes, {}", e))?;
+        Ok(pcr0_hash)
     }
 
     fn compute_circuit_hash(&self, circuit_verifying_id: [u32; 8]) -> AnyhowResult<[u8; 32]> {
@@ -60,6 +168,15 @@ impl Vkey for NitroAttVkey {
             compute_combined_vkey_hash::<KeccakHasher>(&protocol_hash, &circuit_verifying_id)?;
         Ok(circuit_hash)
     }
+
+    fn policy_hash(&self) -> AnyhowResult<Option<[u8; 32]>> {
+        if self.policy == NitroAttPolicy::default() {
+            return Ok(None);
+        }
+        let mut policy_bytes: Vec<u8> = Vec::new();
+        BorshSerialize::serialize(&self.policy, &mut policy_bytes).map_err(|err| anyhow!(error_line!(err)))?;
+        Ok(Some(KeccakHasher::hash_out(&policy_bytes)))
+    }
 }
 
 #[derive(Clone, BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug)]
@@ -93,8 +210,8 @@ impl Proof for NitroAttProof {
     }
 
     fn validate_proof(&self, vkey_path: &str, mut _pis_bytes: &[u8]) -> AnyhowResult<()> {
-        let _ =
-            oyster::attestation::verify(&self.att_doc_bytes, AttestationExpectations::default())?;
+        let vkey = NitroAttVkey::read_vk(vkey_path)?;
+        vkey.verify_attestation(&self.att_doc_bytes)?;
         Ok(())
     }
 
@@ -104,9 +221,8 @@ impl Proof for NitroAttProof {
 }
 
 impl NitroAttProof {
-    pub fn get_pis(&self) -> AnyhowResult<Vec<u8>> {
-        let att_decoded =
-            oyster::attestation::verify(&self.att_doc_bytes, AttestationExpectations::default())?;
+    pub fn get_pis(&self, vkey: &NitroAttVkey) -> AnyhowResult<Vec<u8>> {
+        let att_decoded = vkey.verify_attestation(&self.att_doc_bytes)?;
 
         let mut pis_bytes = vec![];
 
@@ -167,3 +283,105 @@ impl Pis for NitroAttPis {
         Ok(self.0.clone())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use aws_nitro_enclaves_cose::{crypto::Openssl, header_map::HeaderMap, CoseSign1};
+    use aws_nitro_enclaves_nsm_api::api::{AttestationDoc, Digest as NsmDigest};
+    use openssl::pkey::PKey;
+    use rcgen::{BasicConstraints, CertificateParams, IsCa, KeyPair, PKCS_ECDSA_P384_SHA384};
+
+    use super::*;
+
+    const PCRS: [[u8; PCR_LENGTH]; 3] = [[1u8; PCR_LENGTH], [2u8; PCR_LENGTH], [3u8; PCR_LENGTH]];
+
+    // attestation doc signed through a self-signed P-384 root, i.e. a chain that does not end in AWS
+    fn get_self_signed_attestation() -> Vec<u8> {
+        let root_key = KeyPair::generate_for(&PKCS_ECDSA_P384_SHA384).unwrap();
+        let mut root_params = CertificateParams::new(vec![]).unwrap();
+        root_params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+        let root_cert = root_params.self_signed(&root_key).unwrap();
+
+        let leaf_key = KeyPair::generate_for(&PKCS_ECDSA_P384_SHA384).unwrap();
+        let leaf_cert = CertificateParams::new(vec![]).unwrap().signed_by(&leaf_key, &root_cert, &root_key).unwrap();
+
+        let pcrs: BTreeMap<usize, Vec<u8>> = PCRS.iter().enumerate().map(|(i, pcr)| (i, pcr.to_vec())).collect();
+        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis() as u64;
+        let att_doc = AttestationDoc::new(
+            "i-test-enc".to_string(),
+            NsmDigest::SHA384,
+            timestamp,
+            pcrs,
+            leaf_cert.der().to_vec(),
+            vec![root_cert.der().to_vec()],
+            Some(b"quantum:test".to_vec()),
+            None,
+            Some(vec![4u8; 32]),
+        );
+
+        let signing_key = PKey::private_key_from_pem(leaf_key.serialize_pem().as_bytes()).unwrap();
+        CoseSign1::new::<Openssl>(&att_doc.to_binary(), &HeaderMap::new(), &signing_key)
+            .unwrap()
+            .as_bytes(false)
+            .unwrap()
+    }
+
+    fn get_vkey(policy: NitroAttPolicy) -> NitroAttVkey {
+        NitroAttVkey { pcr0_bytes: PCRS[0].to_vec(), policy }
+    }
+
+    #[test]
+    fn test_chain_not_ending_in_aws_root_is_rejected() {
+        let att_doc_bytes = get_self_signed_attestation();
+        let att_decoded = oyster::attestation::verify(&att_doc_bytes, AttestationExpectations::default()).unwrap();
+        let policy = NitroAttPolicy { pcr1_bytes: Some(PCRS[1].to_vec()), pcr2_bytes: Some(PCRS[2].to_vec()), ..Default::default() };
+
+        assert!(get_vkey(NitroAttPolicy::default()).verify_attestation(&att_doc_bytes).is_err());
+        assert!(get_vkey(policy.clone()).verify_attestation(&att_doc_bytes).is_err());
+        // the fingerprint of the chain's own root does not replace the AWS root check
+        let fingerprint_policy = NitroAttPolicy { root_public_key_fingerprint: Some(Sha256::digest(&att_decoded.root_public_key).to_vec()), ..policy };
+        assert!(get_vkey(fingerprint_policy).verify_attestation(&att_doc_bytes).is_err());
+    }
+
+    #[test]
+    fn test_attestation_policy_checks() {
+        let att_doc_bytes = get_self_signed_attestation();
+        let att_decoded = oyster::attestation::verify(&att_doc_bytes, AttestationExpectations::default()).unwrap();
+        let policy = NitroAttPolicy {
+            pcr1_bytes: Some(PCRS[1].to_vec()),
+            pcr2_bytes: Some(PCRS[2].to_vec()),
+            root_public_key_fingerprint: Some(Sha256::digest(&att_decoded.root_public_key).to_vec()),
+            max_age_secs: Some(60),
+            user_data_prefix: Some(b"quantum:".to_vec()),
+        };
+
+        let vkey = get_vkey(policy.clone());
+        vkey.validate().unwrap();
+        vkey.check_attestation(&att_decoded).unwrap();
+
+        let wrong_pcr_vkey = get_vkey(NitroAttPolicy { pcr2_bytes: Some(PCRS[1].to_vec()), ..policy.clone() });
+        assert!(wrong_pcr_vkey.check_attestation(&att_decoded).is_err());
+
+        let wrong_root_vkey = get_vkey(NitroAttPolicy { root_public_key_fingerprint: Some(vec![0u8; 32]), ..policy.clone() });
+        assert!(wrong_root_vkey.check_attestation(&att_decoded).is_err());
+
+        let wrong_prefix_vkey = get_vkey(NitroAttPolicy { user_data_prefix: Some(b"other:".to_vec()), ..policy });
+        assert!(wrong_prefix_vkey.check_attestation(&att_decoded).is_err());
+    }
+
+    #[test]
+    fn test_policy_does_not_change_leaf_hash() {
+        let default_vkey = get_vkey(NitroAttPolicy::default());
+        assert_eq!(default_vkey.keccak_hash().unwrap().as_slice(), &PCRS[0][..32]);
+        assert_eq!(default_vkey.policy_hash().unwrap(), None);
+
+        let pinned_vkey = get_vkey(NitroAttPolicy { pcr1_bytes: Some(PCRS[1].to_vec()), ..Default::default() });
+        let other_vkey = get_vkey(NitroAttPolicy { pcr1_bytes: Some(PCRS[2].to_vec()), ..Default::default() });
+        assert_eq!(pinned_vkey.keccak_hash().unwrap(), default_vkey.keccak_hash().unwrap());
+        assert_eq!(pinned_vkey.compute_circuit_hash([0u32; 8]).unwrap(), default_vkey.compute_circuit_hash([0u32; 8]).unwrap());
+        assert!(pinned_vkey.policy_hash().unwrap().is_some());
+        assert_ne!(pinned_vkey.policy_hash().unwrap(), other_vkey.policy_hash().unwrap());
+    }
+}
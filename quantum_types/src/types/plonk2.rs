@@ -1,24 +1,69 @@
-use std::str::FromStr;
+use std::{io::Read, str::FromStr};
 
 use agg_core::inputs::compute_combined_vkey_hash;
 use borsh::{BorshDeserialize, BorshSerialize};
 use num_bigint::BigUint;
-use plonky2::{field::{goldilocks_field::GoldilocksField, types::{Field, PrimeField}}, plonk::{circuit_data::{CommonCircuitData, VerifierCircuitData, VerifierOnlyCircuitData}, config::PoseidonGoldilocksConfig, proof::ProofWithPublicInputs}, util::serialization::DefaultGateSerializer};
+use plonky2::{field::{goldilocks_field::GoldilocksField, types::{Field, PrimeField}}, plonk::{circuit_data::{CommonCircuitData, VerifierCircuitData, VerifierOnlyCircuitData}, config::{GenericConfig, KeccakGoldilocksConfig, PoseidonGoldilocksConfig}, proof::ProofWithPublicInputs}, util::serialization::DefaultGateSerializer};
 use plonky2_core::utils::{plonky2_public_inputs_hash, plonky2_vkey_hash};
 use quantum_utils::{error_line, file::{read_bytes_from_file, write_bytes_to_file}};
 use serde::{Deserialize, Serialize};
-use utils::hash::KeccakHasher;
+use utils::hash::{Hasher, KeccakHasher};
 use anyhow::{anyhow, Result as AnyhowResult};
 use crate::traits::{pis::Pis, proof::Proof, vkey::Vkey};
 
-type C = PoseidonGoldilocksConfig;
 type F = GoldilocksField;
 const D: usize = 2;
 
-#[derive(Clone, BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, PartialEq)]
+// `GenericConfig` the circuit was built with, all plonky2 code paths dispatch on it
+#[derive(Clone, Copy, Default, BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, PartialEq)]
+pub enum Plonky2Config {
+    #[default]
+    PoseidonGoldilocks,
+    KeccakGoldilocks,
+}
+
+impl Plonky2Config {
+    pub fn as_u8(&self) -> u8 {
+        match self {
+            Plonky2Config::PoseidonGoldilocks => 0,
+            Plonky2Config::KeccakGoldilocks => 1,
+        }
+    }
+}
+
+impl TryFrom<u8> for Plonky2Config {
+    type Error = anyhow::Error;
+
+    fn try_from(value: u8) -> AnyhowResult<Self> {
+        match value {
+            0 => Ok(Plonky2Config::PoseidonGoldilocks),
+            1 => Ok(Plonky2Config::KeccakGoldilocks),
+            _ => Err(anyhow!(error_line!(format!("invalid plonky2 config: {}", value)))),
+        }
+    }
+}
+
+#[derive(Clone, BorshSerialize, Serialize, Deserialize, Debug, PartialEq)]
 pub struct Plonky2Vkey {
     pub common_bytes: Vec<u8>,
     pub verifier_only_bytes: Vec<u8>,
+    #[serde(default)]
+    pub config: Plonky2Config,
+}
+
+impl BorshDeserialize for Plonky2Vkey {
+    fn deserialize_reader<R: Read>(reader: &mut R) -> std::io::Result<Self> {
+        let common_bytes = Vec::<u8>::deserialize_reader(reader)?;
+        let verifier_only_bytes = Vec::<u8>::deserialize_reader(reader)?;
+        // vkeys serialised before the config was added end here, they are all poseidon
+        let mut config_tag = [0u8; 1];
+        let config = match reader.read(&mut config_tag)? {
+            0 => Plonky2Config::default(),
+            _ => Plonky2Config::try_from(config_tag[0])
+                .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e.to_string()))?,
+        };
+        Ok(Plonky2Vkey { common_bytes, verifier_only_bytes, config })
+    }
 }
 
 impl Vkey for Plonky2Vkey {
@@ -48,13 +93,25 @@ impl Vkey for Plonky2Vkey {
 
     fn validate(&self) -> AnyhowResult<()> {
         self.get_common_circuit_data()?;
-        self.get_verifier_only()?;
+        match self.config {
+            Plonky2Config::PoseidonGoldilocks => { self.get_verifier_only::<PoseidonGoldilocksConfig>()?; }
+            Plonky2Config::KeccakGoldilocks => { self.get_verifier_only::<KeccakGoldilocksConfig>()?; }
+        }
         Ok(())
     }
 
     fn keccak_hash(&self) -> AnyhowResult<[u8; 32]> {
-        let verifier_only = self.get_verifier_only()?;
-        let hash = plonky2_vkey_hash(&verifier_only);
+        let hash = match self.config {
+            Plonky2Config::PoseidonGoldilocks => {
+                let verifier_only = self.get_verifier_only::<PoseidonGoldilocksConfig>()?;
+                plonky2_vkey_hash(&verifier_only)
+            }
+            // the keccak circuit digest is not goldilocks elements, hash the serialised verifier data with the config
+            Plonky2Config::KeccakGoldilocks => {
+                self.get_verifier_only::<KeccakGoldilocksConfig>()?;
+                KeccakHasher::hash_out(&[self.verifier_only_bytes.as_slice(), &[self.config.as_u8()]].concat())
+            }
+        };
         Ok(hash)
     }
 
@@ -66,7 +123,7 @@ impl Vkey for Plonky2Vkey {
 }
 
 impl Plonky2Vkey {
-    pub fn get_verifier_only(&self) -> AnyhowResult<VerifierOnlyCircuitData<PoseidonGoldilocksConfig, 2>> {
+    pub fn get_verifier_only<C: GenericConfig<D, F = F>>(&self) -> AnyhowResult<VerifierOnlyCircuitData<C, D>> {
         let verifier_only = VerifierOnlyCircuitData::<C, D>::from_bytes(self.verifier_only_bytes.clone()).map_err(|err| anyhow!(error_line!(err)))?;
         Ok(verifier_only)
     }
@@ -76,8 +133,8 @@ impl Plonky2Vkey {
         Ok(common)
     }
 
-    pub fn get_verifier(&self) ->AnyhowResult<VerifierCircuitData<GoldilocksField, PoseidonGoldilocksConfig, 2>> {
-        let verifier_only = self.get_verifier_only()?;
+    pub fn get_verifier<C: GenericConfig<D, F = F>>(&self) -> AnyhowResult<VerifierCircuitData<GoldilocksField, C, 2>> {
+        let verifier_only = self.get_verifier_only::<C>()?;
         let common_circuit_data = self.get_common_circuit_data()?;
         
         let verifier = VerifierCircuitData {
@@ -123,13 +180,10 @@ impl Proof for Plonky2Proof {
     
     fn validate_proof(&self, vkey_path: &str,mut _pis_bytes: &[u8]) -> AnyhowResult<()> {
         let vkey = Plonky2Vkey::read_vk(vkey_path)?;
-        let common_circuit_data = vkey.get_common_circuit_data()?;
-        let proof_with_pis = self.get_proof_with_pis(&common_circuit_data)?;
-        
-        // let pis = proof_with_pis.public_inputs;
-        let verifier = vkey.get_verifier()?;
-        verifier.verify(proof_with_pis)?;
-        Ok(())
+        match vkey.config {
+            Plonky2Config::PoseidonGoldilocks => self.verify_with_config::<PoseidonGoldilocksConfig>(&vkey),
+            Plonky2Config::KeccakGoldilocks => self.verify_with_config::<KeccakGoldilocksConfig>(&vkey),
+        }
     }
     
     fn get_proof_bytes(&self) -> AnyhowResult<Vec<u8>> {
@@ -138,19 +192,35 @@ impl Proof for Plonky2Proof {
 }
 
 impl Plonky2Proof {
-    pub fn get_proof_with_pis(&self, common_circuit_data: &CommonCircuitData<GoldilocksField, 2> ) -> AnyhowResult<ProofWithPublicInputs<GoldilocksField, PoseidonGoldilocksConfig, 2>>{
+    pub fn get_proof_with_pis<C: GenericConfig<D, F = F>>(&self, common_circuit_data: &CommonCircuitData<GoldilocksField, 2> ) -> AnyhowResult<ProofWithPublicInputs<GoldilocksField, C, 2>>{
         let proof_with_pis = ProofWithPublicInputs::from_bytes(self.proof_bytes.clone(), common_circuit_data).map_err(|err| anyhow!(error_line!(err)))?;
         Ok(proof_with_pis)
     }
 
+    fn verify_with_config<C: GenericConfig<D, F = F>>(&self, vkey: &Plonky2Vkey) -> AnyhowResult<()> {
+        let common_circuit_data = vkey.get_common_circuit_data()?;
+        let proof_with_pis = self.get_proof_with_pis::<C>(&common_circuit_data)?;
+        let verifier = vkey.get_verifier::<C>()?;
+        verifier.verify(proof_with_pis)?;
+        Ok(())
+    }
+
+    fn get_public_inputs<C: GenericConfig<D, F = F>>(&self, vkey: &Plonky2Vkey) -> AnyhowResult<Vec<GoldilocksField>> {
+        let common_circuit_data = vkey.get_common_circuit_data()?;
+        let proof_with_pis = self.get_proof_with_pis::<C>(&common_circuit_data)?;
+        Ok(proof_with_pis.public_inputs)
+    }
+
     pub fn get_pis_strings(&self, vkey_path: &str) -> AnyhowResult<Vec<String>> {
         let vkey = Plonky2Vkey::read_vk(vkey_path)?;
-        let common_circuit_data = vkey.get_common_circuit_data()?;
-        let proof_with_pis = self.get_proof_with_pis(&common_circuit_data)?;
+        let public_inputs = match vkey.config {
+            Plonky2Config::PoseidonGoldilocks => self.get_public_inputs::<PoseidonGoldilocksConfig>(&vkey)?,
+            Plonky2Config::KeccakGoldilocks => self.get_public_inputs::<KeccakGoldilocksConfig>(&vkey)?,
+        };
 
         let mut pis = vec![];
 
-        for p in proof_with_pis.public_inputs {
+        for p in public_inputs {
             pis.push(p.to_canonical_biguint().to_string());
         }
         Ok(pis)
@@ -209,4 +279,39 @@ impl Pis for Plonky2Pis {
 //         let prof_with_pis = 
 
 //     }
-// }
\ No newline at end of file
+// }
+
+#[cfg(test)]
+mod tests {
+    use borsh::{BorshDeserialize, BorshSerialize};
+
+    use super::{Plonky2Config, Plonky2Vkey};
+
+    #[derive(BorshSerialize)]
+    struct LegacyPlonky2Vkey {
+        common_bytes: Vec<u8>,
+        verifier_only_bytes: Vec<u8>,
+    }
+
+    #[test]
+    pub fn legacy_vkey_defaults_to_poseidon() {
+        let legacy = LegacyPlonky2Vkey { common_bytes: vec![1, 2], verifier_only_bytes: vec![3] };
+        let mut buffer: Vec<u8> = Vec::new();
+        legacy.serialize(&mut buffer).unwrap();
+
+        let vkey = Plonky2Vkey::deserialize(&mut buffer.as_slice()).unwrap();
+        assert_eq!(vkey.common_bytes, vec![1, 2]);
+        assert_eq!(vkey.verifier_only_bytes, vec![3]);
+        assert_eq!(vkey.config, Plonky2Config::PoseidonGoldilocks);
+    }
+
+    #[test]
+    pub fn config_roundtrip() {
+        let vkey = Plonky2Vkey { common_bytes: vec![1], verifier_only_bytes: vec![2], config: Plonky2Config::KeccakGoldilocks };
+        let mut buffer: Vec<u8> = Vec::new();
+        vkey.serialize(&mut buffer).unwrap();
+
+        let re_vkey = Plonky2Vkey::deserialize(&mut buffer.as_slice()).unwrap();
+        assert_eq!(vkey, re_vkey);
+    }
+}
@@ -0,0 +1,360 @@
+use agg_core::inputs::compute_combined_vkey_hash;
+use anyhow::{anyhow, Result as AnyhowResult};
+use borsh::{BorshDeserialize, BorshSerialize};
+use p3_baby_bear::{BabyBear, DiffusionMatrixBabyBear};
+use p3_challenger::{DuplexChallenger, HashChallenger, SerializingChallenger32, SerializingChallenger64};
+use p3_commit::ExtensionMmcs;
+use p3_dft::Radix2DitParallel;
+use p3_field::{extension::BinomialExtensionField, AbstractField, Field, PrimeField64};
+use p3_fri::{FriConfig, TwoAdicFriPcs};
+use p3_goldilocks::Goldilocks;
+use p3_keccak::Keccak256Hash;
+use p3_merkle_tree::FieldMerkleTreeMmcs;
+use p3_poseidon2::{Poseidon2, Poseidon2ExternalMatrixGeneral};
+use p3_symmetric::{CompressionFunctionFromHasher, PaddingFreeSponge, SerializingHasher32, SerializingHasher64, TruncatedPermutation};
+use p3_uni_stark::{verify, Proof as StarkProof, StarkConfig, StarkGenericConfig, Val};
+use quantum_utils::{error_line, file::{read_bytes_from_file, write_bytes_to_file}};
+use rand_chacha::{rand_core::SeedableRng, ChaCha20Rng};
+use serde::{Deserialize, Serialize};
+use utils::hash::{Hasher, KeccakHasher};
+
+use crate::traits::{pis::Pis, proof::Proof, vkey::Vkey};
+use crate::types::plonky3_air::Plonky3Air;
+
+#[derive(Clone, Copy, BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, PartialEq)]
+pub enum Plonky3Field {
+    BabyBear,
+    Goldilocks,
+}
+
+impl Plonky3Field {
+    pub fn order(&self) -> u64 {
+        match self {
+            Plonky3Field::BabyBear => BabyBear::ORDER_U64,
+            Plonky3Field::Goldilocks => Goldilocks::ORDER_U64,
+        }
+    }
+}
+
+// Merkle/challenger hash. Poseidon2 round constants are drawn from `ChaCha20Rng::seed_from_u64(seed)`
+#[derive(Clone, Copy, BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, PartialEq)]
+pub enum Plonky3Hash {
+    Keccak256,
+    Poseidon2 { seed: u64 },
+}
+
+#[derive(Clone, BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, PartialEq)]
+pub struct Plonky3FriParams {
+    pub log_blowup: u32,
+    pub num_queries: u32,
+    pub proof_of_work_bits: u32,
+}
+
+#[derive(Clone, BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, PartialEq)]
+pub struct Plonky3Vkey {
+    pub field: Plonky3Field,
+    pub hash: Plonky3Hash,
+    pub fri_params: Plonky3FriParams,
+    pub air: Plonky3Air,
+}
+
+const MAX_LOG_BLOWUP: u32 = 4;
+const MAX_PROOF_OF_WORK_BITS: u32 = 30;
+// security bits from queries and grinding below which a vkey is rejected
+const MIN_CONJECTURED_SECURITY_BITS: u32 = 80;
+// log size of the largest trace the verifier's fri pcs is set up for
+const LOG_MAX_TRACE_HEIGHT: usize = 27;
+
+type Dft = Radix2DitParallel;
+
+type KeccakByteHash = Keccak256Hash;
+type KeccakCompress = CompressionFunctionFromHasher<u8, KeccakByteHash, 2, 32>;
+
+type BabyBearKeccakFieldHash = SerializingHasher32<KeccakByteHash>;
+type BabyBearKeccakValMmcs = FieldMerkleTreeMmcs<BabyBear, u8, BabyBearKeccakFieldHash, KeccakCompress, 32>;
+type BabyBearChallenge = BinomialExtensionField<BabyBear, 4>;
+type BabyBearKeccakChallengeMmcs = ExtensionMmcs<BabyBear, BabyBearChallenge, BabyBearKeccakValMmcs>;
+type BabyBearKeccakChallenger = SerializingChallenger32<BabyBear, HashChallenger<u8, KeccakByteHash, 32>>;
+type BabyBearKeccakPcs = TwoAdicFriPcs<BabyBear, Dft, BabyBearKeccakValMmcs, BabyBearKeccakChallengeMmcs>;
+type BabyBearKeccakConfig = StarkConfig<BabyBearKeccakPcs, BabyBearChallenge, BabyBearKeccakChallenger>;
+
+type BabyBearPerm = Poseidon2<BabyBear, Poseidon2ExternalMatrixGeneral, DiffusionMatrixBabyBear, 16, 7>;
+type BabyBearPoseidon2Hash = PaddingFreeSponge<BabyBearPerm, 16, 8, 8>;
+type BabyBearPoseidon2Compress = TruncatedPermutation<BabyBearPerm, 2, 8, 16>;
+type BabyBearPoseidon2ValMmcs = FieldMerkleTreeMmcs<
+    <BabyBear as Field>::Packing,
+    <BabyBear as Field>::Packing,
+    BabyBearPoseidon2Hash,
+    BabyBearPoseidon2Compress,
+    8,
+>;
+type BabyBearPoseidon2ChallengeMmcs = ExtensionMmcs<BabyBear, BabyBearChallenge, BabyBearPoseidon2ValMmcs>;
+type BabyBearPoseidon2Challenger = DuplexChallenger<BabyBear, BabyBearPerm, 16, 8>;
+type BabyBearPoseidon2Pcs = TwoAdicFriPcs<BabyBear, Dft, BabyBearPoseidon2ValMmcs, BabyBearPoseidon2ChallengeMmcs>;
+type BabyBearPoseidon2Config = StarkConfig<BabyBearPoseidon2Pcs, BabyBearChallenge, BabyBearPoseidon2Challenger>;
+
+type GoldilocksKeccakFieldHash = SerializingHasher64<KeccakByteHash>;
+type GoldilocksKeccakValMmcs = FieldMerkleTreeMmcs<Goldilocks, u8, GoldilocksKeccakFieldHash, KeccakCompress, 32>;
+type GoldilocksChallenge = BinomialExtensionField<Goldilocks, 2>;
+type GoldilocksKeccakChallengeMmcs = ExtensionMmcs<Goldilocks, GoldilocksChallenge, GoldilocksKeccakValMmcs>;
+type GoldilocksKeccakChallenger = SerializingChallenger64<Goldilocks, HashChallenger<u8, KeccakByteHash, 32>>;
+type GoldilocksKeccakPcs = TwoAdicFriPcs<Goldilocks, Dft, GoldilocksKeccakValMmcs, GoldilocksKeccakChallengeMmcs>;
+type GoldilocksKeccakConfig = StarkConfig<GoldilocksKeccakPcs, GoldilocksChallenge, GoldilocksKeccakChallenger>;
+
+impl Plonky3FriParams {
+    fn get_fri_config<M>(&self, mmcs: M) -> FriConfig<M> {
+        FriConfig {
+            log_blowup: self.log_blowup as usize,
+            num_queries: self.num_queries as usize,
+            proof_of_work_bits: self.proof_of_work_bits as usize,
+            mmcs,
+        }
+    }
+}
+
+fn verify_stark<SC: StarkGenericConfig>(
+    config: &SC,
+    challenger: &mut SC::Challenger,
+    air: &Plonky3Air,
+    proof_bytes: &[u8],
+    public_values: &[u64],
+) -> AnyhowResult<()>
+where
+    Val<SC>: PrimeField64,
+    StarkProof<SC>: for<'de> Deserialize<'de>,
+{
+    let proof: StarkProof<SC> = bincode::deserialize(proof_bytes).map_err(|err| anyhow!(error_line!(err)))?;
+    let public_values: Vec<Val<SC>> = public_values.iter().map(|v| Val::<SC>::from_canonical_u64(*v)).collect();
+    verify(config, air, challenger, &proof, &public_values)
+        .map_err(|e| anyhow!(error_line!(format!("plonky3 proof validation failed: {:?}", e))))
+}
+
+impl Plonky3Vkey {
+    fn validate_params(&self) -> AnyhowResult<()> {
+        if let (Plonky3Field::Goldilocks, Plonky3Hash::Poseidon2 { .. }) = (self.field, self.hash) {
+            return Err(anyhow!(error_line!("plonky3 poseidon2 is only supported over babybear")));
+        }
+        let fri_params = &self.fri_params;
+        if fri_params.log_blowup == 0 || fri_params.log_blowup > MAX_LOG_BLOWUP {
+            return Err(anyhow!(error_line!(format!("plonky3 log_blowup must be in 1..={}", MAX_LOG_BLOWUP))));
+        }
+        if fri_params.proof_of_work_bits > MAX_PROOF_OF_WORK_BITS {
+            return Err(anyhow!(error_line!(format!("plonky3 proof_of_work_bits must be at most {}", MAX_PROOF_OF_WORK_BITS))));
+        }
+        // num_queries comes from the client, a product wrapping around u32 must not pass as secure
+        let security_bits = fri_params.log_blowup.checked_mul(fri_params.num_queries)
+            .and_then(|query_bits| query_bits.checked_add(fri_params.proof_of_work_bits))
+            .ok_or(anyhow!(error_line!("plonky3 fri params overflow the security bits")))?;
+        if security_bits < MIN_CONJECTURED_SECURITY_BITS {
+            return Err(anyhow!(error_line!(format!("plonky3 fri params give {} bits of security, at least {} are needed", security_bits, MIN_CONJECTURED_SECURITY_BITS))));
+        }
+        Ok(())
+    }
+
+    pub fn verify_proof(&self, proof_bytes: &[u8], public_values: &[u64]) -> AnyhowResult<()> {
+        if public_values.len() != self.air.num_public_values as usize {
+            return Err(anyhow!(error_line!("plonky3 proof has an unexpected number of public values")));
+        }
+        if public_values.iter().any(|v| *v >= self.field.order()) {
+            return Err(anyhow!(error_line!("plonky3 public values are not canonical")));
+        }
+
+        match (self.field, self.hash) {
+            (Plonky3Field::BabyBear, Plonky3Hash::Keccak256) => {
+                let byte_hash = KeccakByteHash {};
+                let val_mmcs = BabyBearKeccakValMmcs::new(BabyBearKeccakFieldHash::new(byte_hash), KeccakCompress::new(byte_hash));
+                let fri_config = self.fri_params.get_fri_config(BabyBearKeccakChallengeMmcs::new(val_mmcs.clone()));
+                let pcs = BabyBearKeccakPcs::new(LOG_MAX_TRACE_HEIGHT, Dft {}, val_mmcs, fri_config);
+                let config = BabyBearKeccakConfig::new(pcs);
+                let mut challenger = BabyBearKeccakChallenger::from_hasher(vec![], byte_hash);
+                verify_stark(&config, &mut challenger, &self.air, proof_bytes, public_values)
+            }
+            (Plonky3Field::BabyBear, Plonky3Hash::Poseidon2 { seed }) => {
+                let perm = BabyBearPerm::new_from_rng_128(
+                    Poseidon2ExternalMatrixGeneral,
+                    DiffusionMatrixBabyBear::default(),
+                    &mut ChaCha20Rng::seed_from_u64(seed),
+                );
+                let val_mmcs = BabyBearPoseidon2ValMmcs::new(BabyBearPoseidon2Hash::new(perm.clone()), BabyBearPoseidon2Compress::new(perm.clone()));
+                let fri_config = self.fri_params.get_fri_config(BabyBearPoseidon2ChallengeMmcs::new(val_mmcs.clone()));
+                let pcs = BabyBearPoseidon2Pcs::new(LOG_MAX_TRACE_HEIGHT, Dft {}, val_mmcs, fri_config);
+                let config = BabyBearPoseidon2Config::new(pcs);
+                let mut challenger = BabyBearPoseidon2Challenger::new(perm);
+                verify_stark(&config, &mut challenger, &self.air, proof_bytes, public_values)
+            }
+            (Plonky3Field::Goldilocks, Plonky3Hash::Keccak256) => {
+                let byte_hash = KeccakByteHash {};
+                let val_mmcs = GoldilocksKeccakValMmcs::new(GoldilocksKeccakFieldHash::new(byte_hash), KeccakCompress::new(byte_hash));
+                let fri_config = self.fri_params.get_fri_config(GoldilocksKeccakChallengeMmcs::new(val_mmcs.clone()));
+                let pcs = GoldilocksKeccakPcs::new(LOG_MAX_TRACE_HEIGHT, Dft {}, val_mmcs, fri_config);
+                let config = GoldilocksKeccakConfig::new(pcs);
+                let mut challenger = GoldilocksKeccakChallenger::from_hasher(vec![], byte_hash);
+                verify_stark(&config, &mut challenger, &self.air, proof_bytes, public_values)
+            }
+            (Plonky3Field::Goldilocks, Plonky3Hash::Poseidon2 { .. }) => {
+                Err(anyhow!(error_line!("plonky3 poseidon2 is only supported over babybear")))
+            }
+        }
+    }
+}
+
+impl Vkey for Plonky3Vkey {
+    fn serialize_vkey(&self) -> AnyhowResult<Vec<u8>> {
+        let mut buffer: Vec<u8> = Vec::new();
+        BorshSerialize::serialize(&self, &mut buffer).map_err(|err| anyhow!(error_line!(err)))?;
+        Ok(buffer)
+    }
+
+    fn deserialize_vkey(bytes: &mut &[u8]) -> AnyhowResult<Self> {
+        let key: Plonky3Vkey =
+            BorshDeserialize::deserialize(bytes).map_err(|err| anyhow!(error_line!(err)))?;
+        Ok(key)
+    }
+
+    fn dump_vk(&self, path: &str) -> AnyhowResult<()> {
+        let vkey_bytes = self.serialize_vkey()?;
+        write_bytes_to_file(&vkey_bytes, path)?;
+        Ok(())
+    }
+
+    fn read_vk(full_path: &str) -> AnyhowResult<Self> {
+        let vkey_bytes = read_bytes_from_file(full_path)?;
+        let vkey = Plonky3Vkey::deserialize_vkey(&mut vkey_bytes.as_slice())?;
+        Ok(vkey)
+    }
+
+    fn validate(&self) -> AnyhowResult<()> {
+        self.validate_params()?;
+        self.air.validate(self.field.order())?;
+        Ok(())
+    }
+
+    fn keccak_hash(&self) -> AnyhowResult<[u8; 32]> {
+        let hash = KeccakHasher::hash_out(&self.serialize_vkey()?);
+        Ok(hash)
+    }
+
+    fn compute_circuit_hash(&self, circuit_verifying_id: [u32; 8]) -> AnyhowResult<[u8; 32]> {
+        let protocol_hash = self.keccak_hash()?;
+        let circuit_hash = compute_combined_vkey_hash::<KeccakHasher>(&protocol_hash, &circuit_verifying_id)?;
+        Ok(circuit_hash)
+    }
+}
+
+// public values are carried along with the bincode serialised `p3_uni_stark::Proof`
+#[derive(Clone, BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, PartialEq)]
+pub struct Plonky3Proof {
+    pub public_values: Vec<u64>,
+    pub proof_bytes: Vec<u8>,
+}
+
+impl Proof for Plonky3Proof {
+    fn serialize_proof(&self) -> AnyhowResult<Vec<u8>> {
+        let mut buffer: Vec<u8> = Vec::new();
+        BorshSerialize::serialize(&self, &mut buffer)?;
+        Ok(buffer)
+    }
+
+    fn deserialize_proof(bytes: &mut &[u8]) -> AnyhowResult<Self> {
+        let key: Plonky3Proof =
+            BorshDeserialize::deserialize(bytes).map_err(|err| anyhow!(error_line!(err)))?;
+        Ok(key)
+    }
+
+    fn dump_proof(&self, path: &str) -> AnyhowResult<()> {
+        let proof_bytes = self.serialize_proof()?;
+        write_bytes_to_file(&proof_bytes, path)?;
+        Ok(())
+    }
+
+    fn read_proof(full_path: &str) -> AnyhowResult<Self> {
+        let proof_bytes = read_bytes_from_file(full_path)?;
+        let proof = Plonky3Proof::deserialize_proof(&mut proof_bytes.as_slice())?;
+        Ok(proof)
+    }
+
+    fn validate_proof(&self, vkey_path: &str, mut _pis_bytes: &[u8]) -> AnyhowResult<()> {
+        let vkey = Plonky3Vkey::read_vk(vkey_path)?;
+        vkey.verify_proof(&self.proof_bytes, &self.public_values)?;
+        Ok(())
+    }
+
+    fn get_proof_bytes(&self) -> AnyhowResult<Vec<u8>> {
+        self.serialize_proof()
+    }
+}
+
+impl Plonky3Proof {
+    pub fn get_pis_strings(&self, vkey_path: &str) -> AnyhowResult<Vec<String>> {
+        let vkey = Plonky3Vkey::read_vk(vkey_path)?;
+        if self.public_values.iter().any(|v| *v >= vkey.field.order()) {
+            return Err(anyhow!(error_line!("plonky3 public values are not canonical")));
+        }
+        Ok(self.public_values.iter().map(|v| v.to_string()).collect())
+    }
+}
+
+#[derive(Clone, BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, PartialEq)]
+pub struct Plonky3Pis(pub Vec<String>);
+
+impl Pis for Plonky3Pis {
+    fn serialize_pis(&self) -> AnyhowResult<Vec<u8>> {
+        let mut buffer: Vec<u8> = Vec::new();
+        BorshSerialize::serialize(&self, &mut buffer)?;
+        Ok(buffer)
+    }
+
+    fn deserialize_pis(bytes: &mut &[u8]) -> AnyhowResult<Self> {
+        let key: Plonky3Pis =
+            BorshDeserialize::deserialize(bytes).map_err(|err| anyhow!(error_line!(err)))?;
+        Ok(key)
+    }
+
+    fn dump_pis(&self, path: &str) -> AnyhowResult<()> {
+        let pis_bytes = self.serialize_pis()?;
+        write_bytes_to_file(&pis_bytes, path)?;
+        Ok(())
+    }
+
+    fn read_pis(full_path: &str) -> AnyhowResult<Self> {
+        let pis_bytes = read_bytes_from_file(full_path)?;
+        let pis = Plonky3Pis::deserialize_pis(&mut pis_bytes.as_slice())?;
+        Ok(pis)
+    }
+
+    // keccak of the public values as little endian u64s
+    fn keccak_hash(&self) -> AnyhowResult<[u8; 32]> {
+        let mut pis_bytes = vec![];
+        for p in &self.0 {
+            pis_bytes.extend_from_slice(&p.parse::<u64>()?.to_le_bytes());
+        }
+        Ok(KeccakHasher::hash_out(&pis_bytes))
+    }
+
+    fn get_data(&self) -> AnyhowResult<Vec<String>> {
+        Ok(self.0.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vkey_with_fri_params(log_blowup: u32, num_queries: u32, proof_of_work_bits: u32) -> Plonky3Vkey {
+        Plonky3Vkey {
+            field: Plonky3Field::BabyBear,
+            hash: Plonky3Hash::Keccak256,
+            fri_params: Plonky3FriParams { log_blowup, num_queries, proof_of_work_bits },
+            air: Plonky3Air { width: 1, num_public_values: 0, constraints: vec![] },
+        }
+    }
+
+    #[test]
+    fn test_fri_security_bits() {
+        assert!(vkey_with_fri_params(1, 100, 16).validate_params().is_ok());
+        assert!(vkey_with_fri_params(1, 10, 0).validate_params().is_err());
+        // 4 * (2^30 + 25) wraps around to 100 in u32
+        assert!(vkey_with_fri_params(4, (1 << 30) + 25, 0).validate_params().is_err());
+        assert!(vkey_with_fri_params(2, u32::MAX, 0).validate_params().is_err());
+    }
+}
@@ -0,0 +1,185 @@
+use std::io::{Error as IoError, ErrorKind, Read};
+
+use anyhow::{anyhow, Result as AnyhowResult};
+use borsh::{BorshDeserialize, BorshSerialize};
+use p3_air::{Air, AirBuilder, AirBuilderWithPublicValues, BaseAir};
+use p3_field::AbstractField;
+use p3_matrix::Matrix;
+use quantum_utils::error_line;
+use serde::{Deserialize, Serialize};
+
+// same as serde_json's recursion limit, expressions are walked recursively by `validate` and `eval`
+const MAX_EXPR_DEPTH: usize = 128;
+
+/*
+    A plonky3 AIR carried as data, so a circuit can be registered without shipping rust code.
+    Every constraint is an expression over the local row, the next row and the public values
+    which must vanish on its domain.
+    Borsh deserialization is written by hand to bound the nesting depth of client supplied expressions.
+ */
+#[derive(Clone, BorshSerialize, Serialize, Deserialize, Debug, PartialEq)]
+pub enum Plonky3AirExpr {
+    Local(u32),
+    Next(u32),
+    Public(u32),
+    Const(u64),
+    Add(Box<Plonky3AirExpr>, Box<Plonky3AirExpr>),
+    Sub(Box<Plonky3AirExpr>, Box<Plonky3AirExpr>),
+    Mul(Box<Plonky3AirExpr>, Box<Plonky3AirExpr>),
+    Neg(Box<Plonky3AirExpr>),
+}
+
+#[derive(Clone, Copy, BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, PartialEq)]
+pub enum Plonky3ConstraintDomain {
+    FirstRow,
+    LastRow,
+    Transition,
+    AllRows,
+}
+
+#[derive(Clone, BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, PartialEq)]
+pub struct Plonky3Constraint {
+    pub domain: Plonky3ConstraintDomain,
+    pub expr: Plonky3AirExpr,
+}
+
+#[derive(Clone, BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, PartialEq)]
+pub struct Plonky3Air {
+    pub width: u32,
+    pub num_public_values: u32,
+    pub constraints: Vec<Plonky3Constraint>,
+}
+
+impl BorshDeserialize for Plonky3AirExpr {
+    fn deserialize_reader<R: Read>(reader: &mut R) -> std::io::Result<Self> {
+        Self::deserialize_with_depth(reader, 1)
+    }
+}
+
+impl Plonky3AirExpr {
+    // variant tags follow the declaration order, as written by the derived `BorshSerialize`
+    fn deserialize_with_depth<R: Read>(reader: &mut R, depth: usize) -> std::io::Result<Self> {
+        if depth > MAX_EXPR_DEPTH {
+            return Err(IoError::new(ErrorKind::InvalidData, format!("plonky3 air expression is nested deeper than {}", MAX_EXPR_DEPTH)));
+        }
+        let expr = match u8::deserialize_reader(reader)? {
+            0 => Plonky3AirExpr::Local(u32::deserialize_reader(reader)?),
+            1 => Plonky3AirExpr::Next(u32::deserialize_reader(reader)?),
+            2 => Plonky3AirExpr::Public(u32::deserialize_reader(reader)?),
+            3 => Plonky3AirExpr::Const(u64::deserialize_reader(reader)?),
+            4 => Plonky3AirExpr::Add(Self::deserialize_child(reader, depth)?, Self::deserialize_child(reader, depth)?),
+            5 => Plonky3AirExpr::Sub(Self::deserialize_child(reader, depth)?, Self::deserialize_child(reader, depth)?),
+            6 => Plonky3AirExpr::Mul(Self::deserialize_child(reader, depth)?, Self::deserialize_child(reader, depth)?),
+            7 => Plonky3AirExpr::Neg(Self::deserialize_child(reader, depth)?),
+            tag => return Err(IoError::new(ErrorKind::InvalidData, format!("invalid plonky3 air expression tag {}", tag))),
+        };
+        Ok(expr)
+    }
+
+    fn deserialize_child<R: Read>(reader: &mut R, depth: usize) -> std::io::Result<Box<Self>> {
+        Ok(Box::new(Self::deserialize_with_depth(reader, depth + 1)?))
+    }
+
+    // checks column/public indices and that constants are canonical for a field of order `field_order`
+    fn validate(&self, width: u32, num_public_values: u32, field_order: u64) -> AnyhowResult<()> {
+        match self {
+            Plonky3AirExpr::Local(i) | Plonky3AirExpr::Next(i) if *i >= width => {
+                Err(anyhow!(error_line!(format!("plonky3 air references column {} of a {} column trace", i, width))))
+            }
+            Plonky3AirExpr::Public(i) if *i >= num_public_values => {
+                Err(anyhow!(error_line!(format!("plonky3 air references public value {} of {}", i, num_public_values))))
+            }
+            Plonky3AirExpr::Const(c) if *c >= field_order => {
+                Err(anyhow!(error_line!(format!("plonky3 air constant {} is not canonical", c))))
+            }
+            Plonky3AirExpr::Add(a, b) | Plonky3AirExpr::Sub(a, b) | Plonky3AirExpr::Mul(a, b) => {
+                a.validate(width, num_public_values, field_order)?;
+                b.validate(width, num_public_values, field_order)
+            }
+            Plonky3AirExpr::Neg(a) => a.validate(width, num_public_values, field_order),
+            _ => Ok(()),
+        }
+    }
+
+    fn eval<AB: AirBuilder>(&self, local: &[AB::Var], next: &[AB::Var], public_values: &[AB::Expr]) -> AB::Expr {
+        match self {
+            Plonky3AirExpr::Local(i) => local[*i as usize].into(),
+            Plonky3AirExpr::Next(i) => next[*i as usize].into(),
+            Plonky3AirExpr::Public(i) => public_values[*i as usize].clone(),
+            Plonky3AirExpr::Const(c) => AB::Expr::from_canonical_u64(*c),
+            Plonky3AirExpr::Add(a, b) => a.eval::<AB>(local, next, public_values) + b.eval::<AB>(local, next, public_values),
+            Plonky3AirExpr::Sub(a, b) => a.eval::<AB>(local, next, public_values) - b.eval::<AB>(local, next, public_values),
+            Plonky3AirExpr::Mul(a, b) => a.eval::<AB>(local, next, public_values) * b.eval::<AB>(local, next, public_values),
+            Plonky3AirExpr::Neg(a) => -a.eval::<AB>(local, next, public_values),
+        }
+    }
+}
+
+impl Plonky3Air {
+    pub fn validate(&self, field_order: u64) -> AnyhowResult<()> {
+        if self.width == 0 {
+            return Err(anyhow!(error_line!("plonky3 air has no columns")));
+        }
+        if self.constraints.is_empty() {
+            return Err(anyhow!(error_line!("plonky3 air has no constraints")));
+        }
+        for constraint in &self.constraints {
+            constraint.expr.validate(self.width, self.num_public_values, field_order)?;
+        }
+        Ok(())
+    }
+}
+
+impl<F> BaseAir<F> for Plonky3Air {
+    fn width(&self) -> usize {
+        self.width as usize
+    }
+}
+
+impl<AB: AirBuilderWithPublicValues> Air<AB> for Plonky3Air {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let local: Vec<AB::Var> = main.row_slice(0).to_vec();
+        let next: Vec<AB::Var> = main.row_slice(1).to_vec();
+        let public_values: Vec<AB::Expr> = builder.public_values().iter().map(|p| (*p).into()).collect();
+
+        for constraint in &self.constraints {
+            let expr = constraint.expr.eval::<AB>(&local, &next, &public_values);
+            match constraint.domain {
+                Plonky3ConstraintDomain::FirstRow => builder.when_first_row().assert_zero(expr),
+                Plonky3ConstraintDomain::LastRow => builder.when_last_row().assert_zero(expr),
+                Plonky3ConstraintDomain::Transition => builder.when_transition().assert_zero(expr),
+                Plonky3ConstraintDomain::AllRows => builder.assert_zero(expr),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_borsh_roundtrip_of_air_expr() {
+        let expr = Plonky3AirExpr::Sub(
+            Box::new(Plonky3AirExpr::Mul(Box::new(Plonky3AirExpr::Local(0)), Box::new(Plonky3AirExpr::Local(1)))),
+            Box::new(Plonky3AirExpr::Neg(Box::new(Plonky3AirExpr::Const(7)))),
+        );
+        let bytes = borsh::to_vec(&expr).unwrap();
+        assert_eq!(Plonky3AirExpr::try_from_slice(&bytes).unwrap(), expr);
+    }
+
+    #[test]
+    fn test_deeply_nested_air_expr_is_rejected() {
+        // Neg(Neg(..Const(1)..)) nested far deeper than the limit
+        let mut bytes = vec![7u8; 100_000];
+        bytes.push(3);
+        bytes.extend_from_slice(&1u64.to_le_bytes());
+        assert!(Plonky3AirExpr::try_from_slice(&bytes).is_err());
+
+        let mut bytes = vec![7u8; MAX_EXPR_DEPTH - 1];
+        bytes.push(3);
+        bytes.extend_from_slice(&1u64.to_le_bytes());
+        assert!(Plonky3AirExpr::try_from_slice(&bytes).is_ok());
+    }
+}
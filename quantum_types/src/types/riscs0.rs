@@ -1,16 +1,36 @@
+use std::io::Read;
+
 use agg_core::inputs::compute_combined_vkey_hash;
 use anyhow::{anyhow , Result as AnyhowResult};
 use borsh::{BorshDeserialize, BorshSerialize};
 use quantum_utils::{error_line, file::{read_bytes_from_file, write_bytes_to_file}};
-use risc0_zkvm::Receipt;
+use risc0_zkvm::{sha::Digestible, CompositeReceiptVerifierParameters, Groth16ReceiptVerifierParameters, InnerReceipt, Receipt, SuccinctReceiptVerifierParameters};
 use serde::{Deserialize, Serialize};
 use utils::hash::{Hasher, KeccakHasher};
 
+use crate::enums::risc0_receipt_kind::Risc0ReceiptKind;
 use crate::traits::{pis::Pis, proof::Proof, vkey::Vkey};
 
-#[derive(Clone, BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, PartialEq)]
+#[derive(Clone, BorshSerialize, Serialize, Deserialize, Debug, PartialEq)]
 pub struct Risc0Vkey {
-    pub vkey_bytes: [u32;8]
+    pub vkey_bytes: [u32;8],
+    // risc0 version the program's receipts are produced with, `None` means the version of the node
+    #[serde(default)]
+    pub risc0_version: Option<String>,
+}
+
+impl BorshDeserialize for Risc0Vkey {
+    fn deserialize_reader<R: Read>(reader: &mut R) -> std::io::Result<Self> {
+        let vkey_bytes = <[u32; 8]>::deserialize_reader(reader)?;
+        // vkeys serialised before the version pin was added end here
+        let mut version_bytes = vec![];
+        reader.read_to_end(&mut version_bytes)?;
+        let risc0_version = match version_bytes.is_empty() {
+            true => None,
+            false => Option::<String>::deserialize(&mut version_bytes.as_slice())?,
+        };
+        Ok(Risc0Vkey { vkey_bytes, risc0_version })
+    }
 }
 
 impl Vkey for Risc0Vkey {
@@ -39,6 +59,9 @@ impl Vkey for Risc0Vkey {
     }
 
     fn validate(&self) -> AnyhowResult<()> {
+        if let Some(risc0_version) = &self.risc0_version {
+            check_risc0_version(risc0_version)?;
+        }
         Ok(())
     }
 
@@ -57,6 +80,44 @@ impl Vkey for Risc0Vkey {
     }
 }
 
+impl Risc0Vkey {
+    pub fn get_risc0_version(&self) -> String {
+        self.risc0_version.clone().unwrap_or(risc0_zkvm::VERSION.to_string())
+    }
+}
+
+// receipts stay verifiable across patch releases, the recursion control roots change with the minor version
+pub fn check_risc0_version(risc0_version: &str) -> AnyhowResult<()> {
+    let major_minor = |version: &str| version.trim_start_matches('v').split('.').take(2).map(|v| v.to_string()).collect::<Vec<String>>();
+    let pinned = major_minor(risc0_version);
+    if pinned.len() != 2 || pinned != major_minor(risc0_zkvm::VERSION) {
+        return Err(anyhow!(error_line!(format!("receipts of risc0 {} can not be verified by this node, it runs risc0 {}", risc0_version, risc0_zkvm::VERSION))));
+    }
+    Ok(())
+}
+
+// The verifier parameters a receipt commits to change with the risc0 version it was produced with
+pub fn check_receipt_version(receipt: &Receipt, risc0_version: &str) -> AnyhowResult<()> {
+    check_risc0_version(risc0_version)?;
+    let verifier_parameters = match get_receipt_kind(receipt)? {
+        Risc0ReceiptKind::Composite => CompositeReceiptVerifierParameters::default().digest(),
+        Risc0ReceiptKind::Succinct => SuccinctReceiptVerifierParameters::default().digest(),
+        Risc0ReceiptKind::Groth16 => Groth16ReceiptVerifierParameters::default().digest(),
+    };
+    if receipt.metadata.verifier_parameters != verifier_parameters {
+        return Err(anyhow!(error_line!(format!("risc0 receipt was not produced with risc0 {}, the version pinned in the vkey", risc0_version))));
+    }
+    Ok(())
+}
+
+pub fn get_receipt_kind(receipt: &Receipt) -> AnyhowResult<Risc0ReceiptKind> {
+    match &receipt.inner {
+        InnerReceipt::Composite(_) => Ok(Risc0ReceiptKind::Composite),
+        InnerReceipt::Succinct(_) => Ok(Risc0ReceiptKind::Succinct),
+        InnerReceipt::Groth16(_) => Ok(Risc0ReceiptKind::Groth16),
+        _ => Err(anyhow!(error_line!("only composite, succinct and groth16 risc0 receipts are accepted"))),
+    }
+}
 
 #[derive(Clone, BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug)]
 pub struct Risc0Proof {
@@ -87,10 +148,12 @@ impl Proof for Risc0Proof {
         Ok(gnark_proof)
     }
     
+    // whether the receipt kind is accepted depends on the circuit's bonsai image, see `Risc0Scheme::get_receipt_kind`
     fn validate_proof(&self, vkey_path: &str,mut _pis_bytes: &[u8]) -> AnyhowResult<()> {
         let vkey = Risc0Vkey::read_vk(vkey_path)?;
-        self.get_receipt()?.inner.succinct().map_err(|err| anyhow!(error_line!(format!("error in getting succinct receipt of risc0 proof:{}", err))))?;
-        self.get_receipt()?.verify(vkey.vkey_bytes)?;
+        let receipt = self.get_receipt()?;
+        check_receipt_version(&receipt, &vkey.get_risc0_version())?;
+        receipt.verify(vkey.vkey_bytes).map_err(|err| anyhow!(error_line!(format!("risc0 receipt verification against risc0 {} failed: {}", vkey.get_risc0_version(), err))))?;
         Ok(())
     }
     
@@ -144,3 +207,33 @@ impl Pis for Risc0Pis {
         Ok(self.0.clone())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_risc0_vkey_version_is_checked() {
+        let vkey = Risc0Vkey { vkey_bytes: [1, 2, 3, 4, 5, 6, 7, 8], risc0_version: None };
+        vkey.validate().unwrap();
+        let vkey = Risc0Vkey { risc0_version: Some(risc0_zkvm::VERSION.to_string()), ..vkey };
+        vkey.validate().unwrap();
+
+        let vkey = Risc0Vkey { risc0_version: Some(String::from("0.19.1")), ..vkey };
+        assert!(vkey.validate().is_err());
+        let vkey = Risc0Vkey { risc0_version: Some(String::from("not a version")), ..vkey };
+        assert!(vkey.validate().is_err());
+    }
+
+    #[test]
+    fn test_risc0_vkey_without_version_still_deserializes() {
+        let vkey = Risc0Vkey { vkey_bytes: [1, 2, 3, 4, 5, 6, 7, 8], risc0_version: None };
+        let mut old_vkey_bytes = vec![];
+        BorshSerialize::serialize(&vkey.vkey_bytes, &mut old_vkey_bytes).unwrap();
+        assert_eq!(Risc0Vkey::deserialize_vkey(&mut old_vkey_bytes.as_slice()).unwrap(), vkey);
+
+        let vkey = Risc0Vkey { risc0_version: Some(String::from("1.0.1")), ..vkey };
+        let vkey_bytes = vkey.serialize_vkey().unwrap();
+        assert_eq!(Risc0Vkey::deserialize_vkey(&mut vkey_bytes.as_slice()).unwrap(), vkey);
+    }
+}
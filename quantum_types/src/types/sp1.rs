@@ -40,6 +40,7 @@ impl Vkey for Sp1Vkey {
     }
 
     fn validate(&self) -> AnyhowResult<()> {
+        self.get_verifying_key()?;
         Ok(())
     }
 
@@ -99,7 +100,7 @@ impl Proof for Sp1Proof {
         let proof_with_public_input = self.get_proof_with_public_inputs()?;
         match proof_with_public_input.proof {
             sp1_sdk::SP1Proof::Compressed(_) => {},
-            _ => {Err(anyhow!("sp1 is not of compressed type"))}?,
+            _ => {Err(anyhow!("sp1 is not of compressed type, register groth16/plonk wrapped proofs as Sp1Groth16/Sp1Plonk"))}?,
         }
         client.verify(&self.get_proof_with_public_inputs()?, &vkey.get_verifying_key()?)?;
         Ok(())
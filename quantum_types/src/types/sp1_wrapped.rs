@@ -0,0 +1,265 @@
+use std::{fmt::Debug, marker::PhantomData};
+
+use agg_core::inputs::compute_combined_vkey_hash;
+use anyhow::{anyhow, Result as AnyhowResult};
+use borsh::{BorshDeserialize, BorshSerialize};
+use quantum_utils::{error_line, file::{read_bytes_from_file, write_bytes_to_file}};
+use serde::{Deserialize, Serialize};
+use sp1_sdk::{HashableKey, SP1Proof, SP1ProofWithPublicValues};
+use sp1_verifier::{Groth16Verifier, PlonkVerifier, GROTH16_VK_BYTES, PLONK_VK_BYTES};
+use utils::hash::KeccakHasher;
+
+use crate::traits::{proof::Proof, vkey::Vkey};
+use crate::types::sp1::Sp1Vkey;
+
+/*
+    Sp1 proofs wrapped in the sp1 Groth16/Plonk bn254 circuits. Unlike compressed proofs, these are reduced on
+    bonsai like gnark proofs, against the sp1 circuit vkeys shipped with `sp1_verifier`.
+    The vkey hash and the pis hash are the compressed sp1 ones, only the circuit hash is combined with the
+    reduction image id like every other leaf of the risc0 aggregation.
+ */
+pub trait Sp1WrapVerifier: Clone + Debug + Default {
+    const WRAPPER_NAME: &'static str;
+
+    fn is_wrapped(proof: &SP1Proof) -> bool;
+
+    // bn254 verifying key of the sp1 wrapper circuit
+    fn circuit_vk_bytes() -> Vec<u8>;
+
+    fn verify(proof: &[u8], public_values: &[u8], program_vkey_hash: &str) -> AnyhowResult<()>;
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Sp1Groth16Verifier;
+
+impl Sp1WrapVerifier for Sp1Groth16Verifier {
+    const WRAPPER_NAME: &'static str = "groth16";
+
+    fn is_wrapped(proof: &SP1Proof) -> bool {
+        matches!(proof, SP1Proof::Groth16(_))
+    }
+
+    fn circuit_vk_bytes() -> Vec<u8> {
+        GROTH16_VK_BYTES.to_vec()
+    }
+
+    fn verify(proof: &[u8], public_values: &[u8], program_vkey_hash: &str) -> AnyhowResult<()> {
+        Groth16Verifier::verify(proof, public_values, program_vkey_hash, &GROTH16_VK_BYTES)
+            .map_err(|e| anyhow!(error_line!(format!("sp1 groth16 proof validation failed: {:?}", e))))
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Sp1PlonkVerifier;
+
+impl Sp1WrapVerifier for Sp1PlonkVerifier {
+    const WRAPPER_NAME: &'static str = "plonk";
+
+    fn is_wrapped(proof: &SP1Proof) -> bool {
+        matches!(proof, SP1Proof::Plonk(_))
+    }
+
+    fn circuit_vk_bytes() -> Vec<u8> {
+        PLONK_VK_BYTES.to_vec()
+    }
+
+    fn verify(proof: &[u8], public_values: &[u8], program_vkey_hash: &str) -> AnyhowResult<()> {
+        PlonkVerifier::verify(proof, public_values, program_vkey_hash, &PLONK_VK_BYTES)
+            .map_err(|e| anyhow!(error_line!(format!("sp1 plonk proof validation failed: {:?}", e))))
+    }
+}
+
+#[derive(Clone, BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
+pub struct Sp1WrappedVkey(pub Sp1Vkey);
+
+impl Vkey for Sp1WrappedVkey {
+    fn serialize_vkey(&self) -> anyhow::Result<Vec<u8>> {
+        let mut buffer: Vec<u8> = Vec::new();
+        BorshSerialize::serialize(&self, &mut buffer).map_err(|err| anyhow!(error_line!(err)))?;
+        Ok(buffer)
+    }
+
+    fn deserialize_vkey(bytes: &mut &[u8]) -> anyhow::Result<Self> {
+        let key: Sp1WrappedVkey = BorshDeserialize::deserialize(bytes).map_err(|err| anyhow!(error_line!(err)))?;
+        Ok(key)
+    }
+
+    fn dump_vk(&self, path: &str) -> AnyhowResult<()> {
+        let vkey_bytes = self.serialize_vkey()?;
+        write_bytes_to_file(&vkey_bytes, path)?;
+        Ok(())
+    }
+
+    fn read_vk(full_path: &str) -> AnyhowResult<Self> {
+        let vkey_bytes = read_bytes_from_file(full_path)?;
+        let vkey = Sp1WrappedVkey::deserialize_vkey(&mut vkey_bytes.as_slice())?;
+        Ok(vkey)
+    }
+
+    fn validate(&self) -> AnyhowResult<()> {
+        self.0.validate()
+    }
+
+    fn keccak_hash(&self) -> AnyhowResult<[u8; 32]> {
+        self.0.keccak_hash()
+    }
+
+    fn compute_circuit_hash(&self, circuit_verifying_id: [u32; 8]) -> AnyhowResult<[u8; 32]> {
+        let protocol_hash = self.keccak_hash()?;
+        let circuit_hash = compute_combined_vkey_hash::<KeccakHasher>(&protocol_hash, &circuit_verifying_id)?;
+        Ok(circuit_hash)
+    }
+}
+
+#[derive(Clone, BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug)]
+pub struct Sp1WrappedProof<W: Sp1WrapVerifier> {
+    pub proof_bytes: Vec<u8>,
+    #[borsh(skip)]
+    #[serde(skip)]
+    verifier: PhantomData<W>,
+}
+
+pub type Sp1Groth16Proof = Sp1WrappedProof<Sp1Groth16Verifier>;
+pub type Sp1PlonkProof = Sp1WrappedProof<Sp1PlonkVerifier>;
+
+impl<W: Sp1WrapVerifier> Proof for Sp1WrappedProof<W> {
+    fn serialize_proof(&self) -> AnyhowResult<Vec<u8>> {
+        let mut buffer: Vec<u8> = Vec::new();
+        BorshSerialize::serialize(&self, &mut buffer)?;
+        Ok(buffer)
+    }
+
+    fn deserialize_proof(bytes: &mut &[u8]) -> AnyhowResult<Self> {
+        let key: Sp1WrappedProof<W> = BorshDeserialize::deserialize(bytes).map_err(|err| anyhow!(error_line!(err)))?;
+        Ok(key)
+    }
+
+    fn dump_proof(&self, path: &str) -> AnyhowResult<()> {
+        let proof_bytes = self.serialize_proof()?;
+        write_bytes_to_file(&proof_bytes, path)?;
+        Ok(())
+    }
+
+    fn read_proof(full_path: &str) -> AnyhowResult<Self> {
+        let proof_bytes = read_bytes_from_file(full_path)?;
+        let sp1_proof = Sp1WrappedProof::deserialize_proof(&mut proof_bytes.as_slice())?;
+        Ok(sp1_proof)
+    }
+
+    fn validate_proof(&self, vkey_path: &str, mut _pis_bytes: &[u8]) -> AnyhowResult<()> {
+        let vkey = Sp1WrappedVkey::read_vk(vkey_path)?;
+        self.verify(&vkey)
+    }
+
+    fn get_proof_bytes(&self) -> AnyhowResult<Vec<u8>> {
+        Ok(self.proof_bytes.clone())
+    }
+}
+
+impl<W: Sp1WrapVerifier> Sp1WrappedProof<W> {
+    pub fn new(proof_bytes: Vec<u8>) -> Self {
+        Sp1WrappedProof { proof_bytes, verifier: PhantomData }
+    }
+
+    pub fn get_proof_with_public_inputs(&self) -> AnyhowResult<SP1ProofWithPublicValues> {
+        let proof = bincode::deserialize(&self.proof_bytes)?;
+        Ok(proof)
+    }
+
+    pub fn verify(&self, vkey: &Sp1WrappedVkey) -> AnyhowResult<()> {
+        let proof_with_public_input = self.get_proof_with_public_inputs()?;
+        if !W::is_wrapped(&proof_with_public_input.proof) {
+            return Err(anyhow!(error_line!(format!("sp1 proof is not {} wrapped", W::WRAPPER_NAME))));
+        }
+        W::verify(
+            &proof_with_public_input.bytes(),
+            proof_with_public_input.public_values.as_slice(),
+            &vkey.0.get_verifying_key()?.bytes32(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use utils::hash::Hasher;
+
+    use crate::traits::pis::Pis;
+    use crate::types::sp1::Sp1Pis;
+
+    use super::*;
+
+    /*
+        Wrapped proofs can only be generated by the sp1 prover, `test_data/sp1_wrapped` holds the ones written by
+        `scripts/sp1_wrapped_fixture`: `vkey.bin` (bincode `SP1VerifyingKey`), `groth16.bin` and `plonk.bin`
+        (bincode `SP1ProofWithPublicValues`).
+     */
+    fn read_fixture(name: &str) -> Vec<u8> {
+        read_bytes_from_file(&format!("{}/test_data/sp1_wrapped/{}", env!("CARGO_MANIFEST_DIR"), name)).unwrap()
+    }
+
+    fn check_wrapped_proof<W: Sp1WrapVerifier>(proof_file: &str) {
+        let vkey = Sp1WrappedVkey(Sp1Vkey { vkey_bytes: read_fixture("vkey.bin") });
+        vkey.validate().unwrap();
+        let proof = Sp1WrappedProof::<W>::new(read_fixture(proof_file));
+        proof.verify(&vkey).unwrap();
+
+        // the leaf is built from the same vkey and pis hashes as an sp1 aggregation leaf
+        let circuit_verifying_id = [7u32; 8];
+        assert_eq!(vkey.keccak_hash().unwrap(), vkey.0.keccak_hash().unwrap());
+        assert_eq!(
+            vkey.compute_circuit_hash(circuit_verifying_id).unwrap(),
+            compute_combined_vkey_hash::<KeccakHasher>(&vkey.0.keccak_hash().unwrap(), &circuit_verifying_id).unwrap()
+        );
+        let public_values = proof.get_proof_with_public_inputs().unwrap().public_values.to_vec();
+        let pis = Sp1Pis(vec![hex::encode(&public_values)]);
+        assert_eq!(pis.keccak_hash().unwrap(), KeccakHasher::hash_out(&public_values));
+
+        let mut tampered_proof = proof.get_proof_with_public_inputs().unwrap();
+        tampered_proof.public_values = sp1_sdk::SP1PublicValues::from(&[public_values, vec![0u8]].concat());
+        let tampered_proof = Sp1WrappedProof::<W>::new(bincode::serialize(&tampered_proof).unwrap());
+        assert!(tampered_proof.verify(&vkey).is_err());
+    }
+
+    #[test]
+    #[ignore = "needs the sp1 fixtures of scripts/sp1_wrapped_fixture"]
+    fn test_sp1_groth16_wrapped_proof() {
+        check_wrapped_proof::<Sp1Groth16Verifier>("groth16.bin");
+        assert!(Sp1WrappedProof::<Sp1PlonkVerifier>::new(read_fixture("groth16.bin"))
+            .verify(&Sp1WrappedVkey(Sp1Vkey { vkey_bytes: read_fixture("vkey.bin") }))
+            .is_err());
+    }
+
+    #[test]
+    #[ignore = "needs the sp1 fixtures of scripts/sp1_wrapped_fixture"]
+    fn test_sp1_plonk_wrapped_proof() {
+        check_wrapped_proof::<Sp1PlonkVerifier>("plonk.bin");
+        assert!(Sp1WrappedProof::<Sp1Groth16Verifier>::new(read_fixture("plonk.bin"))
+            .verify(&Sp1WrappedVkey(Sp1Vkey { vkey_bytes: read_fixture("vkey.bin") }))
+            .is_err());
+    }
+
+    #[test]
+    fn test_sp1_wrapped_garbage_is_rejected() {
+        let vkey = Sp1WrappedVkey(Sp1Vkey { vkey_bytes: vec![1, 2, 3] });
+        assert!(vkey.validate().is_err());
+        assert!(Sp1Groth16Proof::new(vec![1, 2, 3]).verify(&vkey).is_err());
+        assert!(Sp1PlonkProof::new(vec![1, 2, 3]).verify(&vkey).is_err());
+    }
+
+    #[test]
+    fn test_sp1_proof_of_another_kind_is_rejected() {
+        let core_proof = SP1ProofWithPublicValues {
+            proof: SP1Proof::Core(vec![]),
+            public_values: sp1_sdk::SP1PublicValues::new(),
+            sp1_version: "v4.0.0".to_string(),
+        };
+        let proof_bytes = bincode::serialize(&core_proof).unwrap();
+        // the kind is checked before the vkey is read
+        let vkey = Sp1WrappedVkey(Sp1Vkey { vkey_bytes: vec![] });
+
+        let err = Sp1Groth16Proof::new(proof_bytes.clone()).verify(&vkey).unwrap_err();
+        assert!(err.to_string().contains("sp1 proof is not groth16 wrapped"));
+        let err = Sp1PlonkProof::new(proof_bytes).verify(&vkey).unwrap_err();
+        assert!(err.to_string().contains("sp1 proof is not plonk wrapped"));
+    }
+}
@@ -2,4 +2,5 @@ pub mod file;
 pub mod keccak;
 pub mod logger;
 pub mod paths;
-pub mod error_line;
\ No newline at end of file
+pub mod error_line;
+pub mod retry;
\ No newline at end of file
@@ -0,0 +1,122 @@
+use std::{future::Future, time::{Duration, Instant}};
+
+use anyhow::Result as AnyhowResult;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use tracing::{error, info};
+
+// Marks an error as not worth retrying, e.g. a rejected request or an invalid receipt
+#[derive(Debug)]
+pub struct FatalError(pub String);
+
+impl std::fmt::Display for FatalError {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        write!(fmt, "{}", self.0)
+    }
+}
+
+impl std::error::Error for FatalError {}
+
+pub fn is_retryable(error: &anyhow::Error) -> bool {
+    !error.chain().any(|cause| cause.is::<FatalError>())
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct RetryPolicy {
+    pub max_attempts: u32,
+    pub initial_delay_ms: u64,
+    pub max_delay_ms: u64,
+    pub multiplier: f64,
+    // fraction of the delay randomly added or removed, 0.0 disables jitter
+    pub jitter: f64,
+    // give up once this much time has passed since the first attempt
+    pub max_elapsed_secs: Option<u64>,
+}
+
+impl RetryPolicy {
+    // delay before the retry following the given (1-based) failed attempt, without jitter
+    pub fn get_base_delay(&self, attempt: u32) -> Duration {
+        let exponent = attempt.saturating_sub(1) as i32;
+        let delay_ms = (self.initial_delay_ms as f64) * self.multiplier.powi(exponent);
+        Duration::from_millis(delay_ms.min(self.max_delay_ms as f64) as u64)
+    }
+
+    pub fn get_delay(&self, attempt: u32) -> Duration {
+        let base_delay = self.get_base_delay(attempt);
+        if self.jitter <= 0.0 {
+            return base_delay;
+        }
+        let jitter_factor = rand::thread_rng().gen_range(-self.jitter..=self.jitter);
+        base_delay.mul_f64((1.0 + jitter_factor).max(0.0))
+    }
+}
+
+/*
+    Runs `operation` until it succeeds, returns a fatal error, or the policy runs out of attempts/time.
+    The last error is returned as is.
+ */
+pub async fn retry_with_policy<T, F, Fut>(policy: &RetryPolicy, operation_name: &str, mut operation: F) -> AnyhowResult<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = AnyhowResult<T>>,
+{
+    let start = Instant::now();
+    let mut attempt = 0;
+    loop {
+        attempt += 1;
+        let error = match operation().await {
+            Ok(value) => return Ok(value),
+            Err(e) => e,
+        };
+
+        if !is_retryable(&error) {
+            error!("{} failed with fatal error, not retrying: {:?}", operation_name, error);
+            return Err(error);
+        }
+        if attempt >= policy.max_attempts {
+            error!("{} failed with max retry count: {}, error: {:?}", operation_name, attempt, error);
+            return Err(error);
+        }
+
+        let delay = policy.get_delay(attempt);
+        if let Some(max_elapsed_secs) = policy.max_elapsed_secs {
+            if start.elapsed() + delay > Duration::from_secs(max_elapsed_secs) {
+                error!("{} failed, max elapsed time of {}s reached after {} attempts, error: {:?}", operation_name, max_elapsed_secs, attempt, error);
+                return Err(error);
+            }
+        }
+
+        error!("{} failed... retrying with count: {}, error: {:?}", operation_name, attempt, error);
+        info!("Trying {} again in {:?}", operation_name, delay);
+        tokio::time::sleep(delay).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_base_delay_is_exponential_and_capped() {
+        let policy = RetryPolicy {
+            max_attempts: 5,
+            initial_delay_ms: 1000,
+            max_delay_ms: 5000,
+            multiplier: 2.0,
+            jitter: 0.0,
+            max_elapsed_secs: None,
+        };
+        assert_eq!(policy.get_delay(1), Duration::from_millis(1000));
+        assert_eq!(policy.get_delay(2), Duration::from_millis(2000));
+        assert_eq!(policy.get_delay(3), Duration::from_millis(4000));
+        assert_eq!(policy.get_delay(4), Duration::from_millis(5000));
+    }
+
+    #[test]
+    fn test_fatal_error_is_not_retryable() {
+        let error = anyhow::anyhow!(FatalError("bad request".to_string()));
+        assert!(!is_retryable(&error));
+        assert!(!is_retryable(&error.context("while calling api")));
+        assert!(is_retryable(&anyhow::anyhow!("connection reset")));
+    }
+}
@@ -13,7 +13,7 @@ use quantum_circuits_interface::ffi::circuit_builder::{
     Risc0Data, Sp1Data, G1, G1A, G2,
 };
 use quantum_db::repository::{
-    bonsai_image::get_aggregate_circuit_bonsai_image, proof_repository::{update_proof_status, update_superproof_id_in_proof}, superproof_repository::{
+    bonsai_image::get_aggregate_circuit_bonsai_image, proof_repository::{clear_superproof_id_in_proof, increment_sp1_aggregation_attempts, update_proof_status}, superproof_repository::{
         update_cycles_in_superproof, update_r0_leaves_path,
         update_r0_receipts_path, update_r0_root, update_sp1_leaves_path, update_sp1_root,
         update_sp1_snark_receipt_path, update_superproof_agg_time, update_superproof_pis_path,
@@ -21,10 +21,12 @@ use quantum_db::repository::{
     }, user_circuit_data_repository::get_user_circuit_data_by_circuit_hash
 };
 use quantum_types::{
-    enums::{proof_status::ProofStatus, proving_schemes::ProvingSchemes},
+    dispatch_scheme,
+    enums::proof_status::ProofStatus,
+    schemes::Scheme,
     traits::{pis::Pis, proof::Proof, vkey::Vkey},
     types::{
-        config::ConfigData, db::proof::Proof as DBProof, gnark_groth16::{GnarkGroth16Pis, GnarkGroth16Vkey, SuperproofGnarkGroth16Proof}, gnark_plonk::{GnarkPlonkPis, GnarkPlonkVkey}, halo2_plonk::{Halo2PlonkPis, Halo2PlonkVkey}, halo2_poseidon::{Halo2PoseidonPis, Halo2PoseidonVkey}, nitro_att::{NitroAttPis, NitroAttVkey}, plonk2::{Plonky2Pis, Plonky2Vkey}, riscs0::{Risc0Pis, Risc0Vkey}, snarkjs_groth16::{SnarkJSGroth16Pis, SnarkJSGroth16Vkey}, sp1::{Sp1Proof, Sp1Vkey}
+        config::ConfigData, db::proof::Proof as DBProof, gnark_groth16::{GnarkGroth16Pis, SuperproofGnarkGroth16Proof}, sp1::{Sp1Proof, Sp1Vkey}
     },
 };
 use quantum_utils::{
@@ -39,7 +41,7 @@ use risc0_zkvm::{serde::to_vec, Receipt};
 use serde::Serialize;
 use sp1_sdk::{ProverClient, SP1ProofWithPublicValues, SP1ProvingKey, SP1VerifyingKey};
 use tokio::time;
-use tracing::info;
+use tracing::{error, info};
 use utils::hash::{Hasher, KeccakHasher};
 
 // superroot = digest( risc0_root || sp1_root )
@@ -62,35 +64,26 @@ pub async fn handle_proof_aggregation_and_updation(
     let risc0_handle = tokio::spawn(  async move  {handle_proof_aggregation_r0(proof_r0_clone, superproof_id, &config_clone).await});
     let mut sp1_handle = tokio::spawn( async move {handle_proof_aggregation_sp1(proof_sp1_clone, superproof_id, &config_clone_sp1).await});
     
-    let sp1_aggregation = tokio::select! {
-        result = &mut sp1_handle => {
-            match result {
-                Ok(agg) => agg,
-                Err(e) => {
-                    println!("got tokio error");
-                    error_line!(format!("sp1 tokio task panked or aborted:{}",e));
-                    for proof in proofs_sp1.iter() {
-                        update_proof_status(get_pool().await, proof.id.unwrap_or_else(|| 0), ProofStatus::Reduced).await?;
-                        update_superproof_id_in_proof(get_pool().await, proof.id.unwrap_or_else(|| 0), 0).await?;
-                    }
-                    proofs_sp1.clear();
-                    handle_no_sp1_proof_aggregation(config)
-
-                },
-            }
+    let sp1_aggregation_timeout = Duration::from_secs(config.sp1_aggregation_timeout_secs);
+    let sp1_aggregation = match time::timeout(sp1_aggregation_timeout, &mut sp1_handle).await {
+        Ok(Ok(Ok(agg))) => agg,
+        Ok(Ok(Err(e))) => {
+            error!("sp1 proof aggregation failed: {:?}", e);
+            release_sp1_proofs(proofs_sp1, config).await?;
+            handle_no_sp1_proof_aggregation(config)?
         }
-        _ = time::sleep(Duration::from_secs(7*60)) => {
-            println!("sp1 task timeout");
-            error_line!("----------------sp1 proof aggregation Timeout after 10 minutes----------");
+        Ok(Err(e)) => {
+            error!("sp1 tokio task panicked or aborted: {:?}", e);
+            release_sp1_proofs(proofs_sp1, config).await?;
+            handle_no_sp1_proof_aggregation(config)?
+        }
+        Err(_) => {
+            error!("sp1 proof aggregation timed out after {:?} seconds", config.sp1_aggregation_timeout_secs);
             sp1_handle.abort();
-            for proof in proofs_sp1.iter() {
-                update_proof_status(get_pool().await, proof.id.unwrap_or_else(|| 0), ProofStatus::Reduced).await?;
-                update_superproof_id_in_proof(get_pool().await, proof.id.unwrap_or_else(|| 0), 0).await?;
-            }
-            proofs_sp1.clear();
-            handle_no_sp1_proof_aggregation(config)
+            release_sp1_proofs(proofs_sp1, config).await?;
+            handle_no_sp1_proof_aggregation(config)?
         }
-    }?;
+    };
 
     let sp1_snark_proof = sp1_aggregation.0;
     let sp1_root_bytes = sp1_aggregation.1;
@@ -280,6 +273,26 @@ async fn handle_proof_aggregation_sp1(
     Ok((aggregated_proof, root, aggregation_time))
 }
 
+/*
+    Takes the sp1 proofs out of the superproof after a failed sp1 aggregation, the superproof goes ahead with the empty sp1 proof.
+    Proofs go back to Reduced for the next superproof, until they run out of `sp1_max_aggregation_attempts`.
+ */
+async fn release_sp1_proofs(proofs_sp1: &mut Vec<DBProof>, config: &ConfigData) -> AnyhowResult<()> {
+    for proof in proofs_sp1.iter() {
+        let proof_id = proof.id.unwrap_or_else(|| 0);
+        clear_superproof_id_in_proof(get_pool().await, proof_id).await?;
+        let attempts = increment_sp1_aggregation_attempts(get_pool().await, proof_id).await?;
+        if attempts >= config.sp1_max_aggregation_attempts {
+            error!("sp1 proof {:?} failed aggregation {:?} times, marking it as failed", proof_id, attempts);
+            update_proof_status(get_pool().await, proof_id, ProofStatus::AggregationFailed).await?;
+        } else {
+            update_proof_status(get_pool().await, proof_id, ProofStatus::Reduced).await?;
+        }
+    }
+    proofs_sp1.clear();
+    Ok(())
+}
+
 fn handle_no_sp1_proof_aggregation(config: &ConfigData) -> AnyhowResult<(SP1ProofWithPublicValues, [u8; 32], Duration)>{
     info!("No new sp1 proofs, using old aggregated_sp1_snark_receipt");
     // use hardocoded aggregated_sp1_snark_receipt_path
@@ -291,6 +304,17 @@ fn handle_no_sp1_proof_aggregation(config: &ConfigData) -> AnyhowResult<(SP1Proo
     Ok((sp1_snark_proof, sp1_root_bytes, sp1_aggregation_time))
 }
 
+// Leaf inputs of a proof in the risc0 aggregation: the scheme's protocol id, vkey hash and pis hash
+fn get_aggregation_leaf_data<S: Scheme>(vk_path: &str, pis_path: &str) -> AnyhowResult<(u8, [u8; 32], [u8; 32])> {
+    let protocol_id = S::AGGREGATION_PROTOCOL_ID.ok_or(anyhow!(error_line!(format!(
+        "{} proofs are not aggregated in the risc0 aggregation",
+        S::PROVING_SCHEME.to_string()
+    ))))?;
+    let protocol_vkey = S::Vkey::read_vk(vk_path)?;
+    let protocol_pis = S::Pis::read_pis(pis_path)?;
+    Ok((protocol_id, protocol_vkey.keccak_hash()?, protocol_pis.keccak_hash()?))
+}
+
 async fn handle_proof_aggregation_r0(
     proofs: Vec<DBProof>,
     superproof_id: u64,
@@ -313,75 +337,12 @@ async fn handle_proof_aggregation_r0(
         let protocol_circuit_vkey_path = user_circuit_data.vk_path;
         let protocol_pis_path = proof.pis_path.clone();
 
-        match user_circuit_data.proving_scheme {
-            ProvingSchemes::Groth16 => {
-                let protocol_vkey = SnarkJSGroth16Vkey::read_vk(&protocol_circuit_vkey_path)?;
-                protocol_vkey_hashes.push(protocol_vkey.keccak_hash()?);
-
-                let protocol_pis = SnarkJSGroth16Pis::read_pis(&protocol_pis_path)?;
-                protocol_pis_hashes.push(protocol_pis.keccak_hash()?);
-                protocol_ids.push(0);
-            }
-            ProvingSchemes::Halo2Plonk => {
-                let protocol_vkey = Halo2PlonkVkey::read_vk(&protocol_circuit_vkey_path)?;
-                protocol_vkey_hashes.push(protocol_vkey.keccak_hash()?);
-
-                let protocol_pis = Halo2PlonkPis::read_pis(&protocol_pis_path)?;
-                protocol_pis_hashes.push(protocol_pis.keccak_hash()?);
-                protocol_ids.push(1);
-            }
-            ProvingSchemes::GnarkGroth16 => {
-                let protocol_vkey = GnarkGroth16Vkey::read_vk(&protocol_circuit_vkey_path)?;
-                protocol_vkey_hashes.push(protocol_vkey.keccak_hash()?);
-
-                let protocol_pis = GnarkGroth16Pis::read_pis(&protocol_pis_path)?;
-                protocol_pis_hashes.push(protocol_pis.keccak_hash()?);
-                protocol_ids.push(3);
-            }
-            ProvingSchemes::GnarkPlonk => {
-                let protocol_vkey = GnarkPlonkVkey::read_vk(&protocol_circuit_vkey_path)?;
-                protocol_vkey_hashes.push(protocol_vkey.keccak_hash()?);
-
-                let protocol_pis = GnarkPlonkPis::read_pis(&protocol_pis_path)?;
-                protocol_pis_hashes.push(protocol_pis.keccak_hash()?);
-                protocol_ids.push(4);
-            }
-            ProvingSchemes::Plonky2 => {
-                let protocol_vkey = Plonky2Vkey::read_vk(&protocol_circuit_vkey_path)?;
-                protocol_vkey_hashes.push(protocol_vkey.keccak_hash()?);
-
-                let protocol_pis = Plonky2Pis::read_pis(&protocol_pis_path)?;
-                protocol_pis_hashes.push(protocol_pis.keccak_hash()?);
-                protocol_ids.push(2);
-            }
-            ProvingSchemes::Halo2Poseidon => {
-                let protocol_vkey = Halo2PoseidonVkey::read_vk(&protocol_circuit_vkey_path)?;
-                protocol_vkey_hashes.push(protocol_vkey.keccak_hash()?);
-
-                let protocol_pis = Halo2PoseidonPis::read_pis(&protocol_pis_path)?;
-                protocol_pis_hashes.push(protocol_pis.keccak_hash()?);
-                protocol_ids.push(5);
-            }
-            ProvingSchemes::Risc0 => {
-                let protocol_vkey = Risc0Vkey::read_vk(&protocol_circuit_vkey_path)?;
-                protocol_vkey_hashes.push(protocol_vkey.keccak_hash()?);
-
-                let protocol_pis = Risc0Pis::read_pis(&protocol_pis_path)?;
-                protocol_pis_hashes.push(protocol_pis.keccak_hash()?);
-                protocol_ids.push(6);
-            }
-            ProvingSchemes::NitroAtt => {
-                let protocol_vkey = NitroAttVkey::read_vk(&protocol_circuit_vkey_path)?;
-                protocol_vkey_hashes.push(protocol_vkey.keccak_hash()?);
-
-                let protocol_pis = NitroAttPis::read_pis(&protocol_pis_path)?;
-                protocol_pis_hashes.push(protocol_pis.keccak_hash()?);
-                protocol_ids.push(8);
-            }
-            _ => {
-                panic!("Shouldnt happen!!")
-            }
-        }
+        let (protocol_id, protocol_vkey_hash, protocol_pis_hash) = dispatch_scheme!(user_circuit_data.proving_scheme, S => {
+            get_aggregation_leaf_data::<S>(&protocol_circuit_vkey_path, &protocol_pis_path)?
+        });
+        protocol_ids.push(protocol_id);
+        protocol_vkey_hashes.push(protocol_vkey_hash);
+        protocol_pis_hashes.push(protocol_pis_hash);
     }
 
     let (agg_input, leaves, batch_root_bytes) = get_agg_inputs::<KeccakHasher>(
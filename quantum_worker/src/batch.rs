@@ -0,0 +1,112 @@
+use quantum_types::{enums::batch_close_reason::BatchCloseReason, types::db::proof::Proof};
+
+/*
+    Picks the risc0 proofs of the next superproof from the `Reduced` queue, in queue order.
+    The batch closes at `max_batch_size` proofs, or before the proof that would take the reduction cycles of the batch
+    over `max_cycle_count`. The rest of the queue waits for the next superproof.
+    `queue` holds one proof more than `max_batch_size` when more are waiting, so a full batch is told apart from a drained queue.
+    Proofs of `isolation_attempts` failed superproofs never share a batch, one at the head of the queue is aggregated alone.
+ */
+pub fn select_batch(queue: Vec<Proof>, max_batch_size: u64, max_cycle_count: u64, isolation_attempts: u64) -> (Vec<Proof>, BatchCloseReason) {
+    let mut batch = vec![];
+    let mut batch_cycle_used: u64 = 0;
+    for proof in queue {
+        if is_isolated(&proof, isolation_attempts) {
+            if batch.is_empty() {
+                return (vec![proof], BatchCloseReason::Isolated);
+            }
+            continue;
+        }
+        if batch.len() as u64 >= max_batch_size {
+            return (batch, BatchCloseReason::MaxBatchSize);
+        }
+        let cycle_used = proof.cycle_used.unwrap_or(0);
+        // a proof above the limit on its own still gets a superproof, alone
+        if !batch.is_empty() && batch_cycle_used.saturating_add(cycle_used) > max_cycle_count {
+            return (batch, BatchCloseReason::MaxCycleCount);
+        }
+        batch_cycle_used = batch_cycle_used.saturating_add(cycle_used);
+        batch.push(proof);
+    }
+    (batch, BatchCloseReason::QueueDrained)
+}
+
+pub fn is_isolated(proof: &Proof, isolation_attempts: u64) -> bool {
+    proof.aggregation_attempts.unwrap_or(0) >= isolation_attempts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quantum_types::enums::proof_status::ProofStatus;
+
+    fn reduced_proof(id: u64, cycle_used: u64, aggregation_attempts: u64) -> Proof {
+        Proof {
+            id: Some(id),
+            proof_hash: format!("0x{}", id),
+            pis_path: String::new(),
+            proof_path: String::new(),
+            input_id: None,
+            session_id: None,
+            superproof_id: None,
+            reduction_time: None,
+            proof_status: ProofStatus::Reduced,
+            user_circuit_hash: String::new(),
+            cycle_used: Some(cycle_used),
+            sp1_aggregation_attempts: None,
+            aggregation_attempts: Some(aggregation_attempts),
+        }
+    }
+
+    fn ids(batch: &Vec<Proof>) -> Vec<u64> {
+        batch.iter().map(|proof| proof.id.unwrap()).collect()
+    }
+
+    #[test]
+    fn closes_on_batch_size() {
+        let queue = (1..=4).map(|id| reduced_proof(id, 10, 0)).collect();
+        let (batch, reason) = select_batch(queue, 3, 1000, 2);
+        assert_eq!(ids(&batch), vec![1, 2, 3]);
+        assert_eq!(reason, BatchCloseReason::MaxBatchSize);
+    }
+
+    #[test]
+    fn closes_on_cycle_count() {
+        let queue = vec![reduced_proof(1, 40, 0), reduced_proof(2, 50, 0), reduced_proof(3, 20, 0)];
+        let (batch, reason) = select_batch(queue, 32, 100, 2);
+        assert_eq!(ids(&batch), vec![1, 2]);
+        assert_eq!(reason, BatchCloseReason::MaxCycleCount);
+    }
+
+    #[test]
+    fn takes_an_oversized_proof_alone() {
+        let queue = vec![reduced_proof(1, 500, 0), reduced_proof(2, 10, 0)];
+        let (batch, reason) = select_batch(queue, 32, 100, 2);
+        assert_eq!(ids(&batch), vec![1]);
+        assert_eq!(reason, BatchCloseReason::MaxCycleCount);
+    }
+
+    #[test]
+    fn drains_a_short_queue() {
+        let queue = vec![reduced_proof(1, 10, 0), reduced_proof(2, 10, 0)];
+        let (batch, reason) = select_batch(queue, 32, 100, 2);
+        assert_eq!(ids(&batch), vec![1, 2]);
+        assert_eq!(reason, BatchCloseReason::QueueDrained);
+    }
+
+    #[test]
+    fn isolates_a_repeatedly_failed_proof() {
+        let queue = vec![reduced_proof(1, 10, 2), reduced_proof(2, 10, 0), reduced_proof(3, 10, 1)];
+        let (batch, reason) = select_batch(queue, 32, 100, 2);
+        assert_eq!(ids(&batch), vec![1]);
+        assert_eq!(reason, BatchCloseReason::Isolated);
+    }
+
+    #[test]
+    fn leaves_an_isolated_proof_out_of_a_shared_batch() {
+        let queue = vec![reduced_proof(1, 10, 1), reduced_proof(2, 10, 3), reduced_proof(3, 10, 0)];
+        let (batch, reason) = select_batch(queue, 32, 100, 2);
+        assert_eq!(ids(&batch), vec![1, 3]);
+        assert_eq!(reason, BatchCloseReason::QueueDrained);
+    }
+}
@@ -1,41 +1,70 @@
 use std::time::Duration;
 
-use bonsai_sdk::non_blocking::{Client, SessionId};
-use quantum_db::repository::{bonsai_image::get_bonsai_image_by_image_id, proof_repository::{update_cycle_used_in_proof, update_session_id_in_proof}, superproof_repository::{update_session_id_superproof, update_snark_session_id_superproof}};
-use quantum_utils::error_line;
+use quantum_db::repository::{bonsai_image::get_bonsai_image_by_image_id, proof_repository::{get_proof_by_proof_id, update_cycle_used_in_proof, update_session_id_in_proof}, superproof_repository::{get_superproof_by_id, update_session_id_superproof, update_snark_session_id_superproof}};
+use once_cell::sync::OnceCell;
+use quantum_types::types::config::{ConfigData, RetryConfig};
+use quantum_utils::{error_line, retry::{retry_with_policy, FatalError, RetryPolicy}};
 use risc0_zkvm::Receipt;
 use tracing::{info, error};
 
-use crate::{connection::get_pool, worker::increment_cycle};
+use crate::{connection::get_pool, prover_backend::{get_prover_backend, ProverBackend, SessionState}};
 
 use anyhow::{anyhow, Result as AnyhowResult};
 
-pub fn get_bonsai_client() -> AnyhowResult<Client> {
-    let client = Client::from_env(risc0_zkvm::VERSION)?;
-    Ok(client)
+static BONSAI_RETRY_POLICY: OnceCell<RetryPolicy> = OnceCell::new();
+
+// Marks a session failure caused by the proofs it was given, i.e. its guest rejected them or its receipt does not verify
+#[derive(Debug)]
+pub struct ProofRejected(pub String);
+
+impl std::fmt::Display for ProofRejected {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        write!(fmt, "{}", self.0)
+    }
 }
 
-pub async fn execute_proof_reduction(input_data: &Vec<u8>, image_id: &str, proof_id: u64, assumptions: &Vec<String>) -> AnyhowResult<(Option<Receipt>, String)> {
-    
-    let client = get_bonsai_client()?;
+impl std::error::Error for ProofRejected {}
 
-    // TODO: store it in DB
-    let input_id = client.upload_input((*input_data).clone()).await?;
-    println!("input_id: {:?}", input_id);
+pub fn is_proof_rejected(error: &anyhow::Error) -> bool {
+    error.downcast_ref::<ProofRejected>().is_some()
+}
 
+pub fn init_bonsai_retry_policy(config: &ConfigData) {
+    if BONSAI_RETRY_POLICY.set(config.retry.bonsai.clone()).is_ok() {
+        info!("bonsai retry policy: {:?}", config.retry.bonsai);
+    }
+}
+
+fn get_bonsai_retry_policy() -> &'static RetryPolicy {
+    BONSAI_RETRY_POLICY.get_or_init(|| RetryConfig::default().bonsai)
+}
+
+pub async fn execute_proof_reduction(input_data: &Vec<u8>, image_id: &str, proof_id: u64, assumptions: &Vec<String>) -> AnyhowResult<(Option<Receipt>, String)> {
+    
+    let backend = get_prover_backend();
     let bonsai_image = get_bonsai_image_by_image_id(get_pool().await, image_id).await?;
 
-    // Wether to run in execute only mode
-    let execute_only = false;
+    // Re-attach to the session of a previous attempt, if it is still alive
+    let proof = get_proof_by_proof_id(get_pool().await, proof_id).await?;
+    let session_uuid_id = match get_resumable_session(backend, &proof.session_id).await? {
+        Some(session_id) => {
+            info!("resuming session {:?} for proof_id {:?}", session_id, proof_id);
+            session_id
+        }
+        None => {
+            // TODO: store it in DB
+            let input_id = backend.upload_input((*input_data).clone()).await?;
+            info!("input_id: {:?}", input_id);
 
-    //TODO: store in DB
-    let session = client.create_session(image_id.to_string(), input_id, (*assumptions).clone(), execute_only).await?;
-    let session_uuid_id = session.uuid.clone();
-    println!("sessionId: {:?}", session.uuid);
+            let session_uuid_id = backend.create_session(image_id, &input_id, assumptions).await?;
+            info!("sessionId: {:?}", session_uuid_id);
 
-    update_session_id_in_proof(get_pool().await, proof_id, &session.uuid).await?;
+            update_session_id_in_proof(get_pool().await, proof_id, &session_uuid_id).await?;
+            session_uuid_id
+        }
+    };
 
-    let (receipt, cycle_used) = check_session_status(session, client, &bonsai_image.circuit_verifying_id).await?;
+    let (receipt, cycle_used) = check_session_status(&session_uuid_id, backend, &bonsai_image.circuit_verifying_id).await?;
 
     update_cycle_used_in_proof(get_pool().await, proof_id, cycle_used).await?;
 
@@ -43,187 +72,172 @@ pub async fn execute_proof_reduction(input_data: &Vec<u8>, image_id: &str, proof
 }
 
 pub async fn execute_proof_reduction_with_retry(input_data: &Vec<u8>, image_id: &str, proof_id: u64, assumptions: &Vec<String>) -> AnyhowResult<(Option<Receipt>, String)> {
-    let mut retries = 0;
-    let receipt;
-    let session_uuid_id;
-    loop {
-        match execute_proof_reduction(input_data, image_id, proof_id, assumptions).await {
-            Ok(r) => {
-                receipt = r.0;
-                session_uuid_id = r.1;
-                break;
-            },
-            Err(e) => {
-                retries += 1;
-                if retries >= 3 {
-                    error!("proof reduction failed in bonsai with max retry count: {}", retries);
-                    return Err(e);
-                }
-                error!("proof reduction failed in bonsai... retrying with count: {}", retries);
-            },
-        };
-        tokio::time::sleep(Duration::from_secs(120)).await;
-    }
-    
-    Ok((receipt, session_uuid_id))
+    retry_with_policy(get_bonsai_retry_policy(), "proof reduction in bonsai", || {
+        execute_proof_reduction(input_data, image_id, proof_id, assumptions)
+    }).await
 }
 
 pub async fn execute_aggregation(input_data: &Vec<u8>, image_id: &str, assumptions: &Vec<String>, superproof_id: u64, ) -> AnyhowResult<(Option<Receipt>, String, u64)> {
     
-    let client = get_bonsai_client()?;
+    let backend = get_prover_backend();
     let bonsai_image = get_bonsai_image_by_image_id(get_pool().await, image_id).await?;
-    // TODO: store it in DB
-    let input_id = client.upload_input(input_data.clone()).await?;
-    println!("input_id: {:?}", input_id);
 
-    let execute_only = false;
-    
-    let session = client.create_session(image_id.to_string(), input_id, assumptions.clone(), execute_only).await?;
-    let session_uuid_id = session.uuid.clone();
-    println!("sessionId: {:?}", session.uuid);
-    update_session_id_superproof(get_pool().await,  &session.uuid, superproof_id).await?;
+    // Re-attach to the aggregation session of a previous attempt, if it is still alive
+    let superproof = get_superproof_by_id(get_pool().await, superproof_id).await?;
+    let session_uuid_id = match get_resumable_session(backend, &superproof.session_id).await? {
+        Some(session_id) => {
+            info!("resuming aggregation session {:?} for superproof_id {:?}", session_id, superproof_id);
+            session_id
+        }
+        None => {
+            // TODO: store it in DB
+            let input_id = backend.upload_input(input_data.clone()).await?;
+            info!("input_id: {:?}", input_id);
 
-    let (receipt, cycle_used )= check_session_status(session, client, &bonsai_image.circuit_verifying_id).await?;    
+            let session_uuid_id = backend.create_session(image_id, &input_id, assumptions).await?;
+            info!("sessionId: {:?}", session_uuid_id);
+            update_session_id_superproof(get_pool().await,  &session_uuid_id, superproof_id).await?;
+            session_uuid_id
+        }
+    };
+
+    let (receipt, cycle_used )= check_session_status(&session_uuid_id, backend, &bonsai_image.circuit_verifying_id).await?;    
     Ok((receipt, session_uuid_id, cycle_used))
 }
 
 pub async fn execute_aggregation_with_retry(input_data: &Vec<u8>, image_id: &str, assumptions: &Vec<String>, superproof_id: u64) -> AnyhowResult<(Option<Receipt>, String, u64)> {
-    let mut retries = 0;
-    let receipt;
-    let session_uuid_id;
-    let cycle_used;
-    loop {
-        match execute_aggregation(input_data, image_id, assumptions, superproof_id).await {
-            Ok(r) => {
-                receipt = r.0;
-                session_uuid_id = r.1;
-                cycle_used = r.2;
-                break;
-            },
-            Err(e) => {
-                retries += 1;
-                if retries >= 3 {
-                    error!("aggregation failed in bonsai with max retry count: {}", retries);
-                    return Err(e);
-                }
-                error!("aggregation failed in bonsai... retrying with count: {}", retries);
-            },
-        };
-        tokio::time::sleep(Duration::from_secs(120)).await;
+    retry_with_policy(get_bonsai_retry_policy(), "aggregation in bonsai", || {
+        execute_aggregation(input_data, image_id, assumptions, superproof_id)
+    }).await
+}
+
+/*
+    Returns the session id if the session is still running or has succeeded, and None only once it has failed or stopped.
+    An error fetching its status is returned as is, so the caller's retry checks the same session again instead of starting a new one.
+ */
+pub async fn get_resumable_session(backend: &dyn ProverBackend, session_id: &Option<String>) -> AnyhowResult<Option<String>> {
+    let session_id = match session_id {
+        Some(session_id) => session_id.clone(),
+        None => return Ok(None),
+    };
+    let status = match backend.session_status(&session_id).await {
+        Ok(status) => status,
+        Err(e) => {
+            error!("not able to fetch status of previous session {:?}: {:?}", session_id, e);
+            return Err(e);
+        }
+    };
+    match status {
+        SessionState::Running(_) | SessionState::Succeeded(_) => Ok(Some(session_id)),
+        SessionState::Failed(error_msg) | SessionState::Stopped(error_msg) => {
+            info!("previous session {:?} failed: {:?}, creating a new one", session_id, error_msg);
+            Ok(None)
+        }
     }
-    
-    Ok((receipt, session_uuid_id, cycle_used))
 }
 
-async fn check_session_status(session: SessionId, client: Client, circuit_verifying_id: &[u32;8] ) -> AnyhowResult<(Option<Receipt>, u64)> {
-    let mut receipt: Option<Receipt> = None;
-    loop {
-        let res = session.status(&client).await?;
-        println!("Current status: {}", res.status);
-        if res.status == "RUNNING" {
-            info!(
-                "Current status for session_id {} : {} - state: {} - continue polling...",
-                session.uuid,
-                res.status,
-                res.state.unwrap_or_default()
-            );
-            tokio::time::sleep(Duration::from_secs(15)).await;
-            continue;
+pub async fn get_resumable_snark_session(backend: &dyn ProverBackend, snark_session_id: &Option<String>) -> AnyhowResult<Option<String>> {
+    let snark_session_id = match snark_session_id {
+        Some(snark_session_id) => snark_session_id.clone(),
+        None => return Ok(None),
+    };
+    let status = match backend.snark_session_status(&snark_session_id).await {
+        Ok(status) => status,
+        Err(e) => {
+            error!("not able to fetch status of previous snark session {:?}: {:?}", snark_session_id, e);
+            return Err(e);
         }
-        if res.status == "SUCCEEDED" {
-            // TODO: store Risc0 status in DB
-            info!("proof reduction completed for session_id: {:?}", session.uuid);
-            
-            //TODO: remove the unwrap
-            let cycle_used = res.stats.unwrap().cycles;
-            info!("cycle used in session {:?}: {:?}", &session.uuid, cycle_used);
-            increment_cycle(cycle_used as i64).await;
-            
-            // Download the receipt, containing the output
-            let receipt_url = res
-                .receipt_url
-                .expect("API error, missing receipt on completed session");
-
-            let receipt_buf = client.download(&receipt_url).await?;
-            receipt = Some(bincode::deserialize(&receipt_buf)?);
-            // let METHOD_ID = get_verifier_id(0);
-            receipt.clone().unwrap().verify(circuit_verifying_id.clone())
-                .expect("Receipt verification failed");
-            return Ok((receipt, cycle_used));
-            
-        } else {
-            info!("sesion status: {:?}", res.status);
-            error!("error occured in bonsai session: {:?} with status {:?}, and error messgae: {:?}", &session.uuid, res.status, res.error_msg);
-            return Err(anyhow!(error_line!("bonsai_session_failed")));
-            // panic!(
-            //     "Workflow exited: {} - | err: {}",
-            //     res.status,
-            //     res.error_msg.unwrap_or_default()
-            // );
-            // error!("session status: {:?}, wit")
+    };
+    match status {
+        SessionState::Running(_) | SessionState::Succeeded(_) => Ok(Some(snark_session_id)),
+        SessionState::Failed(error_msg) | SessionState::Stopped(error_msg) => {
+            info!("previous snark session {:?} failed: {:?}, creating a new one", snark_session_id, error_msg);
+            Ok(None)
         }
-        // break;
     }
-    // Ok(receipt)
 }
 
-pub async fn run_stark2snark_with_retry(agg_session_id: &str, superproof_id: u64) -> AnyhowResult<Option<Receipt>> {
-    let mut retries = 0;
-    let receipt;
+async fn check_session_status(session_id: &str, backend: &dyn ProverBackend, circuit_verifying_id: &[u32;8] ) -> AnyhowResult<(Option<Receipt>, u64)> {
     loop {
-        match run_stark2snark(agg_session_id, superproof_id).await {
-            Ok(r) => {
-                receipt = r;
-                break;
-            },
-            Err(e) => {
-                retries += 1;
-                if retries >= 3 {
-                    error!("stark2snark failed in bonsai with max retry count: {}", retries);
-                    return Err(e);
+        let res = backend.session_status(session_id).await?;
+        match res {
+            SessionState::Running(state) => {
+                info!(
+                    "Current status for session_id {} : RUNNING - state: {} - continue polling...",
+                    session_id,
+                    state.unwrap_or_default()
+                );
+                tokio::time::sleep(Duration::from_secs(15)).await;
+                continue;
+            }
+            SessionState::Succeeded(cycle_used) => {
+                // TODO: store Risc0 status in DB
+                info!("proof reduction completed for session_id: {:?}", session_id);
+
+                info!("cycle used in session {:?}: {:?}", session_id, cycle_used);
+
+                // Download the receipt, containing the output
+                let receipt = backend.fetch_receipt(session_id).await?;
+                if let Err(err) = receipt.verify(circuit_verifying_id.clone()) {
+                    let message = error_line!(format!("Receipt verification failed: {:?}", err));
+                    return Err(anyhow!(FatalError(message.clone())).context(ProofRejected(message)));
                 }
-                error!("stark2snark failed in bonsai... retrying with count: {}", retries);
-            },
-        };
-        tokio::time::sleep(Duration::from_secs(120)).await;
+                return Ok((Some(receipt), cycle_used));
+            }
+            SessionState::Failed(error_msg) => {
+                error!("error occured in bonsai session: {:?} with error messgae: {:?}", session_id, error_msg);
+                return Err(anyhow!(ProofRejected(error_line!(format!("bonsai_session_failed: {:?}", error_msg)))));
+            }
+            SessionState::Stopped(error_msg) => {
+                error!("bonsai session {:?} stopped with error messgae: {:?}", session_id, error_msg);
+                return Err(anyhow!(error_line!("bonsai_session_stopped")));
+            }
+        }
     }
-    Ok(receipt)
+}
+
+pub async fn run_stark2snark_with_retry(agg_session_id: &str, superproof_id: u64) -> AnyhowResult<Option<Receipt>> {
+    retry_with_policy(get_bonsai_retry_policy(), "stark2snark in bonsai", || {
+        run_stark2snark(agg_session_id, superproof_id)
+    }).await
 }
 
 pub async fn run_stark2snark(agg_session_id: &str, superproof_id: u64) -> AnyhowResult<Option<Receipt>> {
-    let client = get_bonsai_client()?;
-    let mut receipt: Option<Receipt> = None;
-    let snark_session = client.create_snark(agg_session_id.to_string()).await?;
-    println!("Created snark session: {}", snark_session.uuid);
-    update_snark_session_id_superproof(get_pool().await, &snark_session.uuid, superproof_id).await?;
+    let backend = get_prover_backend();
+    let receipt: Option<Receipt>;
+    let superproof = get_superproof_by_id(get_pool().await, superproof_id).await?;
+    let snark_session_id = match get_resumable_snark_session(backend, &superproof.snark_session_id).await? {
+        Some(snark_session_id) => {
+            info!("resuming snark session {:?} for superproof_id {:?}", snark_session_id, superproof_id);
+            snark_session_id
+        }
+        None => {
+            let snark_session_id = backend.create_snark_session(agg_session_id).await?;
+            info!("created snark session {:?} for superproof_id {:?}", snark_session_id, superproof_id);
+            update_snark_session_id_superproof(get_pool().await, &snark_session_id, superproof_id).await?;
+            snark_session_id
+        }
+    };
     loop {
-        let res = snark_session.status(&client).await.map_err(|err| anyhow!(error_line!(err)))?;
-        println!("Current status: {}", res.status,);
-        match res.status.as_str() {
-            "RUNNING" => {
-                println!("continue polling...");
+        let res = backend.snark_session_status(&snark_session_id).await?;
+        match res {
+            SessionState::Running(state) => {
+                info!(
+                    "Current status for snark session_id {} : RUNNING - state: {} - continue polling...",
+                    snark_session_id,
+                    state.unwrap_or_default()
+                );
                 tokio::time::sleep(Duration::from_secs(15)).await;
                 continue;
             }
-            "SUCCEEDED" => {
-                let snark_receipt_url = res.output.unwrap();
-
-                let receipt_buf = client.download(&snark_receipt_url).await?;
-                let snark_receipt: Receipt  = bincode::deserialize(&receipt_buf)?;
-                println!("Snark proof!: {snark_receipt:?}");
-                // let file = File::create("snark_receipt.json").unwrap();
-                // let mut writer = BufWriter::new(file);
-                // serde_json::to_writer(&mut writer, &snark_receipt.unwrap()).unwrap();
+            SessionState::Succeeded(_) => {
+                let snark_receipt = backend.fetch_snark_receipt(&snark_session_id).await?;
+                info!("snark proof completed for snark session_id: {:?}", snark_session_id);
                 receipt = Some(snark_receipt);
                 break;
             }
-            _ => {
-                // panic!(
-                //     "Workflow exited: {} err: {}",
-                //     res.status,
-                //     res.error_msg.unwrap_or_default()
-                // );
-                error!("error occured in bonsai session: {:?} with status {:?}, and error messgae: {:?}", &snark_session.uuid, res.status, res.error_msg);
+            SessionState::Failed(error_msg) | SessionState::Stopped(error_msg) => {
+                error!("error occured in bonsai session: {:?} with error messgae: {:?}", &snark_session_id, error_msg);
                 return Err(anyhow!(error_line!("bonsai_session_failed")));
             }
         }
@@ -233,9 +247,7 @@ pub async fn run_stark2snark(agg_session_id: &str, superproof_id: u64) -> Anyhow
 
 
 pub async fn upload_receipt(receipt: Receipt) -> AnyhowResult<String> {
-    let client = get_bonsai_client()?;
-    let serialized_receipt = bincode::serialize(&receipt)?;
-    let receipt_id = client.upload_receipt(serialized_receipt).await?;
+    let receipt_id = get_prover_backend().upload_receipt(&receipt).await?;
     Ok(receipt_id)
 }
 
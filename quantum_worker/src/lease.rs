@@ -0,0 +1,115 @@
+use std::{future::Future, time::Duration};
+
+use anyhow::{anyhow, Result as AnyhowResult};
+use once_cell::sync::Lazy;
+use quantum_db::repository::{proof_repository::update_proof_status, task_repository::{get_expired_leased_tasks, renew_task_lease, reset_expired_task}, worker_lock_repository::renew_worker_lock};
+use quantum_types::enums::{proof_status::ProofStatus, task_type::TaskType};
+use quantum_utils::error_line;
+use tokio::{task::JoinHandle, time::Instant};
+use tracing::{error, info};
+
+use crate::connection::get_pool;
+
+// Identifies this worker process in task leases, override with WORKER_ID
+pub static WORKER_ID: Lazy<String> = Lazy::new(|| {
+    match std::env::var("WORKER_ID") {
+        Ok(id) => id,
+        Err(_) => format!("{}-{}", std::env::var("HOSTNAME").unwrap_or(String::from("worker")), std::process::id()),
+    }
+});
+
+pub fn get_worker_id() -> &'static str {
+    WORKER_ID.as_str()
+}
+
+// Held by the single worker which aggregates superproofs, the others only reduce proofs
+pub const AGGREGATOR_LOCK: &str = "aggregator";
+
+// Keeps renewing the lease on a task until the returned handle is aborted
+pub fn spawn_task_heartbeat(task_id: u64, lease_secs: u64) -> JoinHandle<()> {
+    let heartbeat_interval = Duration::from_secs(std::cmp::max(lease_secs / 3, 1));
+    tokio::spawn(async move {
+        loop {
+            tokio::time::sleep(heartbeat_interval).await;
+            match renew_task_lease(get_pool().await, task_id, get_worker_id(), lease_secs).await {
+                Ok(true) => info!("renewed lease on task {}", task_id),
+                Ok(false) => error!("lease on task {} is no longer held by worker {}", task_id, get_worker_id()),
+                Err(e) => error!("error in renewing lease on task {}: {:?}", task_id, e),
+            }
+        }
+    })
+}
+
+/*
+    Keeps renewing the aggregator lock until the returned handle is aborted.
+    Returns once the lock is lost: taken over by another worker, or not renewed for a whole lease, after which
+    another worker may take it.
+ */
+pub fn spawn_aggregator_lock_heartbeat(lease_secs: u64) -> JoinHandle<()> {
+    let heartbeat_interval = Duration::from_secs(std::cmp::max(lease_secs / 3, 1));
+    let lease_duration = Duration::from_secs(lease_secs);
+    tokio::spawn(async move {
+        let mut last_renewed = Instant::now();
+        loop {
+            tokio::time::sleep(heartbeat_interval).await;
+            match renew_worker_lock(get_pool().await, AGGREGATOR_LOCK, get_worker_id(), lease_secs).await {
+                Ok(true) => {
+                    info!("renewed aggregator lock");
+                    last_renewed = Instant::now();
+                }
+                Ok(false) => {
+                    error!("aggregator lock is no longer held by worker {}", get_worker_id());
+                    return;
+                }
+                Err(e) => error!("error in renewing aggregator lock: {:?}", e),
+            }
+            if last_renewed.elapsed() >= lease_duration {
+                error!("aggregator lock not renewed for {:?} seconds, it may be held by another worker", lease_secs);
+                return;
+            }
+        }
+    })
+}
+
+/*
+    Runs `aggregation`, which must be called right after `acquire_worker_lock` succeeds, while renewing the
+    aggregator lock. The aggregation is dropped as soon as the lock is lost, so that two workers never aggregate
+    the same proofs; its superproof is left InProgress and is resumed or failed on the next worker start.
+ */
+pub async fn run_under_aggregator_lock<F>(lease_secs: u64, aggregation: F) -> AnyhowResult<()>
+where
+    F: Future<Output = AnyhowResult<()>>,
+{
+    let mut heartbeat = spawn_aggregator_lock_heartbeat(lease_secs);
+    let result = tokio::select! {
+        result = aggregation => result,
+        _ = &mut heartbeat => Err(anyhow!(error_line!("aggregator lock lost, aggregation stopped"))),
+    };
+    heartbeat.abort();
+    result
+}
+
+/*
+    Returns tasks with an expired lease back to NotPicked, so that another worker can pick them.
+    The proof keeps its session_id, so the next pick re-attaches to the bonsai session if it is still alive.
+ */
+pub async fn reap_expired_task_leases() -> AnyhowResult<()> {
+    let expired_tasks = get_expired_leased_tasks(get_pool().await).await?;
+    for task in expired_tasks {
+        let task_id = match task.id {
+            Some(id) => id,
+            None => continue,
+        };
+        if !reset_expired_task(get_pool().await, task_id).await? {
+            continue;
+        }
+        info!("lease expired on task {} held by {:?}, task returned to NotPicked", task_id, task.leased_by);
+
+        if task.task_type == TaskType::ProofGeneration {
+            if let Some(proof_id) = task.proof_id {
+                update_proof_status(get_pool().await, proof_id, ProofStatus::Registered).await?;
+            }
+        }
+    }
+    Ok(())
+}
@@ -1,5 +1,6 @@
 
 pub mod aggregator;
+pub mod batch;
 pub mod connection;
 pub mod imt;
 pub mod proof_generator;
@@ -8,3 +9,6 @@ pub mod registration;
 pub static AVAIL_BH: bool = true; // TODO: bh is true for avail; hardcoding for now as we only have avail for this scheme
 pub mod worker;
 pub mod bonsai;
+pub mod prover_backend;
+pub mod lease;
+pub mod resume;
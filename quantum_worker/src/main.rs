@@ -12,9 +12,13 @@
 use std::time::Duration;
 use dotenv::dotenv;
 use tracing::{error, info};
+use quantum_db::webhook::run_webhook_dispatcher;
 use quantum_types::types::config::ConfigData;
 use quantum_utils::logger::initialize_logger;
+use quantum_worker::bonsai::init_bonsai_retry_policy;
 use quantum_worker::connection::get_pool;
+use quantum_worker::lease::get_worker_id;
+use quantum_worker::prover_backend::init_prover_backend;
 use quantum_worker::worker::worker;
 
 #[tokio::main]
@@ -25,6 +29,14 @@ async fn main() {
     let _guard = initialize_logger("qunatum_node_worker.log");
     let config_data = ConfigData::new("./config.yaml");
     let _pool = get_pool().await;
+    init_prover_backend(&config_data);
+    init_bonsai_retry_policy(&config_data);
+    tokio::spawn(run_webhook_dispatcher(
+        get_pool().await,
+        format!("worker-{}", get_worker_id()),
+        Duration::from_secs(config_data.webhook_poll_secs),
+        config_data.retry.webhook.clone(),
+    ));
     let worker_sleep_duration = Duration::from_secs(config_data.worker_sleep_secs);
     match worker(worker_sleep_duration, &config_data).await {
         Ok(_) => {
@@ -0,0 +1,93 @@
+use anyhow::{anyhow, Result as AnyhowResult};
+use async_trait::async_trait;
+use bonsai_sdk::non_blocking::{Client, SessionId, SnarkId};
+use quantum_utils::error_line;
+use risc0_zkvm::Receipt;
+
+use super::{ProverBackend, SessionState};
+
+pub struct BonsaiBackend;
+
+impl BonsaiBackend {
+    pub fn new() -> Self {
+        BonsaiBackend
+    }
+}
+
+pub fn get_bonsai_client() -> AnyhowResult<Client> {
+    let client = Client::from_env(risc0_zkvm::VERSION)?;
+    Ok(client)
+}
+
+#[async_trait]
+impl ProverBackend for BonsaiBackend {
+    async fn upload_input(&self, input_data: Vec<u8>) -> AnyhowResult<String> {
+        let client = get_bonsai_client()?;
+        let input_id = client.upload_input(input_data).await?;
+        Ok(input_id)
+    }
+
+    async fn upload_receipt(&self, receipt: &Receipt) -> AnyhowResult<String> {
+        let client = get_bonsai_client()?;
+        let serialized_receipt = bincode::serialize(receipt)?;
+        let receipt_id = client.upload_receipt(serialized_receipt).await?;
+        Ok(receipt_id)
+    }
+
+    async fn create_session(&self, image_id: &str, input_id: &str, assumptions: &Vec<String>) -> AnyhowResult<String> {
+        let client = get_bonsai_client()?;
+        // Wether to run in execute only mode
+        let execute_only = false;
+        let session = client.create_session(image_id.to_string(), input_id.to_string(), assumptions.clone(), execute_only).await?;
+        Ok(session.uuid)
+    }
+
+    async fn session_status(&self, session_id: &str) -> AnyhowResult<SessionState> {
+        let client = get_bonsai_client()?;
+        let res = SessionId::new(session_id.to_string()).status(&client).await?;
+        match res.status.as_str() {
+            "RUNNING" => Ok(SessionState::Running(res.state)),
+            "SUCCEEDED" => {
+                let stats = res.stats.ok_or(anyhow!(error_line!("missing stats on completed session")))?;
+                Ok(SessionState::Succeeded(stats.cycles))
+            }
+            "FAILED" => Ok(SessionState::Failed(res.error_msg)),
+            _ => Ok(SessionState::Stopped(res.error_msg)),
+        }
+    }
+
+    async fn fetch_receipt(&self, session_id: &str) -> AnyhowResult<Receipt> {
+        let client = get_bonsai_client()?;
+        let res = SessionId::new(session_id.to_string()).status(&client).await?;
+        let receipt_url = res.receipt_url.ok_or(anyhow!(error_line!("API error, missing receipt on completed session")))?;
+        let receipt_buf = client.download(&receipt_url).await?;
+        let receipt: Receipt = bincode::deserialize(&receipt_buf)?;
+        Ok(receipt)
+    }
+
+    async fn create_snark_session(&self, session_id: &str) -> AnyhowResult<String> {
+        let client = get_bonsai_client()?;
+        let snark_session = client.create_snark(session_id.to_string()).await?;
+        Ok(snark_session.uuid)
+    }
+
+    async fn snark_session_status(&self, snark_session_id: &str) -> AnyhowResult<SessionState> {
+        let client = get_bonsai_client()?;
+        let res = SnarkId::new(snark_session_id.to_string()).status(&client).await.map_err(|err| anyhow!(error_line!(err)))?;
+        match res.status.as_str() {
+            "RUNNING" => Ok(SessionState::Running(None)),
+            "SUCCEEDED" => Ok(SessionState::Succeeded(0)),
+            "FAILED" => Ok(SessionState::Failed(res.error_msg)),
+            _ => Ok(SessionState::Stopped(res.error_msg)),
+        }
+    }
+
+    async fn fetch_snark_receipt(&self, snark_session_id: &str) -> AnyhowResult<Receipt> {
+        let client = get_bonsai_client()?;
+        let res = SnarkId::new(snark_session_id.to_string()).status(&client).await.map_err(|err| anyhow!(error_line!(err)))?;
+        let snark_receipt_url = res.output.ok_or(anyhow!(error_line!("API error, missing receipt on completed snark session")))?;
+        let receipt_buf = client.download(&snark_receipt_url).await?;
+        let snark_receipt: Receipt = bincode::deserialize(&receipt_buf)?;
+        Ok(snark_receipt)
+    }
+}
@@ -0,0 +1,182 @@
+use std::{collections::HashMap, sync::{atomic::{AtomicU64, Ordering}, Arc, Mutex}};
+
+use anyhow::{anyhow, Result as AnyhowResult};
+use async_trait::async_trait;
+use quantum_db::repository::bonsai_image::get_bonsai_image_by_image_id;
+use quantum_utils::{error_line, file::read_bytes_from_file};
+use risc0_zkvm::{default_prover, ExecutorEnv, ProverOpts, Receipt};
+use tracing::{error, info};
+
+use crate::connection::get_pool;
+
+use super::{ProverBackend, SessionState};
+
+struct LocalSession {
+    state: SessionState,
+    receipt: Option<Receipt>,
+}
+
+/*
+    In-process risc0 prover, used to run the worker without Bonsai credentials.
+    With RISC0_DEV_MODE=1 the prover returns fake receipts, which is enough for local runs and CI.
+    Sessions live in memory only, so they do not survive a worker restart.
+    Receipts are dropped once a session using them as assumptions succeeds, or once they have been fetched for the last time.
+ */
+pub struct LocalBackend {
+    inputs: Mutex<HashMap<String, Vec<u8>>>,
+    receipts: Arc<Mutex<HashMap<String, Receipt>>>,
+    sessions: Arc<Mutex<HashMap<String, LocalSession>>>,
+    counter: AtomicU64,
+}
+
+impl LocalBackend {
+    pub fn new() -> Self {
+        LocalBackend {
+            inputs: Mutex::new(HashMap::new()),
+            receipts: Arc::new(Mutex::new(HashMap::new())),
+            sessions: Arc::new(Mutex::new(HashMap::new())),
+            counter: AtomicU64::new(0),
+        }
+    }
+
+    fn next_id(&self, prefix: &str) -> String {
+        format!("local-{}-{}", prefix, self.counter.fetch_add(1, Ordering::SeqCst))
+    }
+
+    fn get_assumption_receipt(&self, assumption_id: &str) -> AnyhowResult<Receipt> {
+        if let Some(receipt) = self.receipts.lock().unwrap().get(assumption_id) {
+            return Ok(receipt.clone());
+        }
+        // session ids of previous reductions are used as assumptions in aggregation
+        match self.sessions.lock().unwrap().get(assumption_id).and_then(|s| s.receipt.clone()) {
+            Some(receipt) => Ok(receipt),
+            None => Err(anyhow!(error_line!(format!("unknown assumption: {}", assumption_id)))),
+        }
+    }
+
+    // `consumed_ids` are the receipts and sessions the session proves on top of, they are dropped once it succeeds
+    fn spawn_session<F>(&self, session_id: &str, consumed_ids: Vec<String>, prove: F)
+    where
+        F: FnOnce() -> AnyhowResult<(Receipt, u64)> + Send + 'static,
+    {
+        self.sessions.lock().unwrap().insert(session_id.to_string(), LocalSession { state: SessionState::Running(None), receipt: None });
+
+        let receipts = self.receipts.clone();
+        let sessions = self.sessions.clone();
+        let session_id = session_id.to_string();
+        tokio::spawn(async move {
+            let result = tokio::task::spawn_blocking(prove).await;
+            let session = match result {
+                Ok(Ok((receipt, cycle_used))) => {
+                    info!("local session {} completed", session_id);
+                    // kept until now so a retry after a failed session can still use them
+                    for consumed_id in &consumed_ids {
+                        receipts.lock().unwrap().remove(consumed_id);
+                        sessions.lock().unwrap().remove(consumed_id);
+                    }
+                    LocalSession { state: SessionState::Succeeded(cycle_used), receipt: Some(receipt) }
+                }
+                Ok(Err(e)) => {
+                    error!("local session {} failed: {:?}", session_id, e);
+                    LocalSession { state: SessionState::Failed(Some(e.to_string())), receipt: None }
+                }
+                Err(e) => {
+                    error!("local session {} panicked: {:?}", session_id, e);
+                    LocalSession { state: SessionState::Stopped(Some(e.to_string())), receipt: None }
+                }
+            };
+            sessions.lock().unwrap().insert(session_id, session);
+        });
+    }
+
+    fn get_session_state(&self, session_id: &str) -> AnyhowResult<SessionState> {
+        let mut sessions = self.sessions.lock().unwrap();
+        match sessions.get(session_id).map(|session| session.state.clone()) {
+            // failed sessions have no receipt to hand out, so they are dropped once their state is reported
+            Some(state @ SessionState::Failed(_)) | Some(state @ SessionState::Stopped(_)) => {
+                sessions.remove(session_id);
+                Ok(state)
+            }
+            Some(state) => Ok(state),
+            // sessions of a previous worker run are gone, report them as stopped so a new one gets created
+            None => Ok(SessionState::Stopped(Some(format!("local session not found: {}", session_id)))),
+        }
+    }
+
+    fn get_session_receipt(&self, session_id: &str) -> AnyhowResult<Receipt> {
+        match self.sessions.lock().unwrap().get(session_id).and_then(|s| s.receipt.clone()) {
+            Some(receipt) => Ok(receipt),
+            None => Err(anyhow!(error_line!(format!("missing receipt for local session: {}", session_id)))),
+        }
+    }
+}
+
+fn prove_locally(elf: Vec<u8>, input_data: Vec<u8>, assumptions: Vec<Receipt>) -> AnyhowResult<(Receipt, u64)> {
+    let mut builder = ExecutorEnv::builder();
+    for assumption in assumptions {
+        builder.add_assumption(assumption);
+    }
+    let env = builder.write_slice(&input_data).build()?;
+    let prove_info = default_prover().prove_with_opts(env, &elf, &ProverOpts::succinct())?;
+    Ok((prove_info.receipt, prove_info.stats.total_cycles))
+}
+
+#[async_trait]
+impl ProverBackend for LocalBackend {
+    async fn upload_input(&self, input_data: Vec<u8>) -> AnyhowResult<String> {
+        let input_id = self.next_id("input");
+        self.inputs.lock().unwrap().insert(input_id.clone(), input_data);
+        Ok(input_id)
+    }
+
+    async fn upload_receipt(&self, receipt: &Receipt) -> AnyhowResult<String> {
+        let receipt_id = self.next_id("receipt");
+        self.receipts.lock().unwrap().insert(receipt_id.clone(), receipt.clone());
+        Ok(receipt_id)
+    }
+
+    async fn create_session(&self, image_id: &str, input_id: &str, assumptions: &Vec<String>) -> AnyhowResult<String> {
+        let bonsai_image = get_bonsai_image_by_image_id(get_pool().await, image_id).await?;
+        let elf = read_bytes_from_file(&bonsai_image.elf_file_path)?;
+        let input_data = self.inputs.lock().unwrap().remove(input_id)
+            .ok_or(anyhow!(error_line!(format!("unknown input id: {}", input_id))))?;
+        let mut assumption_receipts = vec![];
+        for assumption in assumptions {
+            assumption_receipts.push(self.get_assumption_receipt(assumption)?);
+        }
+
+        let session_id = self.next_id("session");
+        self.spawn_session(&session_id, assumptions.clone(), move || prove_locally(elf, input_data, assumption_receipts));
+        Ok(session_id)
+    }
+
+    async fn session_status(&self, session_id: &str) -> AnyhowResult<SessionState> {
+        self.get_session_state(session_id)
+    }
+
+    async fn fetch_receipt(&self, session_id: &str) -> AnyhowResult<Receipt> {
+        self.get_session_receipt(session_id)
+    }
+
+    async fn create_snark_session(&self, session_id: &str) -> AnyhowResult<String> {
+        let receipt = self.get_session_receipt(session_id)?;
+        let snark_session_id = self.next_id("snark");
+        self.spawn_session(&snark_session_id, vec![session_id.to_string()], move || {
+            let snark_receipt = default_prover().compress(&ProverOpts::groth16(), &receipt)?;
+            Ok((snark_receipt, 0))
+        });
+        Ok(snark_session_id)
+    }
+
+    async fn snark_session_status(&self, snark_session_id: &str) -> AnyhowResult<SessionState> {
+        self.get_session_state(snark_session_id)
+    }
+
+    async fn fetch_snark_receipt(&self, snark_session_id: &str) -> AnyhowResult<Receipt> {
+        // nothing proves on top of the snark receipt, so its session is done once it is fetched
+        match self.sessions.lock().unwrap().remove(snark_session_id).and_then(|s| s.receipt) {
+            Some(receipt) => Ok(receipt),
+            None => Err(anyhow!(error_line!(format!("missing receipt for local snark session: {}", snark_session_id)))),
+        }
+    }
+}
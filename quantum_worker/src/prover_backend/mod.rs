@@ -0,0 +1,53 @@
+pub mod bonsai_backend;
+pub mod local_backend;
+
+use anyhow::Result as AnyhowResult;
+use async_trait::async_trait;
+use bonsai_backend::BonsaiBackend;
+use local_backend::LocalBackend;
+use once_cell::sync::OnceCell;
+use quantum_types::{enums::prover_backend::ProverBackendType, types::config::ConfigData};
+use risc0_zkvm::Receipt;
+use tracing::info;
+
+static PROVER_BACKEND: OnceCell<Box<dyn ProverBackend>> = OnceCell::new();
+
+// `Failed` is a session whose guest failed on its input, `Stopped` one timed out or aborted by the prover
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SessionState {
+    Running(Option<String>),
+    Succeeded(u64),
+    Failed(Option<String>),
+    Stopped(Option<String>),
+}
+
+/*
+    Proving service used by the worker for reduction, aggregation and stark2snark.
+    Session ids returned here are what gets stored in the proof/superproof tables.
+ */
+#[async_trait]
+pub trait ProverBackend: Send + Sync {
+    async fn upload_input(&self, input_data: Vec<u8>) -> AnyhowResult<String>;
+    async fn upload_receipt(&self, receipt: &Receipt) -> AnyhowResult<String>;
+    async fn create_session(&self, image_id: &str, input_id: &str, assumptions: &Vec<String>) -> AnyhowResult<String>;
+    async fn session_status(&self, session_id: &str) -> AnyhowResult<SessionState>;
+    async fn fetch_receipt(&self, session_id: &str) -> AnyhowResult<Receipt>;
+    async fn create_snark_session(&self, session_id: &str) -> AnyhowResult<String>;
+    async fn snark_session_status(&self, snark_session_id: &str) -> AnyhowResult<SessionState>;
+    async fn fetch_snark_receipt(&self, snark_session_id: &str) -> AnyhowResult<Receipt>;
+}
+
+pub fn init_prover_backend(config: &ConfigData) {
+    let backend: Box<dyn ProverBackend> = match config.prover_backend {
+        ProverBackendType::Bonsai => Box::new(BonsaiBackend::new()),
+        ProverBackendType::Local => Box::new(LocalBackend::new()),
+    };
+    if PROVER_BACKEND.set(backend).is_ok() {
+        info!("prover backend initialised: {:?}", config.prover_backend);
+    }
+}
+
+// falls back to bonsai when the worker has not initialised a backend (e.g. in tests)
+pub fn get_prover_backend() -> &'static dyn ProverBackend {
+    PROVER_BACKEND.get_or_init(|| Box::new(BonsaiBackend::new())).as_ref()
+}
@@ -0,0 +1,104 @@
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result as AnyhowResult};
+use quantum_db::repository::{
+    proof_repository::{clear_superproof_id_in_proof, get_proofs_in_superproof_id, update_proof_status},
+    superproof_repository::{get_in_flight_superproofs, get_orphaned_superproofs, update_superproof_status},
+    task_repository::{get_orphaned_reduction_tasks, take_over_task_lease},
+    user_circuit_data_repository::get_user_circuit_data_by_circuit_hash,
+};
+use quantum_types::{dispatch_scheme, enums::{proof_status::ProofStatus, superproof_status::SuperproofStatus}, types::config::ConfigData};
+use quantum_utils::error_line;
+use tokio::{sync::Semaphore, time::Instant};
+use tracing::{error, info};
+
+use crate::{connection::get_pool, lease::get_worker_id, worker::{handle_aggregate_proof_task, spawn_proof_generation_task}};
+
+/*
+    Picks up reduction tasks left InProgress by a dead worker whose proof already has a bonsai session.
+    `execute_proof_reduction` re-attaches to the stored session_id, and only creates a new session if the old one failed.
+    Tasks leased by this worker id are resumed right away, so run workers with a stable WORKER_ID.
+ */
+pub async fn resume_in_flight_reductions(semaphore: Arc<Semaphore>, config_data: &ConfigData) -> AnyhowResult<()> {
+    let orphaned_tasks = get_orphaned_reduction_tasks(get_pool().await, get_worker_id()).await?;
+    info!("in-flight reduction tasks to resume: {:?}", orphaned_tasks.len());
+    for task in orphaned_tasks {
+        let task_id = match task.id {
+            Some(id) => id,
+            None => continue,
+        };
+        if !take_over_task_lease(get_pool().await, task_id, get_worker_id(), config_data.task_lease_secs).await? {
+            info!("task {:?} already resumed by another worker", task_id);
+            continue;
+        }
+
+        let permit = semaphore.clone().acquire_owned().await.map_err(|e| anyhow!(error_line!(format!("error in acquiring the semaphore: {:?}", e))))?;
+        info!("Resuming proof generation task --> {:?}", task);
+        spawn_proof_generation_task(task, config_data.clone(), permit, Instant::now());
+    }
+    Ok(())
+}
+
+/*
+    Fails InProgress superproofs whose worker died before their aggregation session was created, there is nothing
+    to re-attach to. Otherwise they would stay InProgress forever, blocking the contract submitter and counting
+    against `max_superproofs_in_flight`. Their proofs go back to Reduced without counting an aggregation attempt.
+ */
+pub async fn fail_orphaned_superproofs() -> AnyhowResult<()> {
+    let superproofs = get_orphaned_superproofs(get_pool().await).await?;
+    info!("superproofs orphaned before their aggregation session: {:?}", superproofs.len());
+    for superproof in superproofs {
+        let superproof_id = match superproof.id {
+            Some(id) => id,
+            None => continue,
+        };
+
+        let proofs = get_proofs_in_superproof_id(get_pool().await, superproof_id).await?;
+        for proof in proofs {
+            let proof_id = match proof.id {
+                Some(id) => id,
+                None => continue,
+            };
+            clear_superproof_id_in_proof(get_pool().await, proof_id).await?;
+            update_proof_status(get_pool().await, proof_id, ProofStatus::Reduced).await?;
+        }
+        error!("superproof_id {:?} has no aggregation session, marking it as failed", superproof_id);
+        update_superproof_status(get_pool().await, SuperproofStatus::Failed, superproof_id).await?;
+    }
+    Ok(())
+}
+
+/*
+    Re-runs aggregation for InProgress superproofs which already have an aggregation session.
+    `execute_aggregation` and `run_stark2snark` re-attach to the stored session_id/snark_session_id.
+    The aggregations are awaited one by one, so they run under the aggregator lock held by the caller, like
+    `aggregate_next_superproof`. A failed one is logged and the next one is resumed.
+ */
+pub async fn resume_in_flight_aggregations(config_data: &ConfigData) -> AnyhowResult<()> {
+    let superproofs = get_in_flight_superproofs(get_pool().await).await?;
+    info!("in-flight superproofs to resume: {:?}", superproofs.len());
+    for superproof in superproofs {
+        let superproof_id = match superproof.id {
+            Some(id) => id,
+            None => continue,
+        };
+
+        let proofs = get_proofs_in_superproof_id(get_pool().await, superproof_id).await?;
+        let mut proofs_r0 = vec![];
+        let mut proofs_sp1 = vec![];
+        for proof in proofs {
+            let user_circuit_data = get_user_circuit_data_by_circuit_hash(get_pool().await, &proof.user_circuit_hash).await?;
+            if dispatch_scheme!(user_circuit_data.proving_scheme, S => S::SP1_AGGREGATED) {
+                proofs_sp1.push(proof);
+            } else {
+                proofs_r0.push(proof);
+            }
+        }
+
+        info!("Resuming aggregation of superproof_id {:?} with session {:?}", superproof_id, superproof.session_id);
+        if let Err(e) = handle_aggregate_proof_task(&proofs_r0, &mut proofs_sp1, config_data, superproof_id).await {
+            error!("resumed aggregation failed for superproof_id {:?}: {:?}", superproof_id, e);
+        }
+    }
+    Ok(())
+}
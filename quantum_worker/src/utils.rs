@@ -106,7 +106,9 @@ pub fn get_agg_inputs_sp1<H: Hasher>(
     // Note: this data will not actually be read by the aggregation program, instead it will be
     // witnessed by the prover during the recursive aggregation process inside SP1 itself.
     for (proof, vkey) in protocol_proofs.iter().zip(protocol_vkeys) {
-        let SP1Proof::Compressed(proof) = proof.proof.clone() else { panic!() };
+        let SP1Proof::Compressed(proof) = proof.proof.clone() else {
+            return Err(anyhow!(error_line!("only compressed sp1 proofs go through the sp1 aggregation")));
+        };
         stdin.write_proof(*proof, vkey.vk);
     }
 
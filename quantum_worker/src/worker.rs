@@ -1,16 +1,16 @@
 use anyhow::{anyhow, Result as AnyhowResult};
 use chrono::Utc;
-use once_cell::sync::Lazy;
 use quantum_db::
     repository::{
         proof_repository::{
-            get_reduced_proofs_r0, get_reduced_proofs_sp1, update_proof_status, update_superproof_id_in_proof
+            clear_superproof_id_in_proof, get_reduced_cycle_count, get_reduced_proofs_r0, get_reduced_proofs_sp1, increment_aggregation_attempts, update_proof_status, update_superproof_id_in_proof
         },
-        superproof_repository::{get_last_aggregated_superproof, get_last_verified_superproof, insert_new_superproof, update_proof_ids_in_superproof, update_superproof_status},
-        task_repository::{get_unpicked_tasks, update_task_status}
+        superproof_repository::{get_last_verified_superproof, get_non_submitted_superproof_count, insert_new_superproof, update_batch_close_reason_in_superproof, update_proof_ids_in_superproof, update_superproof_status},
+        task_repository::{claim_unpicked_tasks, get_in_progress_task_count, release_task_lease, update_task_status},
+        worker_lock_repository::{acquire_worker_lock, release_worker_lock}
     };
 use quantum_types::{
-    enums::{ proof_status::ProofStatus,
+    enums::{ batch_close_reason::BatchCloseReason, proof_status::ProofStatus,
         superproof_status::SuperproofStatus, task_status::TaskStatus, task_type::TaskType,
     },
     types::{
@@ -19,20 +19,13 @@ use quantum_types::{
     },
 };
 use quantum_utils::error_line;
-use tokio::{sync::{Mutex, Semaphore}, time::Instant};
+use tokio::{sync::{OwnedSemaphorePermit, Semaphore}, time::Instant};
 use std::{sync::Arc, thread::sleep, time::Duration};
 use tracing::{error, info};
-use crate::{aggregator::handle_proof_aggregation_and_updation, connection::get_pool};
+use crate::{aggregator::handle_proof_aggregation_and_updation, batch::{is_isolated, select_batch}, bonsai::is_proof_rejected, connection::get_pool, lease::{get_worker_id, reap_expired_task_leases, run_under_aggregator_lock, spawn_task_heartbeat, AGGREGATOR_LOCK}, resume::{fail_orphaned_superproofs, resume_in_flight_aggregations, resume_in_flight_reductions}};
 use crate::proof_generator;
 
 
-pub static GLOBAL_CYCLE_COUNTER: Lazy<Arc<Mutex<i64>>> = Lazy::new(|| Arc::new(Mutex::new(0)));
-pub async fn increment_cycle(cycle_used: i64) {
-    let counter: Arc<Mutex<i64>> = GLOBAL_CYCLE_COUNTER.clone(); // Clone the Arc for shared ownership
-    let mut num = counter.lock().await; // Lock the mutex to safely modify the counter
-    *num += cycle_used;
-}
-
 pub async fn handle_aggregate_proof_task(
     proofs_r0: &Vec<Proof>,
     proofs_sp1: &mut Vec<Proof>,
@@ -76,10 +69,8 @@ pub async fn handle_aggregate_proof_task(
         Err(e) => {
             error!("aggregation_request error {:?}", e);
 
-            // Change proof_generation status to FAILED
-            for proof_id in proof_ids {
-                update_proof_status(get_pool().await, proof_id, ProofStatus::AggregationFailed).await?;
-            }
+            // Send the proofs back to the queue, unless one was rejected alone
+            release_failed_superproof_proofs(proofs_r0, proofs_sp1, config, is_proof_rejected(&e)).await?;
 
             error!("changing the superproof status to failed");
             update_superproof_status(get_pool().await, SuperproofStatus::Failed, superproof_id).await?;
@@ -89,14 +80,56 @@ pub async fn handle_aggregate_proof_task(
     Ok(())
 }
 
+/*
+    Puts the proofs of a failed superproof back in the Reduced queue. Only a rejection of the risc0 aggregation by its
+    guest or receipt verification is blamed on the risc0 proofs and counted, bonsai, snark or sp1 failures are not.
+    Proofs of `aggregation_isolation_attempts` rejected superproofs are aggregated alone by `select_batch`, so the
+    proof rejected alone after that is the one breaking the aggregation, and the only one marked AggregationFailed.
+    Sp1 proofs are never blamed here, failures of the sp1 aggregation are counted by the aggregator.
+ */
+async fn release_failed_superproof_proofs(proofs_r0: &Vec<Proof>, proofs_sp1: &Vec<Proof>, config: &ConfigData, proof_rejected: bool) -> AnyhowResult<()> {
+    let is_isolated_batch = proofs_r0.len() == 1 && is_isolated(&proofs_r0[0], config.aggregation_isolation_attempts);
+    for proof in proofs_r0 {
+        let proof_id = match proof.id {
+            Some(id) => Ok(id),
+            None => Err(anyhow!(error_line!("not able to find proofId"))),
+        };
+        let proof_id = proof_id?;
+        clear_superproof_id_in_proof(get_pool().await, proof_id).await?;
+        if !proof_rejected {
+            info!("proof {:?} back to reduced, the superproof failed for a reason other than its proofs", proof_id);
+            update_proof_status(get_pool().await, proof_id, ProofStatus::Reduced).await?;
+            continue;
+        }
+        let attempts = increment_aggregation_attempts(get_pool().await, proof_id).await?;
+        if is_isolated_batch {
+            error!("proof {:?} was rejected alone after {:?} rejected superproofs, marking it as failed", proof_id, attempts);
+            update_proof_status(get_pool().await, proof_id, ProofStatus::AggregationFailed).await?;
+        } else {
+            info!("proof {:?} back to reduced after {:?} rejected superproofs", proof_id, attempts);
+            update_proof_status(get_pool().await, proof_id, ProofStatus::Reduced).await?;
+        }
+    }
+
+    for proof in proofs_sp1 {
+        let proof_id = match proof.id {
+            Some(id) => Ok(id),
+            None => Err(anyhow!(error_line!("not able to find proofId"))),
+        };
+        let proof_id = proof_id?;
+        clear_superproof_id_in_proof(get_pool().await, proof_id).await?;
+        update_proof_status(get_pool().await, proof_id, ProofStatus::Reduced).await?;
+    }
+    Ok(())
+}
+
 pub async fn handle_proof_generation_task(
     proof_generation_task: Task,
     config: &ConfigData,
 ) -> AnyhowResult<()> {
     let proof_id = proof_generation_task.clone().proof_id.clone().unwrap();
-    // Change Task status to InProgress
-    update_task_status(get_pool().await, proof_generation_task.clone().id.unwrap(), TaskStatus::InProgress).await?;
-    info!("Updated Task Status to InProgress");
+    let task_id = proof_generation_task.clone().id.unwrap();
+    // Task is already InProgress, leased to this worker by `claim_unpicked_tasks`
 
     // Update Proof Status to Reducing
     update_proof_status(get_pool().await, proof_id, ProofStatus::Reducing).await?;
@@ -112,7 +145,10 @@ pub async fn handle_proof_generation_task(
         Some(p) => Ok(p),
     }?;
 
+    // Keep the lease alive while the bonsai session runs
+    let heartbeat = spawn_task_heartbeat(task_id, config.task_lease_secs);
     let request = proof_generator::handle_proof_generation_and_updation(proof_id, &proof_hash, &proof_generation_task.user_circuit_hash, config).await;
+    heartbeat.abort();
 
     match request {
         Ok(_) => {
@@ -121,7 +157,7 @@ pub async fn handle_proof_generation_task(
             info!("Changed proof status to REDUCED");
 
             // Update task status to completed
-            update_task_status(get_pool().await, proof_generation_task.clone().id.unwrap(), TaskStatus::Completed).await?;
+            update_task_status(get_pool().await, task_id, TaskStatus::Completed).await?;
             info!("Changed task status to Completed");
 
             info!("Proof Reduced Successfully");
@@ -132,12 +168,13 @@ pub async fn handle_proof_generation_task(
             info!("Changed Proof Status to FAILED");
 
             // Update task status to failed
-            update_task_status(get_pool().await, proof_generation_task.clone().id.unwrap(), TaskStatus::Failed).await?;
+            update_task_status(get_pool().await, task_id, TaskStatus::Failed).await?;
             info!("Changed Task Status to FAILED");
 
             error!("Proof Reduction Failed: {:?}", e.root_cause().to_string());
         }
     }
+    release_task_lease(get_pool().await, task_id).await?;
 
     Ok(())
 }
@@ -145,6 +182,7 @@ pub async fn handle_proof_generation_task(
 pub async fn aggregate_and_generate_new_superproof(
     aggregation_awaiting_proofs_r0: &Vec<Proof>, 
     aggregation_awaiting_proofs_sp1: &mut Vec<Proof>, 
+    batch_close_reason: BatchCloseReason,
     config_data: &ConfigData) -> AnyhowResult<()>
 {
     // INSERT NEW SUPERPROOF RECORD
@@ -168,6 +206,8 @@ pub async fn aggregate_and_generate_new_superproof(
 
     let superproof_id = insert_new_superproof(get_pool().await, SuperproofStatus::InProgress).await?;
     info!("added new superproof record => superproof_id={}",superproof_id);
+    update_batch_close_reason_in_superproof(get_pool().await, batch_close_reason, superproof_id).await?;
+    info!("risc0 batch of superproof_id={} closed by {}", superproof_id, batch_close_reason.to_string());
 
 
     for proof_id in proof_ids.clone() {
@@ -183,88 +223,127 @@ pub async fn aggregate_and_generate_new_superproof(
     Ok(())
 }
 
+pub fn spawn_proof_generation_task(task: Task, config_data: ConfigData, permit: OwnedSemaphorePermit, start: Instant) {
+    let task_id = task.id;
+    let handle = tokio::spawn(async move {
+        let result = handle_proof_generation_task(task, &config_data).await;
+        // Release the permit when the task is done
+        drop(permit);
+        result
+    });
+
+    tokio::spawn(async move {
+        
+        match handle.await {
+            Ok(Ok(())) => {
+                info!("Task {:?} finished successfully.", task_id);
+                let total_time = start.elapsed().as_secs();
+                info!("total time taken for 5 proofs: {:?}", total_time);
+            }
+            Ok(Err(e)) => {
+                error!("Task {:?} failed with error: {:?}, error in task updation", task_id, e);
+            }
+            Err(join_err) => {
+                error!("Failed to join task {:?}: {:?}", task_id, join_err);
+            }
+        }
+    });
+}
+
+/*
+    Aggregates the next superproof once the previous one has been on chain for `aggregation_wait_time`.
+    Called through `run_under_aggregator_lock`, which stops it if the lock is lost, so the proofs read here
+    are not picked by another worker meanwhile.
+ */
+async fn aggregate_next_superproof(semaphore: Arc<Semaphore>, config_data: &ConfigData) -> AnyhowResult<()> {
+    let last_verified_superproof = get_last_verified_superproof(get_pool().await).await?;
+    // Get reduced proofs through RISC0 stream
+    // Get reduced proofs through SP1 stream
+    // one proof over max_batch_size tells a full batch apart from a drained queue
+    let reduced_r0_proofs = get_reduced_proofs_r0(get_pool().await, config_data.max_batch_size + 1).await?;
+    let (aggregation_awaiting_r0_proofs, batch_close_reason) = select_batch(reduced_r0_proofs, config_data.max_batch_size, config_data.max_batch_cycle_count, config_data.aggregation_isolation_attempts);
+    let mut aggregation_awaitin_sp1_proofs = get_reduced_proofs_sp1(get_pool().await, config_data.sp1_max_batch_size).await?;
+    // aggregation awaitin r0 proofs
+    // aggregation awaitin sp1 proofs
+    // superproof N+1 is aggregated while N waits for onchain submission, up to max_superproofs_in_flight
+    let non_submitted_superproofs = get_non_submitted_superproof_count(get_pool().await).await?;
+    let total_aggregation_awaiting_proofs = aggregation_awaiting_r0_proofs.len() + aggregation_awaitin_sp1_proofs.len();
+    println!(
+        "Aggregation awaiting proofs {:?}",
+        total_aggregation_awaiting_proofs
+    );
+    if last_verified_superproof.is_some() && total_aggregation_awaiting_proofs > 0 && non_submitted_superproofs < config_data.max_superproofs_in_flight {
+        let last_verified_superproof = last_verified_superproof.unwrap(); // safe to use unwrap here, already check 
+        let last_superproof_onchain_time = match last_verified_superproof.onchain_submission_time {
+            Some(t) => Ok(t),
+            None => Err(anyhow!(error_line!("onchain verified time field missing in last verified superproof"))),
+        }?;
+        let next_agg_start_time = last_superproof_onchain_time + Duration::from_secs(config_data.aggregation_wait_time);
+        let remaining_time = next_agg_start_time - Utc::now().naive_utc();
+        println!("remaining time for agg start: {:?} seconds", remaining_time.num_seconds());
+        if next_agg_start_time <= Utc::now().naive_utc() {
+            let permit: tokio::sync::OwnedSemaphorePermit = semaphore.clone().acquire_owned().await.map_err(|e| anyhow!(error_line!(format!("error in acquiring the semaphore: {:?}", e))))?;
+            info!("Picked up Proofs aggregation");
+
+            let aggregation_result = aggregate_and_generate_new_superproof(&aggregation_awaiting_r0_proofs, &mut aggregation_awaitin_sp1_proofs, batch_close_reason, config_data).await;
+            drop(permit);
+            aggregation_result?;
+        }
+    }
+    Ok(())
+}
+
 pub async fn worker(sleep_duration: Duration, config_data: &ConfigData) -> AnyhowResult<()> {
     let semaphore = Arc::new(Semaphore::new(config_data.parallel_bonsai_session_limit as usize));
+
+    // Re-attach to bonsai sessions left behind by a previous run before picking new work
+    resume_in_flight_reductions(semaphore.clone(), config_data).await?;
+    if acquire_worker_lock(get_pool().await, AGGREGATOR_LOCK, get_worker_id(), config_data.task_lease_secs).await? {
+        let resume_result = run_under_aggregator_lock(config_data.task_lease_secs, async {
+            fail_orphaned_superproofs().await?;
+            resume_in_flight_aggregations(config_data).await
+        }).await;
+        release_worker_lock(get_pool().await, AGGREGATOR_LOCK, get_worker_id()).await?;
+        resume_result?;
+    }
+
     loop {
         println!("Running worker loop");
-        let last_verified_superproof = get_last_verified_superproof(get_pool().await).await?;
-        // Get reduced proofs through RISC0 stream
-        // Get reduced proofs through SP1 stream
-        let aggregation_awaiting_r0_proofs = get_reduced_proofs_r0(get_pool().await).await?;
-        let mut aggregation_awaitin_sp1_proofs = get_reduced_proofs_sp1(get_pool().await, 5).await?;
-        // aggregation awaitin r0 proofs
-        // aggregation awaitin sp1 proofs
-        let last_agg_superproof = get_last_aggregated_superproof(get_pool().await).await?;
-        let total_aggregation_awaiting_proofs = aggregation_awaiting_r0_proofs.len() + aggregation_awaitin_sp1_proofs.len();
-        println!(
-            "Aggregation awaiting proofs {:?}",
-            total_aggregation_awaiting_proofs
-        );
-        if last_verified_superproof.is_some() && total_aggregation_awaiting_proofs > 0 && last_agg_superproof.is_none(){
-            let last_verified_superproof = last_verified_superproof.unwrap(); // safe to use unwrap here, already check 
-            let last_superproof_onchain_time = match last_verified_superproof.onchain_submission_time {
-                Some(t) => Ok(t),
-                None => Err(anyhow!(error_line!("onchain verified time field missing in last verified superproof"))),
-            }?;
-            let next_agg_start_time = last_superproof_onchain_time + Duration::from_secs(config_data.aggregation_wait_time);
-            let remaining_time = next_agg_start_time - Utc::now().naive_utc();
-            println!("remaining time for agg start: {:?} seconds", remaining_time.num_seconds());
-            if next_agg_start_time <= Utc::now().naive_utc() {
-                let permit: tokio::sync::OwnedSemaphorePermit = semaphore.clone().acquire_owned().await.map_err(|e| anyhow!(error_line!(format!("error in acquiring the semaphore: {:?}", e))))?;
-                info!("Picked up Proofs aggregation");
-
-                aggregate_and_generate_new_superproof(&aggregation_awaiting_r0_proofs, &mut aggregation_awaitin_sp1_proofs, config_data).await?;
-
-                increment_cycle(-1 as i64 * (config_data.pr_batch_max_cycle_count as i64)).await;
-                let final_value = *GLOBAL_CYCLE_COUNTER.lock().await;
-                info!("current cycle used count: {:?}", final_value);
-                drop(permit);
-            }
+        if acquire_worker_lock(get_pool().await, AGGREGATOR_LOCK, get_worker_id(), config_data.task_lease_secs).await? {
+            let aggregation_result = run_under_aggregator_lock(config_data.task_lease_secs, aggregate_next_superproof(semaphore.clone(), config_data)).await;
+            release_worker_lock(get_pool().await, AGGREGATOR_LOCK, get_worker_id()).await?;
+            aggregation_result?;
+        } else {
+            info!("aggregator lock held by another worker, skipping aggregation");
         }
 
-        let current_available_permits: u64 = semaphore.clone().available_permits() as u64;
-        let unpicked_tasks = get_unpicked_tasks(get_pool().await, current_available_permits).await?;
+        // Re-queue tasks whose worker stopped heartbeating
+        reap_expired_task_leases().await?;
+
+        // reduce at most one batch worth of cycles ahead of aggregation
+        let reduced_cycle_count = get_reduced_cycle_count(get_pool().await).await?;
+        info!("current cycle used count: {:?}", reduced_cycle_count);
+        if reduced_cycle_count >= config_data.pr_batch_max_cycle_count {
+            info!("cycle count for current batch exceeds the limit");
+            sleep(sleep_duration);
+            continue;
+        }
+
+        // the bonsai session limit is shared by every worker
+        let in_progress_tasks = get_in_progress_task_count(get_pool().await, TaskType::ProofGeneration).await?;
+        let current_available_permits: u64 = std::cmp::min(
+            semaphore.clone().available_permits() as u64,
+            config_data.parallel_bonsai_session_limit.saturating_sub(in_progress_tasks),
+        );
+        let claimed_tasks = claim_unpicked_tasks(get_pool().await, TaskType::ProofGeneration, get_worker_id(), current_available_permits, config_data.task_lease_secs).await?;
         let start =  Instant::now();
-        info!("unpicked task count: {:?} and current available permit: {:?}", unpicked_tasks.len(), current_available_permits);
-        for t in unpicked_tasks {
-            if t.task_type == TaskType::ProofGeneration {
-                let permit: tokio::sync::OwnedSemaphorePermit = semaphore.clone().acquire_owned().await.map_err(|e| anyhow!(error_line!(format!("error in acquiring the semaphore: {:?}", e))))?;
-                
-                info!("Picked up proof generation task --> {:?}", t);
-                let config_data_clone = config_data.clone();
-                let task = t.clone();
-
-                let final_value = *GLOBAL_CYCLE_COUNTER.lock().await;
-                info!("current cycle used count: {:?}", final_value);
-                if final_value >= 0  && final_value as u64 >= config_data_clone.pr_batch_max_cycle_count {
-                    info!("cycle count for current batch exceeds the limit");
-                    continue;
-                }
-                
-                let handle = tokio::spawn(async move {
-                    let result = handle_proof_generation_task(task, &config_data_clone).await;
-                    // Release the permit when the task is done
-                    drop(permit);
-                    result
-                });
-
-                tokio::spawn(async move {
-                    
-                    match handle.await {
-                        Ok(Ok(())) => {
-                            info!("Task {:?} finished successfully.", t.id);
-                            let total_time = start.elapsed().as_secs();
-                            info!("total time taken for 5 proofs: {:?}", total_time);
-                        }
-                        Ok(Err(e)) => {
-                            error!("Task {:?} failed with error: {:?}, error in task updation", t.id, e);
-                        }
-                        Err(join_err) => {
-                            error!("Failed to join task {:?}: {:?}", t.id, join_err);
-                        }
-                    }
-                });
-            }
+        info!("claimed task count: {:?} and current available permit: {:?}", claimed_tasks.len(), current_available_permits);
+        for task in claimed_tasks {
+            // Task is InProgress and leased to this worker by `claim_unpicked_tasks`
+            let permit: tokio::sync::OwnedSemaphorePermit = semaphore.clone().acquire_owned().await.map_err(|e| anyhow!(error_line!(format!("error in acquiring the semaphore: {:?}", e))))?;
+
+            info!("Picked up proof generation task --> {:?}", task);
+            spawn_proof_generation_task(task, config_data.clone(), permit, start);
         }
         
         sleep(sleep_duration);
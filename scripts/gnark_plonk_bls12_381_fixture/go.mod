@@ -0,0 +1,8 @@
+module github.com/Electron-Labs/quantum-node/scripts/gnark_plonk_bls12_381_fixture
+
+go 1.21
+
+require (
+	github.com/consensys/gnark v0.10.0
+	github.com/consensys/gnark-crypto v0.12.2-0.20240215234832-d72fcb379d3e
+)
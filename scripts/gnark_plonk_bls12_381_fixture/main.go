@@ -0,0 +1,94 @@
+// Writes the gnark plonk bls12-381 fixtures read by the quantum_types tests:
+// `go mod tidy && go run . ../../quantum_types/test_data/gnark_plonk_bls12_381`
+package main
+
+import (
+	"encoding/json"
+	"log"
+	"os"
+	"path/filepath"
+
+	"github.com/consensys/gnark-crypto/ecc"
+	"github.com/consensys/gnark/backend/plonk"
+	"github.com/consensys/gnark/frontend"
+	"github.com/consensys/gnark/frontend/cs/scs"
+	"github.com/consensys/gnark/test/unsafekzg"
+)
+
+// x * x == y, with a bsb22 commitment so that the commitment part of the verifier is covered too
+type squareCircuit struct {
+	X frontend.Variable
+	Y frontend.Variable `gnark:",public"`
+}
+
+func (c *squareCircuit) Define(api frontend.API) error {
+	api.AssertIsEqual(api.Mul(c.X, c.X), c.Y)
+	commitment, err := api.(frontend.Committer).Commit(c.X, c.Y)
+	if err != nil {
+		return err
+	}
+	api.AssertIsDifferent(commitment, 0)
+	return nil
+}
+
+func writeFile(path string, write func(f *os.File) error) {
+	f, err := os.Create(path)
+	if err != nil {
+		log.Fatal(err)
+	}
+	defer f.Close()
+	if err := write(f); err != nil {
+		log.Fatal(err)
+	}
+}
+
+func main() {
+	if len(os.Args) != 2 {
+		log.Fatal("usage: go run . <output dir>")
+	}
+	outDir := os.Args[1]
+	if err := os.MkdirAll(outDir, 0o755); err != nil {
+		log.Fatal(err)
+	}
+
+	ccs, err := frontend.Compile(ecc.BLS12_381.ScalarField(), scs.NewBuilder, &squareCircuit{})
+	if err != nil {
+		log.Fatal(err)
+	}
+	srs, srsLagrange, err := unsafekzg.NewSRS(ccs)
+	if err != nil {
+		log.Fatal(err)
+	}
+	pk, vk, err := plonk.Setup(ccs, srs, srsLagrange)
+	if err != nil {
+		log.Fatal(err)
+	}
+
+	witness, err := frontend.NewWitness(&squareCircuit{X: 3, Y: 9}, ecc.BLS12_381.ScalarField())
+	if err != nil {
+		log.Fatal(err)
+	}
+	proof, err := plonk.Prove(ccs, pk, witness)
+	if err != nil {
+		log.Fatal(err)
+	}
+	publicWitness, err := witness.Public()
+	if err != nil {
+		log.Fatal(err)
+	}
+	if err := plonk.Verify(proof, vk, publicWitness); err != nil {
+		log.Fatal(err)
+	}
+
+	writeFile(filepath.Join(outDir, "vk.bin"), func(f *os.File) error {
+		_, err := vk.WriteTo(f)
+		return err
+	})
+	writeFile(filepath.Join(outDir, "proof.bin"), func(f *os.File) error {
+		_, err := proof.WriteTo(f)
+		return err
+	})
+	writeFile(filepath.Join(outDir, "pis.json"), func(f *os.File) error {
+		return json.NewEncoder(f).Encode([]string{"9"})
+	})
+}
@@ -0,0 +1,9 @@
+// Commits n and n * n, the public values checked by the quantum_types sp1 wrapped tests
+#![no_main]
+sp1_zkvm::entrypoint!(main);
+
+pub fn main() {
+    let n = sp1_zkvm::io::read::<u32>();
+    sp1_zkvm::io::commit(&n);
+    sp1_zkvm::io::commit(&(n * n));
+}
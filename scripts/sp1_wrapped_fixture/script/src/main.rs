@@ -0,0 +1,28 @@
+// Writes the sp1 wrapped fixtures read by the quantum_types tests, needs the sp1 toolchain and docker for the wrapper circuits:
+// `cargo run --release -- ../../../quantum_types/test_data/sp1_wrapped`
+use std::{env, fs, path::Path};
+
+use sp1_sdk::{include_elf, ProverClient, SP1Stdin};
+
+const FIXTURE_ELF: &[u8] = include_elf!("sp1-wrapped-fixture-program");
+
+fn main() {
+    let out_dir = env::args().nth(1).expect("usage: sp1-wrapped-fixture <out_dir>");
+    let out_dir = Path::new(&out_dir);
+    fs::create_dir_all(out_dir).unwrap();
+
+    let client = ProverClient::from_env();
+    let (pk, vk) = client.setup(FIXTURE_ELF);
+
+    let mut stdin = SP1Stdin::new();
+    stdin.write(&3u32);
+
+    let groth16_proof = client.prove(&pk, &stdin).groth16().run().unwrap();
+    client.verify(&groth16_proof, &vk).unwrap();
+    let plonk_proof = client.prove(&pk, &stdin).plonk().run().unwrap();
+    client.verify(&plonk_proof, &vk).unwrap();
+
+    fs::write(out_dir.join("vkey.bin"), bincode::serialize(&vk).unwrap()).unwrap();
+    fs::write(out_dir.join("groth16.bin"), bincode::serialize(&groth16_proof).unwrap()).unwrap();
+    fs::write(out_dir.join("plonk.bin"), bincode::serialize(&plonk_proof).unwrap()).unwrap();
+}